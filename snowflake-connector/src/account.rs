@@ -0,0 +1,148 @@
+//! Account identifiers in either format Snowflake accepts, see [`AccountIdentifier`].
+//!
+//! ```no_run
+//! # use snowflake_connector::{account::AccountIdentifier, SnowflakeConnector};
+//! # fn example() -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! // Also accepts the legacy locator, ex. `xy12345.us-east-2.aws`, or the URL of the account.
+//! let account: AccountIdentifier = "myorg-my_account".parse()?;
+//! let connector = SnowflakeConnector::try_new_with_account(
+//!     "PUBLIC/KEY/PATH",
+//!     "PRIVATE/KEY/PATH",
+//!     &account,
+//!     "USER@EXAMPLE.COM".into(),
+//! )?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fmt, path::Path, str::FromStr};
+
+use crate::{errors::SnowflakeError, SnowflakeConnector};
+
+/// Account a connector signs in to, which decides both its host and the account of its token.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AccountIdentifier {
+    /// `ORGNAME-ACCOUNT_NAME`, the account name may contain underscores
+    /// which are written as hyphens in its host, ex. `myorg-my-account.snowflakecomputing.com`.
+    Organization { organization: String, account: String },
+    /// Legacy account locator, with the region and cloud of accounts outside of AWS US West (Oregon),
+    /// ex. `xy12345.us-east-2.aws`, the token only names the locator.
+    Locator { locator: String, region: Option<String> },
+}
+
+impl AccountIdentifier {
+    /// Subdomain of `snowflakecomputing.com` the account is reached at.
+    pub fn host(&self) -> String {
+        match self {
+            AccountIdentifier::Organization { organization, account } => {
+                format!("{organization}-{}", account.replace('_', "-")).to_ascii_lowercase()
+            },
+            AccountIdentifier::Locator { locator, region: Some(region) } => format!("{locator}.{region}").to_ascii_lowercase(),
+            AccountIdentifier::Locator { locator, region: None } => locator.to_ascii_lowercase(),
+        }
+    }
+    /// Account of the issuer and subject of the key pair token, in the upper case Snowflake compares it in.
+    pub fn token_account(&self) -> String {
+        match self {
+            AccountIdentifier::Organization { organization, account } => format!("{organization}-{account}"),
+            AccountIdentifier::Locator { locator, .. } => locator.clone(),
+        }
+        .to_ascii_uppercase()
+    }
+}
+
+impl fmt::Display for AccountIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AccountIdentifier::Organization { organization, account } => write!(f, "{organization}-{account}"),
+            AccountIdentifier::Locator { locator, region: Some(region) } => write!(f, "{locator}.{region}"),
+            AccountIdentifier::Locator { locator, region: None } => f.write_str(locator),
+        }
+    }
+}
+
+/// Parses `ORGNAME-ACCOUNT_NAME` or a locator with an optional region and cloud, ignoring case,
+/// a leading `https://` and a trailing `.snowflakecomputing.com`.
+impl FromStr for AccountIdentifier {
+    type Err = SnowflakeError;
+    fn from_str(s: &str) -> Result<Self, SnowflakeError> {
+        let invalid = |reason: &str| SnowflakeError::InvalidAccountIdentifier(anyhow::anyhow!("{s:?} {reason}"));
+        let identifier = s.trim();
+        let identifier = identifier.strip_prefix("https://").unwrap_or(identifier);
+        let identifier = identifier.trim_end_matches('/');
+        let identifier = identifier.strip_suffix(".snowflakecomputing.com").unwrap_or(identifier);
+        let (name, region) = match identifier.split_once('.') {
+            Some((name, region)) => (name, Some(region)),
+            None => (identifier, None),
+        };
+        if name.is_empty() {
+            return Err(invalid("is empty"));
+        }
+        if let Some((organization, account)) = name.split_once('-') {
+            if region.is_some() {
+                return Err(invalid("has a region, which only account locators have"));
+            }
+            if organization.is_empty() || !organization.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(invalid("has an organization name that is not alphanumeric"));
+            }
+            if account.is_empty() || !account.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+                return Err(invalid("has an account name that is not alphanumeric or underscores"));
+            }
+            return Ok(AccountIdentifier::Organization {
+                organization: organization.to_ascii_uppercase(),
+                account: account.replace('-', "_").to_ascii_uppercase(),
+            });
+        }
+        if !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(invalid("is neither ORGNAME-ACCOUNT_NAME nor an alphanumeric account locator"));
+        }
+        let region = match region {
+            Some(region) if region.split('.').all(|part| {
+                !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            }) => Some(region.to_ascii_lowercase()),
+            Some(_) => return Err(invalid("has an invalid region")),
+            None => None,
+        };
+        Ok(AccountIdentifier::Locator { locator: name.to_ascii_uppercase(), region })
+    }
+}
+
+impl SnowflakeConnector {
+    /// Like [`try_new`](Self::try_new) with the host and the account of the token derived from `account`.
+    pub fn try_new_with_account<P: AsRef<Path>>(
+        public_key_path: P,
+        private_key_path: P,
+        account: &AccountIdentifier,
+        user: String,
+    ) -> Result<Self, SnowflakeError> {
+        SnowflakeConnector::try_new(public_key_path, private_key_path, account.host(), account.token_account(), user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsed() -> Result<(), SnowflakeError> {
+        let account: AccountIdentifier = "myorg-my_account".parse()?;
+        assert_eq!(account, AccountIdentifier::Organization { organization: "MYORG".into(), account: "MY_ACCOUNT".into() });
+        assert_eq!(account.host(), "myorg-my-account");
+        assert_eq!(account.token_account(), "MYORG-MY_ACCOUNT");
+        assert_eq!("https://MyOrg-My-Account.snowflakecomputing.com/".parse::<AccountIdentifier>()?, account);
+
+        let account: AccountIdentifier = "xy12345.US-EAST-2.aws".parse()?;
+        assert_eq!(account.host(), "xy12345.us-east-2.aws");
+        assert_eq!(account.token_account(), "XY12345");
+        assert_eq!(account.to_string(), "XY12345.us-east-2.aws");
+        assert_eq!("xy12345".parse::<AccountIdentifier>()?.host(), "xy12345");
+
+        for invalid in ["", "myorg-", "my_org-account", "myorg-account.us-east-2", "xy_12345", "xy12345..aws"] {
+            assert!(invalid.parse::<AccountIdentifier>().is_err(), "{invalid}");
+        }
+
+        let account: AccountIdentifier = "https://xy12345.us-east-2.aws.snowflakecomputing.com/".parse()?;
+        assert_eq!((account.host().as_str(), account.token_account().as_str()), ("xy12345.us-east-2.aws", "XY12345"));
+        Ok(())
+    }
+}
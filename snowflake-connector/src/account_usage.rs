@@ -0,0 +1,246 @@
+//! Typed rows of `SNOWFLAKE.ACCOUNT_USAGE` for cost reporting, ex. credits per query tag to charge teams for their queries.
+//!
+//! Requires a role granted the `SNOWFLAKE` database's usage views, ex. `GOVERNANCE_VIEWER` or `IMPORTED PRIVILEGES`.
+//! The views lag behind by up to three hours. Time ranges are UTC, `since` inclusive and `until` exclusive.
+//!
+//! ```no_run
+//! # use snowflake_connector::{account_usage::CreditGroup, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let until = chrono::Utc::now().naive_utc();
+//! let since = until - chrono::Duration::days(30);
+//! for usage in connector.execute_on("REPORTING_WH").credits_by(CreditGroup::QueryTag, since, until).await? {
+//!     println!("{}: {:.2} credits over {} queries", usage.key, usage.credits(), usage.queries);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::borrow::Cow;
+
+use chrono::{DateTime, FixedOffset, NaiveDateTime};
+
+use crate::{errors::SnowflakeError, SnowflakeExecutor, SnowflakeSQL};
+
+/// Hour of credits a warehouse used, a row of `WAREHOUSE_METERING_HISTORY`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WarehouseMetering {
+    pub start_time: DateTime<FixedOffset>,
+    pub end_time: DateTime<FixedOffset>,
+    pub warehouse_name: String,
+    pub credits_used: f64,
+    pub credits_used_compute: f64,
+    pub credits_used_cloud_services: f64,
+}
+
+/// A statement that ran, a row of `QUERY_HISTORY`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryHistory {
+    pub query_id: String,
+    /// Empty for statements without a tag, see [`SnowflakeSQL::with_query_tag`].
+    pub query_tag: String,
+    pub user_name: String,
+    pub role_name: Option<String>,
+    /// `None` for statements that did not need a warehouse, ex. `SHOW`.
+    pub warehouse_name: Option<String>,
+    pub warehouse_size: Option<String>,
+    /// Ex. `SUCCESS` or `FAIL`.
+    pub execution_status: String,
+    pub start_time: DateTime<FixedOffset>,
+    /// Milliseconds from compiling to the last row, including time queued.
+    pub total_elapsed_time: u64,
+    /// Milliseconds the warehouse executed the statement.
+    pub execution_time: u64,
+    pub bytes_scanned: u64,
+    pub credits_used_cloud_services: f64,
+}
+
+/// What [`credits_by`](SnowflakeExecutor::credits_by) adds credits up by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CreditGroup {
+    /// Statements without a tag are grouped under an empty key.
+    QueryTag,
+    User,
+}
+
+impl CreditGroup {
+    fn column(self) -> &'static str {
+        match self {
+            CreditGroup::QueryTag => "QUERY_TAG",
+            CreditGroup::User => "USER_NAME",
+        }
+    }
+}
+
+/// Credits of the statements of a query tag or user.
+///
+/// Warehouses are billed per hour rather than per statement, so each hour of a warehouse's compute credits
+/// is split between the statements started in it by their share of its execution time.
+/// Idle time of warehouses is attributed to the statements of the same hour.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CreditUsage {
+    pub key: String,
+    pub queries: u64,
+    pub execution_time: u64,
+    pub credits_compute: f64,
+    pub credits_cloud_services: f64,
+}
+
+impl CreditUsage {
+    pub fn credits(&self) -> f64 {
+        self.credits_compute + self.credits_cloud_services
+    }
+}
+
+impl<'a> SnowflakeExecutor<'a> {
+    /// Hours of warehouse credits between `since` and `until`, ordered by start time.
+    pub async fn warehouse_metering(self, since: NaiveDateTime, until: NaiveDateTime) -> Result<Vec<WarehouseMetering>, SnowflakeError> {
+        let rows = usage(self, "\
+            SELECT START_TIME, END_TIME, WAREHOUSE_NAME, CREDITS_USED, CREDITS_USED_COMPUTE, CREDITS_USED_CLOUD_SERVICES \
+            FROM SNOWFLAKE.ACCOUNT_USAGE.WAREHOUSE_METERING_HISTORY \
+            WHERE START_TIME >= ? AND START_TIME < ? \
+            ORDER BY START_TIME, WAREHOUSE_NAME", since, until)?
+            .select().await?
+            .data;
+        Ok(rows.into_iter()
+            .map(|(start_time, end_time, warehouse_name, credits_used, credits_used_compute, credits_used_cloud_services)| WarehouseMetering {
+                start_time,
+                end_time,
+                warehouse_name,
+                credits_used,
+                credits_used_compute,
+                credits_used_cloud_services,
+            })
+            .collect())
+    }
+    /// Statements started between `since` and `until`, ordered by start time.
+    ///
+    /// Accounts with many statements return many rows, keep the range short or use [`credits_by`](Self::credits_by).
+    pub async fn query_history(self, since: NaiveDateTime, until: NaiveDateTime) -> Result<Vec<QueryHistory>, SnowflakeError> {
+        let rows = usage(self, "\
+            SELECT QUERY_ID, COALESCE(QUERY_TAG, ''), USER_NAME, ROLE_NAME, WAREHOUSE_NAME, WAREHOUSE_SIZE, EXECUTION_STATUS, \
+            START_TIME, TOTAL_ELAPSED_TIME, EXECUTION_TIME, BYTES_SCANNED, CREDITS_USED_CLOUD_SERVICES \
+            FROM SNOWFLAKE.ACCOUNT_USAGE.QUERY_HISTORY \
+            WHERE START_TIME >= ? AND START_TIME < ? \
+            ORDER BY START_TIME", since, until)?
+            .select().await?
+            .data;
+        Ok(rows.into_iter()
+            .map(|(
+                query_id, query_tag, user_name, role_name, warehouse_name, warehouse_size, execution_status,
+                start_time, total_elapsed_time, execution_time, bytes_scanned, credits_used_cloud_services,
+            )| QueryHistory {
+                query_id,
+                query_tag,
+                user_name,
+                role_name,
+                warehouse_name,
+                warehouse_size,
+                execution_status,
+                start_time,
+                total_elapsed_time,
+                execution_time,
+                bytes_scanned,
+                credits_used_cloud_services,
+            })
+            .collect())
+    }
+    /// Credits of the statements started between `since` and `until` added up by `group`, most credits first,
+    /// see [`CreditUsage`] for how compute credits are attributed.
+    pub async fn credits_by(self, group: CreditGroup, since: NaiveDateTime, until: NaiveDateTime) -> Result<Vec<CreditUsage>, SnowflakeError> {
+        let statement = format!("\
+            WITH QUERIES AS (\
+                SELECT COALESCE({key}, '') AS KEY, WAREHOUSE_NAME, DATE_TRUNC('HOUR', START_TIME) AS HOUR, \
+                EXECUTION_TIME, CREDITS_USED_CLOUD_SERVICES, \
+                RATIO_TO_REPORT(EXECUTION_TIME) OVER (PARTITION BY WAREHOUSE_NAME, DATE_TRUNC('HOUR', START_TIME)) AS SHARE \
+                FROM SNOWFLAKE.ACCOUNT_USAGE.QUERY_HISTORY \
+                WHERE START_TIME >= ? AND START_TIME < ?\
+            ) \
+            SELECT Q.KEY, COUNT(*), SUM(Q.EXECUTION_TIME), \
+            SUM(COALESCE(Q.SHARE * M.CREDITS_USED_COMPUTE, 0)) AS CREDITS_COMPUTE, \
+            SUM(Q.CREDITS_USED_CLOUD_SERVICES) AS CREDITS_CLOUD_SERVICES \
+            FROM QUERIES Q \
+            LEFT JOIN SNOWFLAKE.ACCOUNT_USAGE.WAREHOUSE_METERING_HISTORY M \
+            ON M.WAREHOUSE_NAME = Q.WAREHOUSE_NAME AND M.START_TIME = Q.HOUR \
+            GROUP BY Q.KEY \
+            ORDER BY CREDITS_COMPUTE + CREDITS_CLOUD_SERVICES DESC", key = group.column());
+        let rows = usage(self, statement, since, until)?
+            .select().await?
+            .data;
+        Ok(rows.into_iter()
+            .map(|(key, queries, execution_time, credits_compute, credits_cloud_services)| CreditUsage {
+                key,
+                queries,
+                execution_time,
+                credits_compute,
+                credits_cloud_services,
+            })
+            .collect())
+    }
+}
+
+/// `statement` with the range bound, run in UTC so the bound timestamps compare as UTC.
+fn usage<'a>(
+    executor: SnowflakeExecutor<'a>,
+    statement: impl Into<Cow<'a, str>>,
+    since: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Result<SnowflakeSQL<'a>, SnowflakeError> {
+    Ok(executor.statement(statement.into())?
+        .with_timezone("UTC")
+        .add_binding(since)
+        .add_binding(until))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use wiremock::{matchers::{body_string_contains, method}, Mock};
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn credits() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("WAREHOUSE_METERING_HISTORY WHERE"))
+            .respond_with(StatementFixture::new()
+                .column("START_TIME", "timestamp_ltz")
+                .column("END_TIME", "timestamp_ltz")
+                .column("WAREHOUSE_NAME", "text")
+                .column("CREDITS_USED", "real")
+                .column("CREDITS_USED_COMPUTE", "real")
+                .column("CREDITS_USED_CLOUD_SERVICES", "real")
+                .row([Some("1704067200.000"), Some("1704070800.000"), Some("WH"), Some("1.5"), Some("1.25"), Some("0.25")])
+                .success())
+            .with_priority(1)
+            .mount(&snowflake.server).await;
+        snowflake.mount(&StatementFixture::new()
+            .column("KEY", "text")
+            .column("COUNT(*)", "fixed")
+            .column("EXECUTION_TIME", "fixed")
+            .column("CREDITS_COMPUTE", "real")
+            .column("CREDITS_CLOUD_SERVICES", "real")
+            .row([Some("nightly"), Some("12"), Some("60000"), Some("0.75"), Some("0.05")])
+            .row([Some(""), Some("3"), Some("1000"), Some("0.5"), Some("0")])).await;
+        let connector = snowflake.connector();
+        let since = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let until = since + chrono::Duration::days(1);
+
+        let metering = connector.execute_on("WH").warehouse_metering(since, until).await?;
+        assert_eq!((metering[0].warehouse_name.as_str(), metering[0].credits_used), ("WH", 1.5));
+        assert_eq!(metering[0].end_time - metering[0].start_time, chrono::Duration::hours(1));
+
+        let usage = connector.execute_on("WH").credits_by(CreditGroup::QueryTag, since, until).await?;
+        assert_eq!((usage[0].key.as_str(), usage[0].queries), ("nightly", 12));
+        assert!((usage[0].credits() - 0.8).abs() < 1e-9);
+
+        let requests = snowflake.server.received_requests().await.unwrap_or_default();
+        let body: serde_json::Value = serde_json::from_slice(&requests[1].body)?;
+        assert!(body["statement"].as_str().unwrap_or_default().contains("COALESCE(QUERY_TAG, '') AS KEY"), "{body}");
+        assert_eq!(body["parameters"]["TIMEZONE"], "UTC");
+        assert_eq!(body["bindings"]["1"]["type"], "TIMESTAMP_NTZ");
+        Ok(())
+    }
+}
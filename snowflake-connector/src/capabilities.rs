@@ -0,0 +1,99 @@
+//! What the Snowflake release of an account supports, see [`SnowflakeConnector::capabilities`].
+//!
+//! ```no_run
+//! # use snowflake_connector::SnowflakeConnector;
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let capabilities = connector.capabilities().await?;
+//! let column = if capabilities.vector() { "VECTOR(FLOAT, 768)" } else { "ARRAY" };
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use crate::{errors::SnowflakeError, SnowflakeConnector};
+
+/// Release of Snowflake as reported by `CURRENT_VERSION()`, ex. `8.40.1`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SnowflakeVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl SnowflakeVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        SnowflakeVersion { major, minor, patch }
+    }
+}
+
+impl fmt::Display for SnowflakeVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Parses `major.minor.patch`, ignoring anything after the patch, ex. the build of `8.40.1 b20241015`.
+impl FromStr for SnowflakeVersion {
+    type Err = SnowflakeError;
+    fn from_str(s: &str) -> Result<Self, SnowflakeError> {
+        let invalid = || SnowflakeError::SqlResultParse(anyhow::anyhow!("invalid Snowflake version {s:?}"));
+        let release = s.split_whitespace().next().ok_or_else(invalid)?;
+        let mut parts = release.split('.').map(|part| part.parse::<u32>().map_err(|_| invalid()));
+        let major = parts.next().ok_or_else(invalid)??;
+        let minor = parts.next().unwrap_or(Ok(0))?;
+        let patch = parts.next().unwrap_or(Ok(0))?;
+        Ok(SnowflakeVersion { major, minor, patch })
+    }
+}
+
+/// Release of the `VECTOR` data type.
+pub const VECTOR_SINCE: SnowflakeVersion = SnowflakeVersion::new(8, 19, 0);
+
+/// Features available to statements, decided by the release of Snowflake.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Capabilities {
+    pub version: SnowflakeVersion,
+}
+
+impl Capabilities {
+    pub fn new(version: SnowflakeVersion) -> Self {
+        Capabilities { version }
+    }
+    /// Whether the release is `version` or newer, for features without a method of their own.
+    pub fn at_least(&self, version: SnowflakeVersion) -> bool {
+        self.version >= version
+    }
+    /// Columns of type `VECTOR`, see [`VECTOR_SINCE`].
+    pub fn vector(&self) -> bool {
+        self.at_least(VECTOR_SINCE)
+    }
+}
+
+impl SnowflakeConnector {
+    /// [`Capabilities`] of the release in [`session_info`](Self::session_info), so cached along with it.
+    pub async fn capabilities(&self) -> Result<Capabilities, SnowflakeError> {
+        let session = self.session_info().await?;
+        Ok(Capabilities::new(session.version.parse()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions() -> Result<(), SnowflakeError> {
+        assert_eq!("8.40.1".parse::<SnowflakeVersion>()?, SnowflakeVersion::new(8, 40, 1));
+        assert_eq!("8.7.0 b20240101".parse::<SnowflakeVersion>()?, SnowflakeVersion::new(8, 7, 0));
+        assert_eq!("9".parse::<SnowflakeVersion>()?, SnowflakeVersion::new(9, 0, 0));
+        assert!("".parse::<SnowflakeVersion>().is_err());
+        assert!("8.x".parse::<SnowflakeVersion>().is_err());
+
+        assert!(SnowflakeVersion::new(8, 9, 0) < SnowflakeVersion::new(8, 19, 0));
+        assert!(!Capabilities::new("8.18.2".parse()?).vector());
+        assert!(Capabilities::new("8.40.1".parse()?).vector());
+        assert!(Capabilities::new("8.40.1".parse()?).at_least(SnowflakeVersion::new(8, 40, 0)));
+        Ok(())
+    }
+}
@@ -0,0 +1,186 @@
+//! At-least-once consumer of the changes recorded by a `STREAM`.
+//!
+//! Changes are moved from the stream into a staging table in a single transaction,
+//! which advances the stream offset, and stay there until the batch is committed.
+//! A batch that is never committed, because processing failed or the process died,
+//! is returned again by the next [`ChangeFeed::poll`].
+//!
+//! Only one consumer may use a staging table at a time.
+
+use std::{marker::PhantomData, time::Duration};
+
+use snowflake_deserializer::SnowflakeDeserialize;
+
+use crate::{
+    errors::SnowflakeError,
+    escape::escape_qualified_name,
+    polling::{Backoff, Deadline},
+    status::StatementOutcome,
+    SnowflakeConnector,
+};
+
+/// Changes of a stream deserialized into `T`, see [`SnowflakeConnector::change_feed`].
+#[derive(Debug)]
+pub struct ChangeFeed<'a, T> {
+    connector: &'a SnowflakeConnector,
+    database: String,
+    warehouse: String,
+    stream: String,
+    staging: String,
+    timeout: Duration,
+    _row: PhantomData<fn() -> T>,
+}
+
+/// Changes returned by [`ChangeFeed::poll`], pass to [`ChangeFeed::commit`] once processed.
+#[derive(Debug)]
+#[must_use = "a batch is delivered again until it is committed"]
+pub struct ChangeBatch<T> {
+    /// Rows of the stream, including its `METADATA$ACTION`, `METADATA$ISUPDATE` and `METADATA$ROW_ID` columns.
+    pub rows: Vec<T>,
+}
+
+impl<T> ChangeBatch<T> {
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+impl SnowflakeConnector {
+    /// Consume `stream` through the `staging` table, both fully qualified or relative to `database`.
+    ///
+    /// Names are escaped with [`escape_qualified_name`], parts that are not upper case are case sensitive.
+    pub fn change_feed<T>(
+        &self,
+        database: impl Into<String>,
+        warehouse: impl Into<String>,
+        stream: impl Into<String>,
+        staging: impl Into<String>,
+    ) -> ChangeFeed<'_, T> {
+        ChangeFeed {
+            connector: self,
+            database: database.into(),
+            warehouse: warehouse.into(),
+            stream: stream.into(),
+            staging: staging.into(),
+            timeout: Duration::from_secs(60),
+            _row: PhantomData,
+        }
+    }
+}
+
+impl<T: SnowflakeDeserialize> ChangeFeed<'_, T> {
+    /// How long to wait for changes to be moved into the staging table, 60 seconds by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    /// Create the staging table with the columns of the stream, if it does not exist.
+    pub async fn create_staging(&self) -> Result<(), SnowflakeError> {
+        let statement = format!(
+            "CREATE TRANSIENT TABLE IF NOT EXISTS {} AS SELECT * FROM {} WHERE FALSE",
+            escape_qualified_name(&self.staging), escape_qualified_name(&self.stream),
+        );
+        self.connector.execute(&self.database, &self.warehouse)
+            .sql(&statement)?
+            .text().await?;
+        Ok(())
+    }
+    /// Uncommitted changes, or else the changes recorded by the stream since the last commit.
+    pub async fn poll(&self) -> Result<ChangeBatch<T>, SnowflakeError> {
+        let rows = self.staged().await?;
+        if !rows.is_empty() {
+            return Ok(ChangeBatch { rows });
+        }
+        self.advance().await?;
+        Ok(ChangeBatch { rows: self.staged().await? })
+    }
+    /// Acknowledge `batch`, its changes are not delivered again.
+    pub async fn commit(&self, batch: ChangeBatch<T>) -> Result<(), SnowflakeError> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        let statement = format!("DELETE FROM {}", escape_qualified_name(&self.staging));
+        self.connector.execute(&self.database, &self.warehouse)
+            .sql(&statement)?
+            .text().await?;
+        Ok(())
+    }
+    async fn staged(&self) -> Result<Vec<T>, SnowflakeError> {
+        let statement = format!("SELECT * FROM {}", escape_qualified_name(&self.staging));
+        Ok(self.connector.execute(&self.database, &self.warehouse)
+            .sql(&statement)?
+            .select::<T>().await?
+            .data)
+    }
+    /// Move the stream's changes into the staging table, advancing its offset in the same transaction.
+    async fn advance(&self) -> Result<(), SnowflakeError> {
+        let script = advance_script(&self.stream, &self.staging);
+        let outcomes = self.connector.execute(&self.database, &self.warehouse)
+            .script(&script)?
+            .run().await?
+            .wait_all(Deadline::after(self.timeout), Backoff::default()).await?;
+        outcomes.into_iter().try_for_each(StatementOutcome::succeeded)
+    }
+}
+
+fn advance_script(stream: &str, staging: &str) -> String {
+    format!(
+        "BEGIN; INSERT INTO {} SELECT * FROM {}; COMMIT;",
+        escape_qualified_name(staging), escape_qualified_name(stream),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{matchers::{body_string_contains, method}, Mock};
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[test]
+    fn script() {
+        assert_eq!(
+            advance_script("ORDERS_STREAM", "ORDERS_STAGING"),
+            "BEGIN; INSERT INTO ORDERS_STAGING SELECT * FROM ORDERS_STREAM; COMMIT;",
+        );
+        assert_eq!(
+            advance_script("DB.PUBLIC.orders_stream", "DB.PUBLIC.ORDERS_STAGING"),
+            r#"BEGIN; INSERT INTO DB.PUBLIC.ORDERS_STAGING SELECT * FROM DB.PUBLIC."orders_stream"; COMMIT;"#,
+        );
+    }
+
+    #[tokio::test]
+    async fn poll_and_commit() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        // Nothing is staged until the changes are moved from the stream.
+        Mock::given(method("POST"))
+            .and(body_string_contains("SELECT * FROM ORDERS_STAGING"))
+            .respond_with(StatementFixture::new().column("ID", "fixed").column("METADATA$ACTION", "text").success())
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&snowflake.server).await;
+        snowflake.mount(&StatementFixture::new()
+            .column("ID", "fixed")
+            .column("METADATA$ACTION", "text")
+            .row(["1", "INSERT"])).await;
+        let connector = snowflake.connector();
+        let feed = connector.change_feed::<(u32, String)>("DB", "WH", "ORDERS_STREAM", "ORDERS_STAGING");
+        let batch = feed.poll().await?;
+        assert_eq!(batch.rows, vec![(1, "INSERT".to_owned())]);
+        feed.commit(batch).await?;
+
+        let statements: Vec<String> = snowflake.server.received_requests().await.unwrap_or_default()
+            .iter()
+            .filter(|request| request.method == wiremock::http::Method::POST)
+            .map(|request| serde_json::from_slice::<serde_json::Value>(&request.body).map(|body| body["statement"].as_str().unwrap_or_default().to_owned()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(statements, [
+            "SELECT * FROM ORDERS_STAGING",
+            "BEGIN; INSERT INTO ORDERS_STAGING SELECT * FROM ORDERS_STREAM; COMMIT;",
+            "SELECT * FROM ORDERS_STAGING",
+            "DELETE FROM ORDERS_STAGING",
+        ]);
+        Ok(())
+    }
+}
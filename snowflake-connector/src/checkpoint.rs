@@ -0,0 +1,168 @@
+//! Persist how far a [`Cursor`] got, so a restarted job resumes from the next partition
+//! instead of downloading the whole result again.
+//!
+//! Results stay available by statement handle for a day, reopen them with [`SnowflakeConnector::cursor`]:
+//!
+//! ```no_run
+//! # use snowflake_connector::{checkpoint::FileCheckpoint, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector, statement_handle: &str) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let mut cursor = connector.cursor(statement_handle).await?
+//!     .with_checkpoint(FileCheckpoint::new("extract.checkpoint"))?;
+//! loop {
+//!     let rows = cursor.fetchmany::<(i64, String)>(10_000).await?;
+//!     if rows.is_empty() {
+//!         break;
+//!     }
+//!     // Process the rows, the checkpoint is saved when the next rows are requested.
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fs, path::PathBuf};
+
+use anyhow::Context;
+
+use crate::{
+    cursor::Cursor,
+    errors::SnowflakeError,
+    progress::ProgressTracker,
+    status::{self, StatementOutcome},
+    SnowflakeConnector,
+};
+
+/// Where the number of consumed partitions of a result is stored.
+pub trait Checkpoint: Send + Sync {
+    /// Partitions of `statement_handle` consumed before, `None` if it was never saved.
+    fn load(&self, statement_handle: &str) -> Result<Option<usize>, anyhow::Error>;
+    /// Every row of the first `partitions` partitions of `statement_handle` was processed.
+    fn save(&self, statement_handle: &str, partitions: usize) -> Result<(), anyhow::Error>;
+}
+
+/// Checkpoint of a single result stored in a file, as `<statement handle> <partitions>`.
+#[derive(Clone, Debug)]
+pub struct FileCheckpoint {
+    path: PathBuf,
+}
+
+impl FileCheckpoint {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        FileCheckpoint { path: path.into() }
+    }
+}
+
+impl Checkpoint for FileCheckpoint {
+    fn load(&self, statement_handle: &str) -> Result<Option<usize>, anyhow::Error> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).with_context(|| format!("could not read {}", self.path.display())),
+        };
+        match content.trim().split_once(' ') {
+            Some((handle, partitions)) if handle == statement_handle => Ok(Some(partitions.parse()
+                .with_context(|| format!("{} is not a checkpoint", self.path.display()))?)),
+            _ => Ok(None),
+        }
+    }
+    /// Written to a temporary file first, so a crash never leaves a partial checkpoint.
+    fn save(&self, statement_handle: &str, partitions: usize) -> Result<(), anyhow::Error> {
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, format!("{statement_handle} {partitions}\n"))
+            .and_then(|()| fs::rename(&temporary, &self.path))
+            .with_context(|| format!("could not write {}", self.path.display()))
+    }
+}
+
+impl SnowflakeConnector {
+    /// Cursor over the result of an already executed statement, ex. to resume it with a [`Checkpoint`].
+    pub async fn cursor(&self, statement_handle: &str) -> Result<Cursor, SnowflakeError> {
+        self.in_flight.check_open()?;
+        let client = self.client()?;
+        let outcome = status::fetch_status(&client, &self.host, statement_handle, &self.null_sentinel).await?;
+        self.result_cursor(client, outcome)
+    }
+    /// Cursor over the result of `outcome`, errors unless it succeeded.
+    pub(crate) fn result_cursor(&self, client: reqwest::Client, outcome: StatementOutcome) -> Result<Cursor, SnowflakeError> {
+        match outcome {
+            StatementOutcome::Success(response) => Ok(Cursor::new(
+                client,
+                self.host.clone(),
+                *response,
+                self.partition_policy.clone(),
+                self.redaction.clone(),
+                self.row_filter.clone(),
+                ProgressTracker::default(),
+            )),
+            StatementOutcome::Failed(status) => Err(SnowflakeError::StatementFailed(Box::new(status))),
+            StatementOutcome::TimedOut(status) => Err(SnowflakeError::StatementTimedOut(Box::new(status))),
+            StatementOutcome::Pending(status) => Err(SnowflakeError::SqlExecution(
+                anyhow::anyhow!("statement {} is still running—{}", status.statement_handle, status.message),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use crate::test_util::{MockSnowflake, StatementFixture, STATEMENT_HANDLE};
+
+    use super::*;
+
+    #[derive(Clone, Default)]
+    struct MemoryCheckpoint(Arc<Mutex<Option<usize>>>);
+
+    impl Checkpoint for MemoryCheckpoint {
+        fn load(&self, _: &str) -> Result<Option<usize>, anyhow::Error> {
+            Ok(*self.0.lock().unwrap())
+        }
+        fn save(&self, _: &str, partitions: usize) -> Result<(), anyhow::Error> {
+            *self.0.lock().unwrap() = Some(partitions);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn resume() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&StatementFixture::new()
+            .column("ID", "fixed")
+            .row([Some("1")])
+            .row([Some("2")])
+            .partition()
+            .row([Some("3")])
+            .partition()
+            .row([Some("4")])).await;
+        let connector = snowflake.connector();
+        let checkpoint = MemoryCheckpoint::default();
+
+        let mut cursor = connector.cursor(STATEMENT_HANDLE).await?.with_checkpoint(checkpoint.clone())?;
+        assert_eq!(cursor.fetchmany::<i64>(2).await?, [1, 2]);
+        assert_eq!(checkpoint.load(STATEMENT_HANDLE)?, None);
+        assert_eq!(cursor.fetchmany::<i64>(1).await?, [3]);
+        assert_eq!(checkpoint.load(STATEMENT_HANDLE)?, Some(1));
+
+        let mut cursor = connector.cursor(STATEMENT_HANDLE).await?.with_checkpoint(checkpoint.clone())?;
+        assert_eq!(cursor.rownumber(), 2);
+        assert_eq!(cursor.fetchall::<i64>().await?, [3, 4]);
+        cursor.save_checkpoint()?;
+        assert_eq!(checkpoint.load(STATEMENT_HANDLE)?, Some(3));
+
+        let mut cursor = connector.cursor(STATEMENT_HANDLE).await?.with_checkpoint(checkpoint)?;
+        assert!(cursor.fetchall::<i64>().await?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn file() -> Result<(), anyhow::Error> {
+        let path = std::env::temp_dir().join(format!("snowflake-checkpoint-{}", uuid::Uuid::new_v4()));
+        let checkpoint = FileCheckpoint::new(&path);
+        assert_eq!(checkpoint.load("HANDLE")?, None);
+        checkpoint.save("HANDLE", 4)?;
+        assert_eq!(checkpoint.load("HANDLE")?, Some(4));
+        assert_eq!(checkpoint.load("OTHER")?, None);
+        fs::remove_file(path)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+//! Large `DELETE` and `UPDATE` statements split into ranges of a numeric column,
+//! run one after the other so each stays within timeouts and locks fewer rows.
+
+use std::borrow::Cow;
+
+use crate::{
+    data_manipulation::Changes,
+    errors::SnowflakeError,
+    SnowflakeSQL,
+};
+
+type OnChunk = Box<dyn Fn(&Chunk) + Send + Sync>;
+
+/// Chunks of a statement, see [`SnowflakeSQL::manipulate_chunked`].
+pub struct ChunkedManipulation<'a> {
+    sql: SnowflakeSQL<'a>,
+    column: String,
+    chunk: u64,
+    resume_from: Option<i64>,
+    on_chunk: Option<OnChunk>,
+}
+
+/// Range of the predicate column a chunk covered, `start` inclusive and `end` exclusive.
+#[derive(Clone, Copy, Debug)]
+pub struct Chunk {
+    pub start: i64,
+    pub end: i64,
+    pub changes: Changes,
+    /// Changes of this and every earlier chunk.
+    pub total: Changes,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ChunkedResult {
+    pub changes: Changes,
+    pub chunks: usize,
+}
+
+/// A chunk failed, chunks before it are committed.
+#[derive(thiserror::Error, Debug)]
+#[error("chunked statement failed after {} chunk(s)", .completed.chunks)]
+pub struct ChunkedError {
+    pub completed: ChunkedResult,
+    /// Pass to [`ChunkedManipulation::resume_from`] to continue with the failed chunk,
+    /// `None` if it failed before the first chunk, ex. reading the column's range.
+    pub resume_from: Option<i64>,
+    pub source: SnowflakeError,
+}
+
+impl<'a> SnowflakeSQL<'a> {
+    /// Run a `DELETE` or `UPDATE` in chunks of `chunk` values of the numeric `predicate_column`,
+    /// from its minimum to its maximum in the target table.
+    ///
+    /// Each chunk is its own statement and committed on its own,
+    /// the statement's `WHERE` condition is kept and combined with the range of the chunk.
+    pub fn manipulate_chunked(self, predicate_column: impl Into<String>, chunk: u64) -> ChunkedManipulation<'a> {
+        ChunkedManipulation {
+            sql: self,
+            column: predicate_column.into(),
+            chunk: chunk.max(1),
+            resume_from: None,
+            on_chunk: None,
+        }
+    }
+}
+
+impl ChunkedManipulation<'_> {
+    /// Start at `value` instead of the column's minimum, ex. [`ChunkedError::resume_from`].
+    pub fn resume_from(mut self, value: i64) -> Self {
+        self.resume_from = Some(value);
+        self
+    }
+    /// Call `on_chunk` after every chunk finished.
+    pub fn on_chunk<F: Fn(&Chunk) + Send + Sync + 'static>(mut self, on_chunk: F) -> Self {
+        self.on_chunk = Some(Box::new(on_chunk));
+        self
+    }
+    pub async fn run(self) -> Result<ChunkedResult, ChunkedError> {
+        let fail = |completed: ChunkedResult, resume_from: Option<i64>, source: SnowflakeError| ChunkedError {
+            completed,
+            resume_from,
+            source,
+        };
+        let (min, max) = self.bounds().await
+            .map_err(|source| fail(ChunkedResult::default(), self.resume_from, source))?;
+        let mut result = ChunkedResult::default();
+        let (Some(min), Some(max)) = (min, max) else {
+            return Ok(result);
+        };
+        let statement = with_range(&self.sql.statement.statement, &self.column);
+        let step = i64::try_from(self.chunk).unwrap_or(i64::MAX);
+        let mut start = self.resume_from.unwrap_or(min);
+        while start <= max {
+            let end = start.saturating_add(step);
+            let mut sql = self.sql.clone();
+            sql.statement.statement = Cow::Owned(statement.clone());
+            sql.uuid = uuid::Uuid::new_v4();
+            let changes = sql.add_binding(start).add_binding(end)
+                .manipulate().await
+                .map_err(|source| fail(result, Some(start), source))?
+                .stats;
+            result.changes += changes;
+            result.chunks += 1;
+            if let Some(on_chunk) = &self.on_chunk {
+                on_chunk(&Chunk { start, end, changes, total: result.changes });
+            }
+            if end == i64::MAX {
+                break;
+            }
+            start = end;
+        }
+        Ok(result)
+    }
+    /// Minimum and maximum of the column in the target table, `None` if it is empty.
+    async fn bounds(&self) -> Result<(Option<i64>, Option<i64>), SnowflakeError> {
+        let table = target_table(&self.sql.statement.statement)?;
+        let mut sql = self.sql.clone();
+        sql.statement.statement = Cow::Owned(format!("SELECT MIN({0}), MAX({0}) FROM {table}", self.column));
+        sql.statement.bindings = None;
+        sql.uuid = uuid::Uuid::new_v4();
+        sql.select_one::<(Option<i64>, Option<i64>)>().await
+    }
+}
+
+/// Table after `DELETE FROM` or `UPDATE`.
+fn target_table(statement: &str) -> Result<&str, SnowflakeError> {
+    let mut words = statement.split_whitespace();
+    let table = match words.next().map(str::to_ascii_uppercase).as_deref() {
+        Some("DELETE") if words.next().is_some_and(|word| word.eq_ignore_ascii_case("FROM")) => words.next(),
+        Some("UPDATE") => words.next(),
+        _ => None,
+    };
+    table.ok_or_else(|| SnowflakeError::InvalidStatement(anyhow::anyhow!(
+        "only DELETE FROM and UPDATE statements can be chunked",
+    )))
+}
+
+/// Statement restricted to `column >= ? AND column < ?`, the existing top-level `WHERE` condition is parenthesized.
+fn with_range(statement: &str, column: &str) -> String {
+    let statement = statement.trim().trim_end_matches(';').trim_end();
+    let range = format!("{column} >= ? AND {column} < ?");
+    match top_level_where(statement) {
+        Some(index) => format!(
+            "{} WHERE ({}) AND {range}",
+            statement[..index].trim_end(),
+            statement[index + "WHERE".len()..].trim(),
+        ),
+        None => format!("{statement} WHERE {range}"),
+    }
+}
+
+/// Position of `WHERE` outside of string literals, quoted identifiers and parentheses.
+fn top_level_where(statement: &str) -> Option<usize> {
+    let bytes = statement.as_bytes();
+    let mut depth = 0usize;
+    let mut quote = None;
+    for (index, &byte) in bytes.iter().enumerate() {
+        match (quote, byte) {
+            (Some(open), _) if byte == open => quote = None,
+            (Some(_), _) => {},
+            (None, b'\'' | b'"') => quote = Some(byte),
+            (None, b'(') => depth += 1,
+            (None, b')') => depth = depth.saturating_sub(1),
+            (None, _) if depth == 0
+                && bytes[index..].len() >= 5
+                && bytes[index..index + 5].eq_ignore_ascii_case(b"WHERE")
+                && (index == 0 || bytes[index - 1].is_ascii_whitespace())
+                && bytes.get(index + 5).is_none_or(|next| next.is_ascii_whitespace() || *next == b'(')
+                => return Some(index),
+            _ => {},
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{matchers::{body_string_contains, method}, Mock, ResponseTemplate};
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[test]
+    fn statements() -> Result<(), anyhow::Error> {
+        assert_eq!(target_table("delete from DB.PUBLIC.EVENTS WHERE x = 1")?, "DB.PUBLIC.EVENTS");
+        assert_eq!(target_table("UPDATE EVENTS SET x = 1")?, "EVENTS");
+        assert!(target_table("SELECT 1").is_err());
+        assert_eq!(with_range("DELETE FROM EVENTS;", "ID"), "DELETE FROM EVENTS WHERE ID >= ? AND ID < ?");
+        assert_eq!(
+            with_range("DELETE FROM EVENTS WHERE a = 'WHERE' OR b IN (SELECT b FROM T WHERE c)", "ID"),
+            "DELETE FROM EVENTS WHERE (a = 'WHERE' OR b IN (SELECT b FROM T WHERE c)) AND ID >= ? AND ID < ?",
+        );
+        assert_eq!(
+            with_range("UPDATE EVENTS SET NOWHERE = 1 WHERE x = ?", "ID"),
+            "UPDATE EVENTS SET NOWHERE = 1 WHERE (x = ?) AND ID >= ? AND ID < ?",
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn chunk_above_i64() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("DELETE FROM EVENTS"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "message": "successfully executed",
+                "stats": { "numRowsInserted": 0, "numRowsDeleted": 10, "numRowsUpdated": 0, "numDmlDuplicates": 0 },
+                "statementHandle": "handle",
+                "requestId": "request",
+            })))
+            .with_priority(1)
+            .mount(&snowflake.server).await;
+        snowflake.mount(&StatementFixture::new()
+            .column("MIN(ID)", "fixed")
+            .column("MAX(ID)", "fixed")
+            .row([Some("1"), Some("10")])).await;
+        let result = snowflake.connector()
+            .execute("DB", "WH")
+            .sql("DELETE FROM EVENTS")?
+            .manipulate_chunked("ID", u64::MAX)
+            .run().await?;
+        assert_eq!((result.chunks, result.changes.rows_deleted), (1, 10));
+        Ok(())
+    }
+}
@@ -0,0 +1,108 @@
+//! sqlx-style names over [`SnowflakeSQL`], so code written against sqlx's
+//! `fetch_all`/`fetch_one`/`fetch_optional`/`execute` ports with minimal changes.
+
+use std::sync::Arc;
+
+use snowflake_deserializer::{
+    coercion::ColumnMismatch, DeserializeFromStr, RowType, SnowflakeDeserialize, SnowflakeSQLResponse,
+    SnowflakeSQLResult,
+};
+
+use crate::{data_manipulation::DataManipulationResult, errors::SnowflakeError, SnowflakeSQL};
+
+impl<'a> SnowflakeSQL<'a> {
+    pub async fn fetch_all<T: SnowflakeDeserialize>(self) -> Result<Vec<T>, SnowflakeError> {
+        Ok(self.select().await?.data)
+    }
+    /// First row, errors if the query returned no rows.
+    pub async fn fetch_one<T: SnowflakeDeserialize>(self) -> Result<T, SnowflakeError> {
+        self.fetch_optional().await?
+            .ok_or(SnowflakeError::UnexpectedRowCount(0))
+    }
+    /// First row, if any.
+    pub async fn fetch_optional<T: SnowflakeDeserialize>(self) -> Result<Option<T>, SnowflakeError> {
+        Ok(self.select().await?.data.into_iter().next())
+    }
+    pub async fn execute(self) -> Result<DataManipulationResult, SnowflakeError> {
+        self.manipulate().await
+    }
+}
+
+/// Untyped row, cells are converted on access with [`Row::try_get`].
+#[derive(Clone, Debug)]
+pub struct Row {
+    columns: Arc<[RowType]>,
+    values: Vec<String>,
+}
+
+impl Row {
+    pub fn columns(&self) -> &[RowType] {
+        &self.columns
+    }
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+    /// Raw cell as Snowflake returned it.
+    pub fn try_get_raw<I: ColumnIndex>(&self, index: I) -> Result<&str, anyhow::Error> {
+        let index = index.index(self)?;
+        Ok(&self.values[index])
+    }
+    pub fn try_get<T, I>(&self, index: I) -> Result<T, anyhow::Error>
+        where T: DeserializeFromStr, T::Err: Into<anyhow::Error>, I: ColumnIndex
+    {
+        let index = index.index(self)?;
+        T::deserialize_from_str(&self.values[index])
+            .map_err(|e| ColumnMismatch::new::<T, _>(&self.columns[index], e).into())
+    }
+    /// Panics if the column does not exist or fails to convert, see [`Row::try_get`].
+    pub fn get<T, I>(&self, index: I) -> T
+        where T: DeserializeFromStr, T::Err: Into<anyhow::Error>, I: ColumnIndex
+    {
+        match self.try_get(index) {
+            Ok(value) => value,
+            Err(e) => panic!("{e}"),
+        }
+    }
+}
+
+impl SnowflakeDeserialize for Row {
+    fn snowflake_deserialize(response: SnowflakeSQLResponse) -> Result<SnowflakeSQLResult<Self>, anyhow::Error> {
+        let columns: Arc<[RowType]> = response.result_set_meta_data.row_type.into();
+        let data = response.data
+            .into_iter()
+            .map(|values| Row {
+                columns: columns.clone(),
+                values,
+            })
+            .collect();
+        Ok(SnowflakeSQLResult::new(data))
+    }
+}
+
+/// Column lookup by position or by (case-insensitive) name.
+pub trait ColumnIndex {
+    fn index(&self, row: &Row) -> Result<usize, anyhow::Error>;
+}
+
+impl ColumnIndex for usize {
+    fn index(&self, row: &Row) -> Result<usize, anyhow::Error> {
+        if *self < row.len() {
+            Ok(*self)
+        } else {
+            Err(anyhow::anyhow!("column index {self} out of bounds, row has {} columns", row.len()))
+        }
+    }
+}
+
+impl ColumnIndex for &str {
+    fn index(&self, row: &Row) -> Result<usize, anyhow::Error> {
+        row.columns
+            .iter()
+            .position(|column| column.name.eq_ignore_ascii_case(self))
+            .filter(|index| *index < row.len())
+            .ok_or_else(|| anyhow::anyhow!("column {self} not found"))
+    }
+}
@@ -0,0 +1,255 @@
+use std::{collections::VecDeque, sync::Arc};
+
+use snowflake_deserializer::{redact::{self, RedactionPolicy}, RowType, SnowflakeDeserialize, SnowflakeSQLResponse};
+use tokio::sync::mpsc;
+
+use crate::{checkpoint::Checkpoint, errors::SnowflakeError, partitions::{self, PartitionFetchPolicy}, progress::{ProgressTracker, ResultStats}, row_filter::RowFilter};
+
+/// DB-API style cursor over an executed statement.
+///
+/// Rows are handed out in order, partitions are only downloaded once the rows
+/// of the previous one have been consumed.
+#[derive(Debug)]
+pub struct Cursor {
+    client: reqwest::Client,
+    host: Arc<str>,
+    response: SnowflakeSQLResponse,
+    buffer: VecDeque<Vec<String>>,
+    next_partition: usize,
+    rows_fetched: usize,
+    partition_policy: PartitionFetchPolicy,
+    redaction: RedactionPolicy,
+    row_filter: Arc<RowFilter>,
+    progress: ProgressTracker,
+    checkpoint: Option<SavedCheckpoint>,
+}
+
+struct SavedCheckpoint {
+    checkpoint: Box<dyn Checkpoint>,
+    partitions: usize,
+}
+
+impl std::fmt::Debug for SavedCheckpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SavedCheckpoint").field("partitions", &self.partitions).finish_non_exhaustive()
+    }
+}
+
+impl Cursor {
+    pub(crate) fn new(
+        client: reqwest::Client,
+        host: Arc<str>,
+        mut response: SnowflakeSQLResponse,
+        partition_policy: PartitionFetchPolicy,
+        redaction: RedactionPolicy,
+        row_filter: Arc<RowFilter>,
+        progress: ProgressTracker,
+    ) -> Self {
+        let buffer = std::mem::take(&mut response.data).into();
+        Cursor {
+            client,
+            host,
+            response,
+            buffer,
+            next_partition: 1,
+            rows_fetched: 0,
+            partition_policy,
+            redaction,
+            row_filter,
+            progress,
+            checkpoint: None,
+        }
+    }
+    /// Columns of the result.
+    pub fn description(&self) -> &[RowType] {
+        &self.response.result_set_meta_data.row_type
+    }
+    /// Text of `NULL` cells in the raw rows, see [`SnowflakeConnector::with_null_sentinel`](crate::SnowflakeConnector::with_null_sentinel).
+    #[cfg(feature = "parquet")]
+    pub(crate) fn null_sentinel(&self) -> &str {
+        &self.response.null_sentinel
+    }
+    /// Total number of rows in the result, across all partitions.
+    pub fn rowcount(&self) -> usize {
+        self.response.result_set_meta_data.num_rows
+    }
+    /// Partitions of the result and those downloaded so far.
+    pub fn stats(&self) -> &ResultStats {
+        self.progress.stats()
+    }
+    /// Number of rows handed out so far.
+    pub fn rownumber(&self) -> usize {
+        self.rows_fetched
+    }
+    pub fn statement_handle(&self) -> &str {
+        &self.response.statement_handle
+    }
+    /// Partition that will be downloaded next, once the buffered rows run out.
+    pub fn next_partition(&self) -> usize {
+        self.next_partition
+    }
+    /// Skip the partitions `checkpoint` saved as consumed for this result,
+    /// and save the consumed partitions whenever more rows are requested.
+    pub fn with_checkpoint<C: Checkpoint + 'static>(mut self, checkpoint: C) -> Result<Self, SnowflakeError> {
+        let partitions = checkpoint.load(&self.response.statement_handle)
+            .map_err(SnowflakeError::Checkpoint)?
+            .unwrap_or(0)
+            .min(self.partition_count());
+        if partitions > 0 {
+            self.buffer.clear();
+            self.next_partition = partitions;
+            self.rows_fetched = self.response.result_set_meta_data.partition_info[..partitions].iter()
+                .map(|partition| partition.row_count)
+                .sum();
+        }
+        self.checkpoint = Some(SavedCheckpoint { checkpoint: Box::new(checkpoint), partitions });
+        Ok(self)
+    }
+    /// Save every partition whose rows were all handed out as consumed,
+    /// done before fetching more rows, call it after processing the last rows.
+    pub fn save_checkpoint(&mut self) -> Result<(), SnowflakeError> {
+        let consumed = self.consumed_partitions();
+        let Some(saved) = &mut self.checkpoint else {
+            return Ok(());
+        };
+        if consumed > saved.partitions {
+            saved.checkpoint.save(&self.response.statement_handle, consumed)
+                .map_err(SnowflakeError::Checkpoint)?;
+            saved.partitions = consumed;
+        }
+        Ok(())
+    }
+    /// Leading partitions whose rows were all handed out.
+    fn consumed_partitions(&self) -> usize {
+        let mut rows = 0;
+        self.response.result_set_meta_data.partition_info.iter()
+            .take_while(|partition| {
+                rows += partition.row_count;
+                rows <= self.rows_fetched
+            })
+            .count()
+    }
+    pub async fn fetchone<T: SnowflakeDeserialize>(&mut self) -> Result<Option<T>, SnowflakeError> {
+        Ok(self.fetchmany(1).await?.pop())
+    }
+    /// Up to `size` rows, fewer only once the result is exhausted.
+    pub async fn fetchmany<T: SnowflakeDeserialize>(&mut self, size: usize) -> Result<Vec<T>, SnowflakeError> {
+        self.save_checkpoint()?;
+        while self.buffer.len() < size && self.next_partition < self.partition_count() {
+            self.fetch_next_partition().await?;
+        }
+        let size = size.min(self.buffer.len());
+        let rows = self.buffer.drain(..size).collect();
+        self.deserialize(rows)
+    }
+    /// Every remaining row.
+    pub async fn fetchall<T: SnowflakeDeserialize>(&mut self) -> Result<Vec<T>, SnowflakeError> {
+        self.save_checkpoint()?;
+        while self.next_partition < self.partition_count() {
+            self.fetch_next_partition().await?;
+        }
+        let rows = self.buffer.drain(..).collect();
+        self.deserialize(rows)
+    }
+    /// Send every remaining row into `sender` as soon as its partition was downloaded, waiting while the channel is full,
+    /// returns the number of rows sent. Stops early once the receiver was dropped.
+    pub async fn send_into<T: SnowflakeDeserialize>(&mut self, sender: &mpsc::Sender<T>) -> Result<usize, SnowflakeError> {
+        let mut sent = 0;
+        loop {
+            self.save_checkpoint()?;
+            if self.buffer.is_empty() {
+                if self.next_partition >= self.partition_count() {
+                    return Ok(sent);
+                }
+                self.fetch_next_partition().await?;
+            }
+            let rows = self.buffer.drain(..).collect();
+            for row in self.deserialize::<T>(rows)? {
+                if sender.send(row).await.is_err() {
+                    return Ok(sent);
+                }
+                sent += 1;
+            }
+        }
+    }
+    /// Rows not handed out yet, the buffered ones or else those of the next partition, `None` once exhausted.
+    #[cfg(feature = "parquet")]
+    pub(crate) async fn fetch_raw(&mut self) -> Result<Option<Vec<Vec<String>>>, SnowflakeError> {
+        self.save_checkpoint()?;
+        if self.buffer.is_empty() {
+            if self.next_partition >= self.partition_count() {
+                return Ok(None);
+            }
+            self.fetch_next_partition().await?;
+        }
+        let rows: Vec<Vec<String>> = self.buffer.drain(..).collect();
+        self.rows_fetched += rows.len();
+        Ok(Some(rows))
+    }
+    fn partition_count(&self) -> usize {
+        self.response.result_set_meta_data.partition_info.len()
+    }
+    async fn fetch_next_partition(&mut self) -> Result<(), SnowflakeError> {
+        let data = partitions::fetch_partition(
+            &self.client,
+            &self.host,
+            &self.response.statement_handle,
+            self.next_partition,
+            &self.partition_policy,
+            &mut self.progress,
+            &self.response.null_sentinel,
+        ).await?;
+        self.buffer.extend(data);
+        self.next_partition += 1;
+        Ok(())
+    }
+    fn deserialize<T: SnowflakeDeserialize>(&mut self, rows: Vec<Vec<String>>) -> Result<Vec<T>, SnowflakeError> {
+        self.rows_fetched += rows.len();
+        let mut response = self.response.clone();
+        response.result_set_meta_data.num_rows = rows.len();
+        response.data = rows;
+        self.row_filter.apply(&mut response);
+        let data = redact::with_policy(&self.redaction, || response.deserialize::<T>())
+            .map_err(SnowflakeError::SqlResultParse)?
+            .data;
+        self.progress.deserialized(data.len());
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn send_into() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&StatementFixture::new()
+            .column("ID", "fixed")
+            .row([Some("1")])
+            .row([Some("2")])
+            .partition()
+            .row([Some("3")])).await;
+        let connector = snowflake.connector();
+        let select = || connector.execute("DB", "WH").sql("SELECT ID FROM T").unwrap();
+
+        let (sender, mut receiver) = mpsc::channel::<i64>(1);
+        let receive = async {
+            let mut ids = Vec::new();
+            while let Some(id) = receiver.recv().await {
+                ids.push(id);
+            }
+            ids
+        };
+        let (sent, ids) = tokio::join!(select().select_into(sender), receive);
+        assert_eq!(sent?, 3);
+        assert_eq!(ids, [1, 2, 3]);
+
+        let (sender, receiver) = mpsc::channel::<i64>(1);
+        drop(receiver);
+        assert_eq!(select().select_into(sender).await?, 0);
+        Ok(())
+    }
+}
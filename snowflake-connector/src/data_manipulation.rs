@@ -1,6 +1,6 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct Changes {
     #[serde(rename = "numRowsInserted")]
     pub rows_inserted: usize,
@@ -12,8 +12,21 @@ pub struct Changes {
     pub duplicates: usize,
 }
 
-#[derive(Deserialize, Debug)]
+impl std::ops::AddAssign for Changes {
+    fn add_assign(&mut self, other: Changes) {
+        self.rows_inserted += other.rows_inserted;
+        self.rows_deleted += other.rows_deleted;
+        self.rows_updated += other.rows_updated;
+        self.duplicates += other.duplicates;
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 pub struct DataManipulationResult {
     pub message: String,
     pub stats: Changes,
+    #[serde(default, rename = "statementHandle")]
+    pub statement_handle: String,
+    #[serde(default, rename = "requestId")]
+    pub request_id: String,
 }
@@ -0,0 +1,470 @@
+//! Statements that change objects rather than return rows, ex. `CREATE TABLE`, and builders for common ones.
+//!
+//! Names are escaped with [`escape_qualified_name`], so they are taken literally: `orders` is the table `"orders"`, not `ORDERS`.
+//!
+//! ```no_run
+//! # use snowflake_connector::{ddl::{ColumnDefinition, CreateTable}, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let orders = CreateTable::new("DB.PUBLIC.ORDERS")
+//!     .column(ColumnDefinition::new("ID", "NUMBER(38,0)").not_null())
+//!     .column(ColumnDefinition::new("NOTE", "VARCHAR").with_comment("free text"))
+//!     .if_not_exists();
+//! connector.execute("DB", "WH").create_table(&orders).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{borrow::Cow, time::Duration};
+
+use snowflake_deserializer::{
+    coercion, null,
+    table::{ColumnMeta, SnowflakeTable},
+    SnowflakeDeserialize, SnowflakeSQLResponse, SnowflakeSQLResult,
+};
+
+use crate::{
+    errors::SnowflakeError,
+    escape::{escape_identifier, escape_qualified_name, escape_string_literal},
+    SnowflakeExecutor, SnowflakeSQL,
+};
+
+/// Row DDL statements return, ex. `Table T successfully created.`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StatementStatusRow {
+    pub status: String,
+}
+
+/// The `status` column, or else the first column, of each row.
+impl SnowflakeDeserialize for StatementStatusRow {
+    fn snowflake_deserialize(response: SnowflakeSQLResponse) -> Result<SnowflakeSQLResult<Self>, anyhow::Error> {
+        let row_types = &response.result_set_meta_data.row_type;
+        let index = coercion::column_index(row_types, "status").unwrap_or(0);
+        let data = response.data
+            .into_iter()
+            .filter_map(|mut row| (index < row.len()).then(|| row.swap_remove(index)))
+            .map(|status| StatementStatusRow {
+                status: if null::is_null(&status) { String::new() } else { status },
+            })
+            .collect();
+        Ok(SnowflakeSQLResult::new(data))
+    }
+}
+
+impl SnowflakeSQL<'_> {
+    /// Run a DDL statement, its status rows are empty if it returned no result set.
+    ///
+    /// Unlike [`select`](Self::select) it never fails because of the columns returned.
+    pub async fn select_ddl(self) -> Result<Vec<StatementStatusRow>, SnowflakeError> {
+        Ok(self.select::<StatementStatusRow>().await?.data)
+    }
+}
+
+/// Column of a [`CreateTable`] or [`AddColumn`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnDefinition {
+    pub name: String,
+    /// Snowflake data type, ex. `NUMBER(38,0)`, inserted as is.
+    pub data_type: String,
+    pub nullable: bool,
+    pub comment: Option<String>,
+}
+
+impl ColumnDefinition {
+    /// Nullable column.
+    pub fn new(name: impl Into<String>, data_type: impl Into<String>) -> Self {
+        ColumnDefinition {
+            name: name.into(),
+            data_type: data_type.into(),
+            nullable: true,
+            comment: None,
+        }
+    }
+    pub fn not_null(mut self) -> Self {
+        self.nullable = false;
+        self
+    }
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+    /// `<name> <data type> [NOT NULL] [COMMENT '<comment>']`.
+    pub fn to_sql(&self) -> String {
+        let mut sql = format!("{} {}", escape_identifier(&self.name), self.data_type);
+        if !self.nullable {
+            sql.push_str(" NOT NULL");
+        }
+        if let Some(comment) = &self.comment {
+            sql.push_str(&format!(" COMMENT {}", escape_string_literal(comment)));
+        }
+        sql
+    }
+}
+
+impl From<&ColumnMeta> for ColumnDefinition {
+    fn from(column: &ColumnMeta) -> Self {
+        ColumnDefinition {
+            name: column.name.into(),
+            data_type: column.data_type.into(),
+            nullable: column.nullable,
+            comment: None,
+        }
+    }
+}
+
+/// `CREATE TABLE`.
+#[derive(Clone, Debug)]
+pub struct CreateTable {
+    pub name: String,
+    pub columns: Vec<ColumnDefinition>,
+    pub or_replace: bool,
+    pub if_not_exists: bool,
+    /// Without fail-safe, ex. for staging tables.
+    pub transient: bool,
+    pub comment: Option<String>,
+}
+
+impl CreateTable {
+    pub fn new(name: impl Into<String>) -> Self {
+        CreateTable {
+            name: name.into(),
+            columns: Vec::new(),
+            or_replace: false,
+            if_not_exists: false,
+            transient: false,
+            comment: None,
+        }
+    }
+    /// Table `T` maps to, ex. a struct deriving `SnowflakeDeserialize` with `#[snowflake(table = "...")]`.
+    pub fn from_table<T: SnowflakeTable>() -> Self {
+        CreateTable {
+            columns: T::COLUMNS.iter().map(ColumnDefinition::from).collect(),
+            ..CreateTable::new(T::TABLE)
+        }
+    }
+    pub fn column(mut self, column: ColumnDefinition) -> Self {
+        self.columns.push(column);
+        self
+    }
+    pub fn or_replace(mut self) -> Self {
+        self.or_replace = true;
+        self
+    }
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+    pub fn transient(mut self) -> Self {
+        self.transient = true;
+        self
+    }
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+    pub fn to_sql(&self) -> Result<String, SnowflakeError> {
+        if self.columns.is_empty() {
+            return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!("table {} has no columns", self.name)));
+        }
+        check_replace(&self.name, self.or_replace, self.if_not_exists)?;
+        let object_type = if self.transient { "TRANSIENT TABLE" } else { "TABLE" };
+        let columns: Vec<String> = self.columns.iter().map(ColumnDefinition::to_sql).collect();
+        let mut statement = create_prefix(object_type, &escape_qualified_name(&self.name), self.or_replace, self.if_not_exists);
+        statement.push_str(&format!(" ({})", columns.join(", ")));
+        if let Some(comment) = &self.comment {
+            statement.push_str(&format!(" COMMENT = {}", escape_string_literal(comment)));
+        }
+        Ok(statement)
+    }
+}
+
+/// `ALTER TABLE ... ADD COLUMN`.
+#[derive(Clone, Debug)]
+pub struct AddColumn {
+    pub table: String,
+    pub column: ColumnDefinition,
+    pub if_not_exists: bool,
+}
+
+impl AddColumn {
+    pub fn new(table: impl Into<String>, column: ColumnDefinition) -> Self {
+        AddColumn { table: table.into(), column, if_not_exists: false }
+    }
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+    pub fn to_sql(&self) -> String {
+        format!(
+            "ALTER TABLE {} ADD COLUMN {}{}",
+            escape_qualified_name(&self.table),
+            if self.if_not_exists { "IF NOT EXISTS " } else { "" },
+            self.column.to_sql(),
+        )
+    }
+}
+
+/// `DROP TABLE`.
+#[derive(Clone, Debug)]
+pub struct DropTable {
+    pub name: String,
+    pub if_exists: bool,
+}
+
+impl DropTable {
+    pub fn new(name: impl Into<String>) -> Self {
+        DropTable { name: name.into(), if_exists: false }
+    }
+    pub fn if_exists(mut self) -> Self {
+        self.if_exists = true;
+        self
+    }
+    pub fn to_sql(&self) -> String {
+        format!(
+            "DROP TABLE {}{}",
+            if self.if_exists { "IF EXISTS " } else { "" },
+            escape_qualified_name(&self.name),
+        )
+    }
+}
+
+/// Kind of object a [`CreateClone`] creates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloneKind {
+    Database,
+    Schema,
+    Table,
+}
+
+impl CloneKind {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            CloneKind::Database => "DATABASE",
+            CloneKind::Schema => "SCHEMA",
+            CloneKind::Table => "TABLE",
+        }
+    }
+}
+
+/// Point in the past a clone is taken at, within the source's data retention time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TimeTravel {
+    /// `AT(TIMESTAMP => ...)`, ex. `2024-03-01 12:00:00 +0000`.
+    AtTimestamp(String),
+    /// `AT(OFFSET => ...)`, this long ago.
+    Offset(Duration),
+    /// `AT(STATEMENT => ...)`, including the changes of the statement with this handle.
+    AtStatement(String),
+    /// `BEFORE(STATEMENT => ...)`, excluding the changes of the statement with this handle, ex. to undo it.
+    BeforeStatement(String),
+}
+
+impl TimeTravel {
+    pub fn to_sql(&self) -> String {
+        match self {
+            TimeTravel::AtTimestamp(timestamp) => format!("AT(TIMESTAMP => {}::TIMESTAMP_TZ)", escape_string_literal(timestamp)),
+            TimeTravel::Offset(offset) => format!("AT(OFFSET => -{})", offset.as_secs()),
+            TimeTravel::AtStatement(handle) => format!("AT(STATEMENT => {})", escape_string_literal(handle)),
+            TimeTravel::BeforeStatement(handle) => format!("BEFORE(STATEMENT => {})", escape_string_literal(handle)),
+        }
+    }
+}
+
+/// `CREATE <kind> ... CLONE`, a zero-copy clone sharing the storage of `source` until either changes.
+#[derive(Clone, Debug)]
+pub struct CreateClone {
+    pub kind: CloneKind,
+    pub name: String,
+    pub source: String,
+    pub at: Option<TimeTravel>,
+    pub or_replace: bool,
+    pub if_not_exists: bool,
+}
+
+impl CreateClone {
+    pub fn new(kind: CloneKind, name: impl Into<String>, source: impl Into<String>) -> Self {
+        CreateClone {
+            kind,
+            name: name.into(),
+            source: source.into(),
+            at: None,
+            or_replace: false,
+            if_not_exists: false,
+        }
+    }
+    pub fn at(mut self, at: TimeTravel) -> Self {
+        self.at = Some(at);
+        self
+    }
+    pub fn or_replace(mut self) -> Self {
+        self.or_replace = true;
+        self
+    }
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+    pub fn to_sql(&self) -> Result<String, SnowflakeError> {
+        check_replace(&self.name, self.or_replace, self.if_not_exists)?;
+        let mut statement = create_prefix(self.kind.as_sql(), &escape_qualified_name(&self.name), self.or_replace, self.if_not_exists);
+        statement.push_str(&format!(" CLONE {}", escape_qualified_name(&self.source)));
+        if let Some(at) = &self.at {
+            statement.push(' ');
+            statement.push_str(&at.to_sql());
+        }
+        Ok(statement)
+    }
+}
+
+/// Object created by [`SnowflakeExecutor::create_clone`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClonedObject {
+    pub kind: CloneKind,
+    pub name: String,
+    pub source: String,
+    /// Status Snowflake returned, ex. `Table ORDERS successfully created.`
+    pub status: String,
+}
+
+impl<'a> SnowflakeExecutor<'a> {
+    pub async fn create_table(self, table: &CreateTable) -> Result<(), SnowflakeError> {
+        self.ddl(table.to_sql()?).await
+    }
+    pub async fn add_column(self, add_column: &AddColumn) -> Result<(), SnowflakeError> {
+        self.ddl(add_column.to_sql()).await
+    }
+    pub async fn drop_table(self, table: &DropTable) -> Result<(), SnowflakeError> {
+        self.ddl(table.to_sql()).await
+    }
+    pub async fn create_clone(self, clone: &CreateClone) -> Result<ClonedObject, SnowflakeError> {
+        let status = self.statement(Cow::Owned(clone.to_sql()?))?
+            .select_ddl().await?
+            .into_iter()
+            .next()
+            .map(|row| row.status)
+            .unwrap_or_default();
+        Ok(ClonedObject {
+            kind: clone.kind,
+            name: clone.name.clone(),
+            source: clone.source.clone(),
+            status,
+        })
+    }
+    /// `CREATE DATABASE <target> CLONE <source>`, at `at` if given, else now.
+    pub async fn clone_database(self, source: &str, target: &str, at: Option<TimeTravel>) -> Result<ClonedObject, SnowflakeError> {
+        self.clone_at(CreateClone::new(CloneKind::Database, target, source), at).await
+    }
+    /// `CREATE SCHEMA <target> CLONE <source>`, at `at` if given, else now.
+    pub async fn clone_schema(self, source: &str, target: &str, at: Option<TimeTravel>) -> Result<ClonedObject, SnowflakeError> {
+        self.clone_at(CreateClone::new(CloneKind::Schema, target, source), at).await
+    }
+    /// `CREATE TABLE <target> CLONE <source>`, at `at` if given, else now.
+    pub async fn clone_table(self, source: &str, target: &str, at: Option<TimeTravel>) -> Result<ClonedObject, SnowflakeError> {
+        self.clone_at(CreateClone::new(CloneKind::Table, target, source), at).await
+    }
+    async fn clone_at(self, clone: CreateClone, at: Option<TimeTravel>) -> Result<ClonedObject, SnowflakeError> {
+        self.create_clone(&CreateClone { at, ..clone }).await
+    }
+    pub(crate) async fn ddl(self, statement: String) -> Result<(), SnowflakeError> {
+        self.statement(Cow::Owned(statement))?.select_ddl().await?;
+        Ok(())
+    }
+}
+
+pub(crate) fn create_prefix(object_type: &str, name: &str, or_replace: bool, if_not_exists: bool) -> String {
+    format!(
+        "CREATE {}{object_type} {}{name}",
+        if or_replace { "OR REPLACE " } else { "" },
+        if if_not_exists { "IF NOT EXISTS " } else { "" },
+    )
+}
+
+/// Snowflake rejects `OR REPLACE` together with `IF NOT EXISTS`.
+fn check_replace(name: &str, or_replace: bool, if_not_exists: bool) -> Result<(), SnowflakeError> {
+    match or_replace && if_not_exists {
+        true => Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+            "{name} can not be created with both OR REPLACE and IF NOT EXISTS",
+        ))),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    struct Order;
+
+    impl SnowflakeTable for Order {
+        const TABLE: &'static str = "DB.PUBLIC.ORDERS";
+        const COLUMNS: &'static [ColumnMeta] = &[
+            ColumnMeta { name: "ID", data_type: "NUMBER(38,0)", nullable: false },
+            ColumnMeta { name: "note", data_type: "VARCHAR", nullable: true },
+        ];
+    }
+
+    #[test]
+    fn statements() -> Result<(), SnowflakeError> {
+        assert_eq!(
+            CreateTable::from_table::<Order>().if_not_exists().with_comment("it's").to_sql()?,
+            "CREATE TABLE IF NOT EXISTS DB.PUBLIC.ORDERS (ID NUMBER(38,0) NOT NULL, \"note\" VARCHAR) COMMENT = 'it\\'s'",
+        );
+        assert!(CreateTable::new("T").to_sql().is_err());
+        assert!(CreateTable::from_table::<Order>().or_replace().if_not_exists().to_sql().is_err());
+        assert_eq!(
+            AddColumn::new("DB.PUBLIC.ORDERS", ColumnDefinition::new("TOTAL", "NUMBER(12,2)").with_comment("USD")).if_not_exists().to_sql(),
+            "ALTER TABLE DB.PUBLIC.ORDERS ADD COLUMN IF NOT EXISTS TOTAL NUMBER(12,2) COMMENT 'USD'",
+        );
+        assert_eq!(DropTable::new("DB.PUBLIC.order items").if_exists().to_sql(), "DROP TABLE IF EXISTS DB.PUBLIC.\"order items\"");
+        assert_eq!(
+            CreateClone::new(CloneKind::Table, "DB.TEST.ORDERS", "DB.PUBLIC.ORDERS").or_replace().to_sql()?,
+            "CREATE OR REPLACE TABLE DB.TEST.ORDERS CLONE DB.PUBLIC.ORDERS",
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn clones() -> Result<(), anyhow::Error> {
+        assert_eq!(
+            CreateClone::new(CloneKind::Database, "TEST_RUN_1", "PROD").at(TimeTravel::Offset(Duration::from_secs(3600))).to_sql()?,
+            "CREATE DATABASE TEST_RUN_1 CLONE PROD AT(OFFSET => -3600)",
+        );
+        assert_eq!(
+            TimeTravel::AtTimestamp("2024-03-01 12:00:00 +0000".into()).to_sql(),
+            "AT(TIMESTAMP => '2024-03-01 12:00:00 +0000'::TIMESTAMP_TZ)",
+        );
+        assert_eq!(TimeTravel::BeforeStatement("01b2-handle".into()).to_sql(), "BEFORE(STATEMENT => '01b2-handle')");
+
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&StatementFixture::new()
+            .column("status", "text")
+            .row([Some("Schema TEST_RUN_1 successfully created.")])).await;
+        let connector = snowflake.connector();
+        let cloned = connector.execute("DB", "WH").clone_schema("DB.PUBLIC", "DB.TEST_RUN_1", None).await?;
+        assert_eq!(cloned, ClonedObject {
+            kind: CloneKind::Schema,
+            name: "DB.TEST_RUN_1".into(),
+            source: "DB.PUBLIC".into(),
+            status: "Schema TEST_RUN_1 successfully created.".into(),
+        });
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn status_rows() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&StatementFixture::new()
+            .column("status", "text")
+            .row([Some("Table T successfully created.")])).await;
+        let connector = snowflake.connector();
+        let rows = connector.execute("DB", "WH").sql("CREATE TABLE T (ID INT)")?.select_ddl().await?;
+        assert_eq!(rows[0].status, "Table T successfully created.");
+
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&StatementFixture::new()).await;
+        let connector = snowflake.connector();
+        assert!(connector.execute("DB", "WH").sql("ALTER SESSION UNSET TIMEZONE")?.select_ddl().await?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+//! Known Snowflake SQL API error codes, with remediation and whether retrying can help.
+
+use std::fmt::Display;
+
+use crate::errors::SnowflakeError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// `090001`, statement executed successfully.
+    Success,
+    /// `333334`, asynchronous execution in progress.
+    InProgress,
+    /// `390112`, session expired.
+    SessionExpired,
+    /// `390114`, authentication token expired.
+    TokenExpired,
+    /// `390144`, JWT is invalid.
+    InvalidToken,
+    /// `390318`, too many requests.
+    RateLimited,
+    /// `000604` or `000605`, statement was cancelled.
+    Cancelled,
+    /// `000606`, no active warehouse selected.
+    NoActiveWarehouse,
+    /// `000630`, statement reached its statement or warehouse timeout.
+    StatementTimeout,
+    /// `001003`, SQL compilation error, ex. a syntax error.
+    SqlCompilation,
+    /// `002003`, object does not exist or not authorized.
+    ObjectNotFound,
+}
+
+impl ErrorCode {
+    pub fn from_code(code: &str) -> Option<Self> {
+        Some(match code {
+            "090001" => ErrorCode::Success,
+            "333334" => ErrorCode::InProgress,
+            "390112" => ErrorCode::SessionExpired,
+            "390114" => ErrorCode::TokenExpired,
+            "390144" => ErrorCode::InvalidToken,
+            "390318" => ErrorCode::RateLimited,
+            "000604" | "000605" => ErrorCode::Cancelled,
+            "000606" => ErrorCode::NoActiveWarehouse,
+            "000630" => ErrorCode::StatementTimeout,
+            "001003" => ErrorCode::SqlCompilation,
+            "002003" => ErrorCode::ObjectNotFound,
+            _ => return None,
+        })
+    }
+    /// Canonical code, for codes with several values the first.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ErrorCode::Success => "090001",
+            ErrorCode::InProgress => "333334",
+            ErrorCode::SessionExpired => "390112",
+            ErrorCode::TokenExpired => "390114",
+            ErrorCode::InvalidToken => "390144",
+            ErrorCode::RateLimited => "390318",
+            ErrorCode::Cancelled => "000604",
+            ErrorCode::NoActiveWarehouse => "000606",
+            ErrorCode::StatementTimeout => "000630",
+            ErrorCode::SqlCompilation => "001003",
+            ErrorCode::ObjectNotFound => "002003",
+        }
+    }
+    /// What to do about it.
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            ErrorCode::Success => "nothing to do",
+            ErrorCode::InProgress => "poll the statement handle until it finishes",
+            ErrorCode::SessionExpired |
+            ErrorCode::TokenExpired
+                => "create a new connector to issue a fresh token, then retry",
+            ErrorCode::InvalidToken => "check the account identifier, user and that the public key is assigned to the user",
+            ErrorCode::RateLimited => "retry with backoff, or reduce concurrency",
+            ErrorCode::Cancelled => "the statement was cancelled, resubmit it if that was not intended",
+            ErrorCode::NoActiveWarehouse => "pass a warehouse the role can use, or resume the warehouse",
+            ErrorCode::StatementTimeout => "raise the statement timeout or STATEMENT_TIMEOUT_IN_SECONDS, or use a bigger warehouse",
+            ErrorCode::SqlCompilation => "fix the statement, retrying will not help",
+            ErrorCode::ObjectNotFound => "check the object name, database and schema, and that the role is granted access",
+        }
+    }
+    /// Whether sending the same request again can succeed, possibly after refreshing the token.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ErrorCode::InProgress
+                | ErrorCode::SessionExpired
+                | ErrorCode::TokenExpired
+                | ErrorCode::RateLimited
+        )
+    }
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({:?})", self.code(), self)
+    }
+}
+
+impl SnowflakeError {
+    /// Code Snowflake returned, if any.
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            SnowflakeError::StatementFailed(status) => Some(&status.code),
+            SnowflakeError::StatementTimedOut(status) => Some(&status.code),
+            _ => None,
+        }
+    }
+    /// Known code Snowflake returned, if any.
+    pub fn error_code(&self) -> Option<ErrorCode> {
+        self.code().and_then(ErrorCode::from_code)
+    }
+    /// Whether sending the same request again can succeed, false for unknown codes.
+    pub fn is_retryable(&self) -> bool {
+        self.error_code().is_some_and(|code| code.is_retryable())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_codes() {
+        assert_eq!(ErrorCode::from_code("390114"), Some(ErrorCode::TokenExpired));
+        assert_eq!(ErrorCode::from_code("000605"), Some(ErrorCode::Cancelled));
+        assert_eq!(ErrorCode::from_code("123456"), None);
+        assert!(ErrorCode::RateLimited.is_retryable());
+        assert!(!ErrorCode::SqlCompilation.is_retryable());
+        assert_eq!(ErrorCode::TokenExpired.to_string(), "390114 (TokenExpired)");
+    }
+}
@@ -1,13 +1,58 @@
-use crate::jwt::KeyPairError;
+use crate::{explain::QueryEstimate, jwt::KeyPairError, status::{QueryFailureStatus, QueryStatus}};
 
+/// Variants carry their cause as [`source`](std::error::Error::source), new ones may be added in minor releases.
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum SnowflakeError {
     #[error(transparent)]
     Token(#[from] KeyPairError),
-    #[error("could not start client—{0}")]
-    SqlClient(anyhow::Error),
-    #[error("could not execute sql statement—{0}")]
-    SqlExecution(anyhow::Error),
-    #[error("failed to parse returned json—{0}")]
-    SqlResultParse(anyhow::Error),
+    #[error("could not start client")]
+    SqlClient(#[source] anyhow::Error),
+    #[error("could not execute sql statement")]
+    SqlExecution(#[source] anyhow::Error),
+    #[error("failed to parse returned json")]
+    SqlResultParse(#[source] anyhow::Error),
+    #[error("invalid statement")]
+    InvalidStatement(#[source] anyhow::Error),
+    #[error("expected a single row, query returned {0}")]
+    UnexpectedRowCount(usize),
+    #[error("statement {} failed with code {}—{}", .0.statement_handle, .0.code, .0.message)]
+    StatementFailed(Box<QueryFailureStatus>),
+    /// Exceeded its timeout and may still be running, see [`SnowflakeConnector::cancel_or_wait`](crate::SnowflakeConnector::cancel_or_wait).
+    #[error("statement {} timed out and may still be running—{}", .0.statement_handle, .0.message)]
+    StatementTimedOut(Box<QueryStatus>),
+    #[error("connector is shutting down, no new statements are accepted")]
+    ShuttingDown,
+    #[error("invalid account identifier")]
+    InvalidAccountIdentifier(#[source] anyhow::Error),
+    #[error("could not load or save checkpoint")]
+    Checkpoint(#[source] anyhow::Error),
+    /// Rejected before it ran, see [`CostLimits`](crate::explain::CostLimits).
+    #[error("statement would scan {} partition(s) of {} byte(s), more than its cost limits allow", .0.partitions_assigned, .0.bytes_assigned)]
+    QueryTooExpensive(Box<QueryEstimate>),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn source_chain() {
+        let io = std::io::Error::new(std::io::ErrorKind::NotFound, "missing");
+        let error = SnowflakeError::SqlExecution(anyhow::Error::new(io).context("sending statement"));
+        assert!(!error.to_string().contains("sending statement"), "causes are left to the source chain");
+        let source = error.source().expect("source");
+        assert_eq!(source.to_string(), "sending statement");
+        assert_eq!(source.source().expect("io error").to_string(), "missing");
+        assert_eq!(format!("{:#}", anyhow::Error::from(error)), "could not execute sql statement: sending statement: missing");
+
+        let error = SnowflakeError::from(KeyPairError::PublicKeyRead(
+            std::io::Error::new(std::io::ErrorKind::NotFound, "missing"),
+            "key.pub".into(),
+        ));
+        assert_eq!(error.source().map(ToString::to_string).as_deref(), Some("missing"));
+        assert!(SnowflakeError::ShuttingDown.source().is_none());
+    }
 }
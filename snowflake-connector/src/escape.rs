@@ -0,0 +1,119 @@
+//! Escape values for the rare statements that can not bind them, prefer [`add_binding`](crate::SnowflakeSQL::add_binding).
+//!
+//! Backslashes start escape sequences in Snowflake string literals, so they are escaped along with quotes.
+//! `%` and `_` are wildcards of `LIKE`, escape them in user input matched literally:
+//!
+//! ```no_run
+//! # use snowflake_connector::{escape::escape_like_pattern, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector, input: &str) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let users = connector.execute("DB", "WH")
+//!     .sql("SELECT NAME FROM USERS WHERE NAME LIKE ? ESCAPE '!'")?
+//!     .add_binding(format!("%{}%", escape_like_pattern(input, '!')))
+//!     .select::<String>().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+/// `value` as a single quoted string literal, ex. `it's` becomes `'it\'s'`.
+pub fn escape_string_literal(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('\'');
+    for c in value.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '\'' => literal.push_str("\\'"),
+            '\0' => literal.push_str("\\0"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+/// `pattern` with `%`, `_` and `escape` itself prefixed by `escape`, so `LIKE ... ESCAPE '<escape>'` matches it literally.
+///
+/// The statement must name the same character in its `ESCAPE` clause, `LIKE` has no escape character otherwise.
+pub fn escape_like_pattern(pattern: &str, escape: char) -> String {
+    let mut escaped = String::with_capacity(pattern.len());
+    for c in pattern.chars() {
+        if c == '%' || c == '_' || c == escape {
+            escaped.push(escape);
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// `identifier` as is if Snowflake resolves it unquoted to itself, else double quoted,
+/// ex. `ORDERS` stays as is while `order items` becomes `"order items"`.
+pub fn escape_identifier(identifier: &str) -> String {
+    let unquoted = identifier.starts_with(|c: char| c.is_ascii_uppercase() || c == '_')
+        && identifier.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_' || c == '$');
+    match unquoted {
+        true => identifier.to_owned(),
+        false => format!("\"{}\"", identifier.replace('"', "\"\"")),
+    }
+}
+
+/// Each part of a `.` separated name escaped with [`escape_identifier`], parts that are already double quoted are kept,
+/// ex. `DB.PUBLIC.order items` becomes `DB.PUBLIC."order items"`.
+pub fn escape_qualified_name(name: &str) -> String {
+    let mut parts = Vec::new();
+    let mut rest = name;
+    while !rest.is_empty() {
+        let length = match rest.starts_with('"') {
+            true => crate::named_bindings::quoted(rest, '"'),
+            false => rest.find('.').unwrap_or(rest.len()),
+        };
+        let part = &rest[..length];
+        parts.push(if part.starts_with('"') { part.to_owned() } else { escape_identifier(part) });
+        rest = rest[length..].strip_prefix('.').unwrap_or(&rest[length..]);
+    }
+    parts.join(".")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::MockSnowflake;
+
+    use super::*;
+
+    #[test]
+    fn literals() {
+        assert_eq!(escape_string_literal("it's"), "'it\\'s'");
+        assert_eq!(escape_string_literal("C:\\temp\\'"), "'C:\\\\temp\\\\\\''");
+        assert_eq!(escape_string_literal("naïve 日本 🦀\n"), "'naïve 日本 🦀\n'");
+        assert_eq!(escape_string_literal("a\0b"), "'a\\0b'");
+    }
+
+    #[test]
+    fn like_patterns() {
+        assert_eq!(escape_like_pattern("100%_done", '!'), "100!%!_done");
+        assert_eq!(escape_like_pattern("wow!", '!'), "wow!!");
+        assert_eq!(escape_like_pattern("a\\b%", '\\'), "a\\\\b\\%");
+        assert_eq!(escape_like_pattern("日本_🦀", '^'), "日本^_🦀");
+    }
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(escape_identifier("ORDERS_2024"), "ORDERS_2024");
+        assert_eq!(escape_identifier("orders"), "\"orders\"");
+        assert_eq!(escape_identifier("a \"b\""), "\"a \"\"b\"\"\"");
+        assert_eq!(escape_identifier("1ST"), "\"1ST\"");
+        assert_eq!(escape_qualified_name("DB.PUBLIC.order items"), "DB.PUBLIC.\"order items\"");
+        assert_eq!(escape_qualified_name("DB.\"my.schema\".T"), "DB.\"my.schema\".T");
+        assert_eq!(escape_qualified_name("\"a\"\".b\".c"), "\"a\"\".b\".\"c\"");
+    }
+
+    #[tokio::test]
+    async fn unicode_statements() -> Result<(), anyhow::Error> {
+        let connector = MockSnowflake::start().await.connector();
+        let sql = connector.execute("DB", "WH")
+            .sql("SELECT \"名前\" FROM T WHERE C = '🦀' AND D LIKE ? ESCAPE '!'")?
+            .add_binding(escape_like_pattern("ü_'\\%", '!'));
+        let body = serde_json::to_value(crate::payload::StatementBody(&sql.statement))?;
+        assert_eq!(body["statement"], "SELECT \"名前\" FROM T WHERE C = '🦀' AND D LIKE ? ESCAPE '!'");
+        assert_eq!(body["bindings"]["1"]["value"], "ü!_'\\!%");
+        Ok(())
+    }
+}
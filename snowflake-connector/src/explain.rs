@@ -0,0 +1,232 @@
+//! Estimate what a statement scans with `EXPLAIN` and reject it before it runs if that is over limits,
+//! ex. for ad-hoc queries written by users.
+//!
+//! Estimates come from the compiled plan, so they are the partitions and bytes left after pruning
+//! by the statement's filters, not what it reads at runtime.
+//!
+//! Only queries and DML can be explained, other statements, ex. DDL, are not checked.
+//! Statements of [`multiple_sql`](crate::SnowflakeExecutor::multiple_sql) are checked one by one before any runs,
+//! those of a [`script`](crate::SnowflakeExecutor::script) are not, they are not known before it runs.
+//!
+//! ```no_run
+//! # use snowflake_connector::{explain::CostLimits, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector, user_query: &str) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let connector = connector.with_cost_limits(CostLimits::new().max_bytes(10 << 30));
+//! match connector.execute("DB", "WH").sql(user_query)?.text().await {
+//!     Err(snowflake_connector::errors::SnowflakeError::QueryTooExpensive(estimate)) => {
+//!         println!("query would scan {} bytes", estimate.bytes_assigned);
+//!     },
+//!     result => println!("{}", result?),
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::Deserialize;
+
+use crate::{errors::SnowflakeError, payload::StatementBody, status::{self, StatementOutcome}, SnowflakeConnector, SnowflakeSQL};
+
+/// What the plan of a statement scans, from the `GlobalStats` of `EXPLAIN USING JSON`.
+#[derive(Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryEstimate {
+    /// Micro-partitions of the tables the statement reads.
+    pub partitions_total: u64,
+    /// Micro-partitions left after pruning.
+    pub partitions_assigned: u64,
+    /// Size of the partitions left after pruning.
+    pub bytes_assigned: u64,
+}
+
+/// Most a statement may scan, limits that are `None` are not checked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CostLimits {
+    pub max_partitions: Option<u64>,
+    pub max_bytes: Option<u64>,
+}
+
+impl CostLimits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn max_partitions(mut self, partitions: u64) -> Self {
+        self.max_partitions = Some(partitions);
+        self
+    }
+    pub fn max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+    /// Whether `estimate` is within every limit.
+    pub fn allows(&self, estimate: &QueryEstimate) -> bool {
+        self.max_partitions.is_none_or(|max| estimate.partitions_assigned <= max)
+            && self.max_bytes.is_none_or(|max| estimate.bytes_assigned <= max)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct Plan {
+    global_stats: QueryEstimate,
+}
+
+impl SnowflakeConnector {
+    /// Check every statement against `limits` before running it, see [`SnowflakeSQL::with_cost_limits`].
+    pub fn with_cost_limits(mut self, limits: CostLimits) -> Self {
+        self.cost_limits = Some(limits);
+        self
+    }
+}
+
+impl<'a> SnowflakeSQL<'a> {
+    /// Run `EXPLAIN USING JSON` on the statement first and fail with [`SnowflakeError::QueryTooExpensive`]
+    /// instead of running it if its [`estimate`](Self::estimate) is over `limits`, replacing the connector's limits.
+    ///
+    /// Costs one more request per statement, partitions of its result are not checked again.
+    pub fn with_cost_limits(mut self, limits: CostLimits) -> SnowflakeSQL<'a> {
+        self.cost_limits = Some(limits);
+        self
+    }
+    /// Run the statement without checking the connector's [`CostLimits`].
+    pub fn without_cost_limits(mut self) -> SnowflakeSQL<'a> {
+        self.cost_limits = None;
+        self
+    }
+    /// What the statement would scan, runs `EXPLAIN USING JSON` on it.
+    pub async fn estimate(&self) -> Result<QueryEstimate, SnowflakeError> {
+        let mut explain = self.clone();
+        // A resubmitted request id is a retry of the same request.
+        explain.uuid = uuid::Uuid::new_v4();
+        explain.wrap_statement("EXPLAIN USING JSON ", "");
+        let _in_flight = explain.in_flight.begin()?;
+        let response = explain.client
+            .post(explain.get_url())
+            .json(&StatementBody(&explain.statement))
+            .send().await
+            .map_err(|e| explain.correlate(None, SnowflakeError::SqlExecution(e.into())))?;
+        let response = match status::parse_outcome(response, &explain.null_sentinel).await.map_err(|e| explain.correlate(None, e))? {
+            StatementOutcome::Success(response) => response,
+            StatementOutcome::Failed(status) => return Err(SnowflakeError::StatementFailed(Box::new(status))),
+            StatementOutcome::TimedOut(status) => return Err(SnowflakeError::StatementTimedOut(Box::new(status))),
+            StatementOutcome::Pending(status) => return Err(explain.correlate(None, SnowflakeError::SqlExecution(
+                anyhow::anyhow!("statement {} did not compile in time—{}", status.statement_handle, status.message),
+            ))),
+        };
+        let plan = response.data.first()
+            .and_then(|row| row.first())
+            .ok_or_else(|| SnowflakeError::SqlResultParse(anyhow::anyhow!("EXPLAIN returned no plan")))?;
+        serde_json::from_str::<Plan>(plan)
+            .map(|plan| plan.global_stats)
+            .map_err(|e| SnowflakeError::SqlResultParse(e.into()))
+    }
+    /// Fail if the statement's estimate is over its [`CostLimits`], if any and it can be explained.
+    pub(crate) async fn check_cost(&self) -> Result<(), SnowflakeError> {
+        let Some(limits) = self.cost_limits else {
+            return Ok(());
+        };
+        if !explainable(&self.statement.statement) {
+            return Ok(());
+        }
+        let estimate = self.estimate().await?;
+        match limits.allows(&estimate) {
+            true => Ok(()),
+            false => Err(SnowflakeError::QueryTooExpensive(Box::new(estimate))),
+        }
+    }
+}
+
+/// Statements `EXPLAIN` supports, queries and DML.
+fn explainable(statement: &str) -> bool {
+    const KEYWORDS: &[&str] = &["SELECT", "WITH", "INSERT", "UPDATE", "DELETE", "MERGE"];
+    let mut statement = statement;
+    loop {
+        statement = statement.trim_start_matches(|c: char| c.is_whitespace() || c == '(');
+        if let Some(comment) = statement.strip_prefix("--").or_else(|| statement.strip_prefix("//")) {
+            statement = comment.split_once('\n').map_or("", |(_, rest)| rest);
+        } else if let Some(comment) = statement.strip_prefix("/*") {
+            statement = comment.split_once("*/").map_or("", |(_, rest)| rest);
+        } else {
+            break;
+        }
+    }
+    let keyword = statement.split(|c: char| !c.is_ascii_alphabetic()).next().unwrap_or_default();
+    KEYWORDS.iter().any(|explainable| explainable.eq_ignore_ascii_case(keyword))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{matchers::{body_string_contains, method}, Mock};
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn limits() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        let plan = json!({
+            "GlobalStats": { "partitionsTotal": 40, "partitionsAssigned": 12, "bytesAssigned": 4096 },
+            "Operations": [],
+        });
+        Mock::given(method("POST"))
+            .and(body_string_contains("EXPLAIN USING JSON"))
+            .respond_with(StatementFixture::new().column("content", "text").row([Some(plan.to_string())]).success())
+            .with_priority(1)
+            .mount(&snowflake.server).await;
+        snowflake.mount(&StatementFixture::new().column("ID", "fixed").row([Some("1")])).await;
+        let connector = snowflake.connector().with_cost_limits(CostLimits::new().max_bytes(1024));
+        let sql = || connector.execute("DB", "WH").sql("SELECT ID FROM EVENTS");
+        assert_eq!(sql()?.estimate().await?, QueryEstimate { partitions_total: 40, partitions_assigned: 12, bytes_assigned: 4096 });
+        match sql()?.select::<(u32,)>().await {
+            Err(SnowflakeError::QueryTooExpensive(estimate)) => assert_eq!(estimate.bytes_assigned, 4096),
+            result => panic!("expected the statement to be rejected, got {result:?}"),
+        }
+        assert_eq!(sql()?.with_cost_limits(CostLimits::new().max_partitions(12)).select::<(u32,)>().await?.data, [(1,)]);
+        assert_eq!(sql()?.without_cost_limits().select::<(u32,)>().await?.data, [(1,)]);
+        Ok(())
+    }
+
+    #[test]
+    fn explainable_statements() {
+        assert!(explainable("  -- latest\n/* events */ (SELECT 1)"));
+        assert!(explainable("insert into T values (1)"));
+        assert!(!explainable("CREATE TABLE T (ID INT)"));
+        assert!(!explainable("BEGIN"));
+    }
+
+    #[tokio::test]
+    async fn multiple_statements() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        let plan = |bytes: u64| json!({
+            "GlobalStats": { "partitionsTotal": 1, "partitionsAssigned": 1, "bytesAssigned": bytes },
+            "Operations": [],
+        }).to_string();
+        for (statement, bytes) in [("EXPLAIN USING JSON SELECT ID FROM SMALL", 1), ("EXPLAIN USING JSON SELECT ID FROM LARGE", 4096)] {
+            Mock::given(method("POST"))
+                .and(body_string_contains(statement))
+                .respond_with(StatementFixture::new().column("content", "text").row([Some(plan(bytes))]).success())
+                .with_priority(1)
+                .mount(&snowflake.server).await;
+        }
+        snowflake.mount(&StatementFixture::new().column("ID", "fixed").row([Some("1")]).with_child_handles(["a", "b"])).await;
+        let connector = snowflake.connector().with_cost_limits(CostLimits::new().max_bytes(1024));
+        let executor = || connector.execute("DB", "WH");
+
+        executor().script("CREATE TABLE LARGE (ID INT); SELECT ID FROM LARGE; -- done")?.run().await?;
+        executor().multiple_sql(&["CREATE TABLE SMALL (ID INT)", "SELECT ID FROM SMALL"])?.run().await?;
+        let explained = |requests: &[wiremock::Request]| requests.iter()
+            .filter(|request| String::from_utf8_lossy(&request.body).contains("EXPLAIN"))
+            .count();
+        assert_eq!(explained(&snowflake.server.received_requests().await.unwrap_or_default()), 1);
+
+        match executor().multiple_sql(&["SELECT ID FROM SMALL", "SELECT ID FROM LARGE"])?.run().await {
+            Err(SnowflakeError::QueryTooExpensive(estimate)) => assert_eq!(estimate.bytes_assigned, 4096),
+            result => panic!("expected the request to be rejected, got {result:?}"),
+        }
+        let requests = snowflake.server.received_requests().await.unwrap_or_default();
+        assert_eq!(explained(&requests), 3);
+        assert!(String::from_utf8_lossy(&requests[requests.len() - 1].body).contains("EXPLAIN"), "nothing runs once a statement is rejected");
+        Ok(())
+    }
+}
@@ -0,0 +1,156 @@
+//! Retry inserts, updates and deletes without applying them twice.
+//!
+//! Snowflake executes a request id once: a request resubmitted with the same id and the `retry` flag
+//! returns the outcome of the first one instead of running the statement again, ex. when the response
+//! to the first one was lost to a network error. That only holds for the same id and the same statement,
+//! so persist the [`IdempotencyKey`] along with the operation before sending it, and never reuse it for another.
+//!
+//! ```no_run
+//! # use snowflake_connector::{idempotency::IdempotencyKey, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! // Derived from the operation, so a restarted job resubmits the same request id.
+//! let key = IdempotencyKey::from_name("load-orders-2024-03-01");
+//! let result = connector.execute("DB", "WH")
+//!     .sql("INSERT INTO ORDERS SELECT * FROM STAGED_ORDERS WHERE DAY = '2024-03-01'")?
+//!     .manipulate_idempotent(key, 3).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fmt, str::FromStr};
+
+use reqwest::StatusCode;
+
+use crate::{data_manipulation::DataManipulationResult, errors::SnowflakeError, payload::StatementBody, polling::Backoff, SnowflakeSQL};
+
+/// Namespace of the request ids of [`IdempotencyKey::from_name`].
+const NAMESPACE: uuid::Uuid = uuid::uuid!("5d1f3b5e-0c55-4d57-9a43-3f1f0e6e7c21");
+
+/// Request id a statement is always sent with, see the [module](self).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey(uuid::Uuid);
+
+impl IdempotencyKey {
+    /// Random key, persist it before sending the statement.
+    pub fn new() -> Self {
+        IdempotencyKey(uuid::Uuid::new_v4())
+    }
+    /// Key derived from `name`, the same name always gives the same key.
+    pub fn from_name(name: &str) -> Self {
+        IdempotencyKey(uuid::Uuid::new_v5(&NAMESPACE, name.as_bytes()))
+    }
+    pub fn request_id(&self) -> uuid::Uuid {
+        self.0
+    }
+}
+
+impl Default for IdempotencyKey {
+    fn default() -> Self {
+        IdempotencyKey::new()
+    }
+}
+
+impl From<uuid::Uuid> for IdempotencyKey {
+    fn from(request_id: uuid::Uuid) -> Self {
+        IdempotencyKey(request_id)
+    }
+}
+
+impl fmt::Display for IdempotencyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for IdempotencyKey {
+    type Err = uuid::Error;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(IdempotencyKey)
+    }
+}
+
+impl SnowflakeSQL<'_> {
+    /// Like [`manipulate`](Self::manipulate) with `key` as the request id, resubmitted as a retry up to `retries` times
+    /// when the request failed to send or Snowflake answered with a server error or `429 Too Many Requests`.
+    ///
+    /// Sending it again later with the same key, ex. after a crash, is a retry as well.
+    pub async fn manipulate_idempotent(mut self, key: IdempotencyKey, retries: u32) -> Result<DataManipulationResult, SnowflakeError> {
+        self.check_cost().await
+            .map_err(|e| self.correlate(None, e))?;
+        let _in_flight = self.in_flight.begin()?;
+        self.uuid = key.request_id();
+        let backoff = Backoff::default();
+        let mut attempt = 0;
+        loop {
+            // Snowflake only deduplicates requests flagged as retries, also flag the first one
+            // in case an earlier process already sent it.
+            let response = self.client
+                .post(format!("{}&retry=true", self.get_url()))
+                .json(&StatementBody(&self.statement))
+                .send().await;
+            let retryable = match &response {
+                Ok(response) => response.status().is_server_error() || response.status() == StatusCode::TOO_MANY_REQUESTS,
+                Err(e) => !e.is_builder(),
+            };
+            if retryable && attempt < retries {
+                tokio::time::sleep(backoff.delay(attempt)).await;
+                attempt += 1;
+                continue;
+            }
+            return response
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(|e| self.correlate(None, SnowflakeError::SqlExecution(e.into())))?
+                .json().await
+                .map_err(|e| self.correlate(None, SnowflakeError::SqlResultParse(e.into())));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::{matchers::{method, query_param}, Mock, ResponseTemplate};
+
+    use crate::test_util::MockSnowflake;
+
+    use super::*;
+
+    #[test]
+    fn keys() {
+        assert_eq!(IdempotencyKey::from_name("load-1"), IdempotencyKey::from_name("load-1"));
+        assert_ne!(IdempotencyKey::from_name("load-1"), IdempotencyKey::from_name("load-2"));
+        let key = IdempotencyKey::new();
+        assert_eq!(key.to_string().parse::<IdempotencyKey>().unwrap(), key);
+    }
+
+    #[tokio::test]
+    async fn retried() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        let key = IdempotencyKey::from_name("load-1");
+        Mock::given(method("POST"))
+            .and(query_param("requestId", key.to_string()))
+            .and(query_param("retry", "true"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&snowflake.server).await;
+        Mock::given(method("POST"))
+            .and(query_param("requestId", key.to_string()))
+            .and(query_param("retry", "true"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "message": "successfully executed",
+                "stats": { "numRowsInserted": 2, "numRowsDeleted": 0, "numRowsUpdated": 0, "numDmlDuplicates": 0 },
+                "statementHandle": "handle",
+                "requestId": key.to_string(),
+            })))
+            .mount(&snowflake.server).await;
+        let result = snowflake.connector()
+            .execute("DB", "WH")
+            .sql("INSERT INTO T VALUES (1), (2)")?
+            .manipulate_idempotent(key, 1).await?;
+        assert_eq!(result.stats.rows_inserted, 2);
+        assert_eq!(result.request_id, key.to_string());
+        assert_eq!(snowflake.server.received_requests().await.map(|requests| requests.len()), Some(2));
+        Ok(())
+    }
+}
@@ -0,0 +1,141 @@
+//! Bind a list of values to a single `?` placeholder of an `IN` condition, instead of formatting them into the statement.
+//!
+//! ```no_run
+//! # use snowflake_connector::{in_list::In, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector, ids: Vec<i64>) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! // Sent as `... WHERE STATUS = ? AND ID IN (?, ?, ?)`.
+//! let orders: Vec<(i64, String)> = connector.execute("DB", "WH")
+//!     .sql("SELECT ID, STATUS FROM ORDERS WHERE STATUS = ? AND ID IN ?")?
+//!     .add_binding("OPEN")
+//!     .select_in_chunks(In::new(ids)).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use snowflake_deserializer::{bindings::BindingValue, SnowflakeDeserialize};
+
+use crate::{errors::SnowflakeError, named_bindings, SnowflakeSQL};
+
+/// Most bindings Snowflake accepts in a statement.
+pub const MAX_BINDINGS: usize = 16_384;
+
+/// Values of an `IN` list, see [`SnowflakeSQL::add_in_binding`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct In(pub Vec<BindingValue>);
+
+impl In {
+    pub fn new<V: Into<BindingValue>>(values: impl IntoIterator<Item = V>) -> Self {
+        In(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<'a> SnowflakeSQL<'a> {
+    /// Bind `list` to the next `?` placeholder, which is expanded to `(?, ?, ...)` with a placeholder per value.
+    ///
+    /// Errors if the list is empty, which Snowflake rejects, if the statement has no unbound `?` placeholder
+    /// or if the statement would have more than [`MAX_BINDINGS`], see [`select_in_chunks`](Self::select_in_chunks).
+    pub fn add_in_binding(mut self, list: In) -> Result<SnowflakeSQL<'a>, SnowflakeError> {
+        if list.0.is_empty() {
+            return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!("IN list has no values")));
+        }
+        let bound = self.statement.bindings.as_ref().map_or(0, |bindings| bindings.len());
+        if bound + list.0.len() > MAX_BINDINGS {
+            return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                "IN list of {} value(s) exceeds the {MAX_BINDINGS} bindings of a statement with {bound} bound",
+                list.0.len(),
+            )));
+        }
+        let offset = *placeholders(&self.statement.statement).get(bound)
+            .ok_or_else(|| SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                "no placeholder for the IN list after {bound} binding(s)",
+            )))?;
+        let expanded = format!("({})", vec!["?"; list.0.len()].join(", "));
+        self.statement.statement.to_mut().replace_range(offset..offset + 1, &expanded);
+        Ok(list.0.into_iter().fold(self, SnowflakeSQL::add_binding))
+    }
+    /// Like [`add_in_binding`](Self::add_in_binding) followed by [`select`](Self::select), lists too long for one
+    /// statement are split into as many statements as needed and their rows are concatenated.
+    ///
+    /// Bindings after the list are not supported, bind them first. Rows of different chunks are not deduplicated
+    /// nor ordered across chunks, so aggregates, `ORDER BY` and `LIMIT` only apply within each chunk.
+    pub async fn select_in_chunks<T: SnowflakeDeserialize>(self, list: In) -> Result<Vec<T>, SnowflakeError> {
+        let bound = self.statement.bindings.as_ref().map_or(0, |bindings| bindings.len());
+        let chunk = MAX_BINDINGS.saturating_sub(bound).max(1);
+        let mut rows = Vec::new();
+        for values in list.0.chunks(chunk) {
+            let mut sql = self.clone();
+            // A resubmitted request id is a retry of the same request.
+            sql.uuid = uuid::Uuid::new_v4();
+            rows.extend(sql.add_in_binding(In(values.to_vec()))?.select::<T>().await?.data);
+        }
+        Ok(rows)
+    }
+}
+
+/// Byte offsets of the `?` placeholders, ignoring string literals, quoted identifiers and comments.
+fn placeholders(statement: &str) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    while let Some(c) = statement[offset..].chars().next() {
+        let rest = &statement[offset..];
+        offset += match c {
+            '\'' | '"' => named_bindings::quoted(rest, c),
+            '-' if rest.starts_with("--") => rest.find('\n').unwrap_or(rest.len()),
+            '/' if rest.starts_with("/*") => rest.find("*/").map_or(rest.len(), |end| end + 2),
+            '$' if rest.starts_with("$$") => rest[2..].find("$$").map_or(rest.len(), |end| end + 4),
+            '?' => {
+                offsets.push(offset);
+                1
+            },
+            c => c.len_utf8(),
+        };
+    }
+    offsets
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{matchers::method, Mock};
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn expanded() -> Result<(), anyhow::Error> {
+        assert_eq!(placeholders("SELECT '?', \"?\" /* ? */ FROM T WHERE A = ? -- ?\nAND B IN ?"), [41, 57]);
+
+        let snowflake = MockSnowflake::start().await;
+        let connector = snowflake.connector();
+        let sql = connector.execute("DB", "WH")
+            .sql("SELECT * FROM T WHERE A = ? AND ID IN ? AND '?' = '?'")?
+            .add_binding("a")
+            .add_in_binding(In::new([1, 2, 3]))?;
+        assert_eq!(sql.statement.statement, "SELECT * FROM T WHERE A = ? AND ID IN (?, ?, ?) AND '?' = '?'");
+        assert_eq!(sql.statement.bindings.as_ref().map(|bindings| bindings["4"].value.clone()), Some(Some("3".into())));
+        assert!(sql.add_in_binding(In::new([4])).is_err());
+        assert!(connector.execute("DB", "WH").sql("SELECT ?")?.add_in_binding(In(Vec::new())).is_err());
+        assert!(connector.execute("DB", "WH").sql("SELECT ?")?.add_in_binding(In::new(0..MAX_BINDINGS as u32 + 1)).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn chunked() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        Mock::given(method("POST"))
+            .respond_with(StatementFixture::new().column("ID", "fixed").row([Some("1")]).success())
+            .mount(&snowflake.server).await;
+        let connector = snowflake.connector();
+        let rows = connector.execute("DB", "WH")
+            .sql("SELECT ID FROM T WHERE ID IN ?")?
+            .select_in_chunks::<(u32,)>(In::new(0..MAX_BINDINGS as u32 + 1)).await?;
+        assert_eq!(rows.len(), 2);
+
+        let requests = snowflake.server.received_requests().await.unwrap_or_default();
+        let body: serde_json::Value = serde_json::from_slice(&requests[1].body)?;
+        assert_eq!(body["statement"], "SELECT ID FROM T WHERE ID IN (?)");
+        assert_eq!(body["bindings"]["1"]["value"], MAX_BINDINGS.to_string());
+        assert_ne!(requests[0].url.query(), requests[1].url.query());
+        Ok(())
+    }
+}
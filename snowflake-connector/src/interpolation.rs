@@ -0,0 +1,113 @@
+//! Guard against values formatted into statements instead of bound, see [`SnowflakeConnector::deny_interpolation`].
+
+use crate::{errors::SnowflakeError, named_bindings::quoted, SnowflakeConnector};
+
+/// Statement known to be a literal, so nothing was formatted into it, created with [`sql_static!`](crate::sql_static).
+#[derive(Clone, Copy, Debug)]
+pub struct StaticSql(&'static str);
+
+impl StaticSql {
+    #[doc(hidden)]
+    pub const fn __new(statement: &'static str) -> Self {
+        StaticSql(statement)
+    }
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+/// Statement that only accepts a string literal, pass it to
+/// [`SnowflakeExecutor::sql_static`](crate::SnowflakeExecutor::sql_static) to skip [`SnowflakeConnector::deny_interpolation`].
+///
+/// ```
+/// # use snowflake_connector::sql_static;
+/// let statement = sql_static!("SELECT * FROM USERS WHERE STATUS = 'ACTIVE'");
+/// ```
+///
+/// ```compile_fail
+/// # use snowflake_connector::sql_static;
+/// let status = "ACTIVE";
+/// let statement = sql_static!(format!("SELECT * FROM USERS WHERE STATUS = '{status}'"));
+/// ```
+#[macro_export]
+macro_rules! sql_static {
+    ($statement:literal) => {
+        $crate::interpolation::StaticSql::__new($statement)
+    };
+}
+
+impl SnowflakeConnector {
+    /// Reject statements passed to [`sql`](crate::SnowflakeExecutor::sql) that look like values were formatted into them,
+    /// ex. ones with string literals or comments, unless they have `?` or `:1` placeholders for bindings.
+    /// Use [`sql_static!`](crate::sql_static) for literal statements that need them.
+    pub fn deny_interpolation(mut self, deny: bool) -> Self {
+        self.deny_interpolation = deny;
+        self
+    }
+}
+
+/// Errors if `statement` has no placeholders but a marker of formatted in values.
+pub(crate) fn check(statement: &str) -> Result<(), SnowflakeError> {
+    match suspicious_marker(statement) {
+        Some(marker) => Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+            "statement has a {marker} but no bindings, bind values with `add_binding` or use `sql_static!`",
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn suspicious_marker(statement: &str) -> Option<&'static str> {
+    if has_placeholders(statement) {
+        return None;
+    }
+    let statement = statement.trim_end().trim_end_matches(';');
+    [("'", "string literal"), ("--", "comment"), ("/*", "comment"), (";", "statement separator")]
+        .into_iter()
+        .find(|(marker, _)| statement.contains(marker))
+        .map(|(_, name)| name)
+}
+
+/// Whether `statement` has a `?` or `:1` placeholder outside of string literals, quoted identifiers and comments.
+fn has_placeholders(statement: &str) -> bool {
+    let mut rest = statement;
+    while let Some(c) = rest.chars().next() {
+        let skipped = match c {
+            '\'' | '"' => quoted(rest, c),
+            '-' if rest.starts_with("--") => rest.find('\n').unwrap_or(rest.len()),
+            '/' if rest.starts_with("/*") => rest.find("*/").map_or(rest.len(), |end| end + 2),
+            '$' if rest.starts_with("$$") => rest[2..].find("$$").map_or(rest.len(), |end| end + 4),
+            ':' if rest.starts_with("::") => 2,
+            '?' => return true,
+            ':' if rest[1..].starts_with(|c: char| c.is_ascii_digit()) => return true,
+            c => c.len_utf8(),
+        };
+        rest = &rest[skipped..];
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markers() {
+        assert_eq!(suspicious_marker("SELECT * FROM USERS WHERE NAME = 'bob'"), Some("string literal"));
+        assert_eq!(suspicious_marker("SELECT * FROM USERS WHERE ID = 1 -- AND ACTIVE"), Some("comment"));
+        assert_eq!(suspicious_marker("SELECT 1; DROP TABLE USERS"), Some("statement separator"));
+        assert_eq!(suspicious_marker("SELECT * FROM USERS;"), None);
+        assert_eq!(suspicious_marker("SELECT * FROM USERS WHERE NAME = ? AND ROLE = 'ADMIN'"), None);
+        assert_eq!(suspicious_marker("SELECT * FROM USERS WHERE NAME = :1"), None);
+        assert_eq!(sql_static!("SELECT 'a'").as_str(), "SELECT 'a'");
+    }
+
+    #[test]
+    fn quoted_placeholders() {
+        assert!(has_placeholders("SELECT * FROM EVENTS WHERE AT = ? AND NOTE = 'x'"));
+        assert!(has_placeholders("SELECT * FROM EVENTS WHERE AT > :1"));
+        assert!(!has_placeholders("SELECT * FROM EVENTS WHERE AT > '12:30' OR NOTE = 'why?'"));
+        assert!(!has_placeholders(r#"SELECT "a?" FROM EVENTS -- :1"#));
+        assert!(!has_placeholders("SELECT ID::1 /* ? */ FROM EVENTS"));
+        assert_eq!(suspicious_marker("SELECT * FROM EVENTS WHERE AT > '12:30'"), Some("string literal"));
+    }
+}
@@ -0,0 +1,122 @@
+//! Run many independent statements with a bound on how many are in flight at once.
+
+use std::{fmt::Display, future::Future};
+
+use futures_util::{stream, StreamExt};
+
+use crate::{errors::SnowflakeError, SnowflakeConnector};
+
+/// Statements of [`SnowflakeConnector::try_join_all`] that failed, the first error is the [`source`](std::error::Error::source).
+#[derive(Debug)]
+pub struct JoinError {
+    pub total: usize,
+    /// Position of each failed statement in the input, with its error, in input order.
+    pub failed: Vec<(usize, SnowflakeError)>,
+}
+
+impl Display for JoinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.failed.first() {
+            Some((index, _)) => write!(f, "{} of {} statement(s) failed, first at {index}", self.failed.len(), self.total),
+            None => write!(f, "none of {} statement(s) failed", self.total),
+        }
+    }
+}
+
+impl std::error::Error for JoinError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.failed.first().map(|(_, error)| error as _)
+    }
+}
+
+impl SnowflakeConnector {
+    /// Await `queries` with at most `concurrency` of them running at once,
+    /// results are in the order of `queries` whichever finishes first.
+    ///
+    /// ```no_run
+    /// # use snowflake_connector::SnowflakeConnector;
+    /// # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+    /// let tables = ["USERS", "ORDERS", "EVENTS"];
+    /// let statements: Vec<String> = tables.iter().map(|table| format!("SELECT COUNT(*) FROM {table}")).collect();
+    /// let counts = connector.join_all(2, statements.iter().map(|statement| async {
+    ///     connector.execute("DB", "WH").sql(statement)?.select_one::<i64>().await
+    /// })).await;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn join_all<T, F, I>(&self, concurrency: usize, queries: I) -> Vec<Result<T, SnowflakeError>>
+        where I: IntoIterator<Item = F>, F: Future<Output = Result<T, SnowflakeError>>
+    {
+        stream::iter(queries)
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+    /// Like [`join_all`](Self::join_all) but every statement must succeed,
+    /// each one still runs to completion so the error lists all failures.
+    pub async fn try_join_all<T, F, I>(&self, concurrency: usize, queries: I) -> Result<Vec<T>, JoinError>
+        where I: IntoIterator<Item = F>, F: Future<Output = Result<T, SnowflakeError>>
+    {
+        let results = self.join_all(concurrency, queries).await;
+        let total = results.len();
+        let mut values = Vec::with_capacity(total);
+        let mut failed = Vec::new();
+        for (index, result) in results.into_iter().enumerate() {
+            match result {
+                Ok(value) => values.push(value),
+                Err(error) => failed.push((index, error)),
+            }
+        }
+        match failed.is_empty() {
+            true => Ok(values),
+            false => Err(JoinError { total, failed }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::test_util::MockSnowflake;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn bounded_in_order() {
+        let mock = MockSnowflake::start().await;
+        let connector = mock.connector();
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let queries = (0..10).map(|i| {
+            let (running, peak) = (&running, &peak);
+            async move {
+                peak.fetch_max(running.fetch_add(1, Ordering::SeqCst) + 1, Ordering::SeqCst);
+                for _ in 0..(10 - i) {
+                    tokio::task::yield_now().await;
+                }
+                running.fetch_sub(1, Ordering::SeqCst);
+                match i % 4 {
+                    3 => Err(SnowflakeError::InvalidStatement(anyhow::anyhow!("{i}"))),
+                    _ => Ok(i),
+                }
+            }
+        });
+        let results = connector.join_all(3, queries).await;
+        assert_eq!(peak.load(Ordering::SeqCst), 3);
+        assert_eq!(results.iter().filter_map(|result| result.as_ref().ok().copied()).collect::<Vec<_>>(), [0, 1, 2, 4, 5, 6, 8, 9]);
+
+        let error = connector.try_join_all(2, (0..5).map(|i| async move {
+            match i {
+                1 | 4 => Err(SnowflakeError::InvalidStatement(anyhow::anyhow!("{i}"))),
+                _ => Ok(i),
+            }
+        })).await.unwrap_err();
+        assert_eq!(error.total, 5);
+        assert_eq!(error.failed.iter().map(|(index, _)| *index).collect::<Vec<_>>(), [1, 4]);
+        assert_eq!(error.to_string(), "2 of 5 statement(s) failed, first at 1");
+        assert_eq!(std::error::Error::source(&error).map(ToString::to_string).as_deref(), Some("invalid statement"));
+        assert_eq!(JoinError { total: 0, failed: Vec::new() }.to_string(), "none of 0 statement(s) failed");
+        assert!(connector.try_join_all(2, (0..5).map(|i| async move { Ok(i) })).await.is_ok());
+    }
+}
@@ -0,0 +1,48 @@
+//! Parsing of response bodies, with simd-json when the `simd-json` feature is enabled, else serde_json.
+
+use serde::de::DeserializeOwned;
+
+#[cfg(not(feature = "simd-json"))]
+pub(crate) fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, anyhow::Error> {
+    Ok(serde_json::from_slice(body)?)
+}
+
+/// simd-json parses in place, so the body is copied first.
+#[cfg(feature = "simd-json")]
+pub(crate) fn from_slice<T: DeserializeOwned>(body: &[u8]) -> Result<T, anyhow::Error> {
+    Ok(simd_json::serde::from_slice(&mut body.to_vec())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use snowflake_deserializer::SnowflakeSQLResponse;
+
+    use super::*;
+
+    #[test]
+    fn response() -> Result<(), anyhow::Error> {
+        let body = br#"{
+            "resultSetMetaData": {
+                "numRows": 2,
+                "format": "jsonv2",
+                "rowType": [
+                    { "name": "ID", "database": "DB", "schema": "PUBLIC", "table": "T", "type": "fixed", "scale": 0, "nullable": false },
+                    { "name": "NAME", "database": "DB", "schema": "PUBLIC", "table": "T", "type": "text", "nullable": true }
+                ],
+                "partitionInfo": [{ "rowCount": 2, "uncompressedSize": 0 }]
+            },
+            "data": [["1", "a \"quoted\" \u00e9"], ["2", null]],
+            "code": "090001",
+            "statementHandle": "handle",
+            "statementStatusUrl": "/api/v2/statements/handle",
+            "requestId": "request",
+            "sqlState": "00000",
+            "message": "Statement executed successfully."
+        }"#;
+        let response: SnowflakeSQLResponse = from_slice(body)?;
+        assert_eq!(response, serde_json::from_slice::<SnowflakeSQLResponse>(body)?);
+        assert_eq!(response.deserialize::<(i64, Option<String>)>()?.data, [(1, Some("a \"quoted\" \u{e9}".into())), (2, None)]);
+        assert!(from_slice::<SnowflakeSQLResponse>(b"{").is_err());
+        Ok(())
+    }
+}
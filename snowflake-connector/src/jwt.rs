@@ -31,35 +31,28 @@ pub fn create_token<P: AsRef<Path>>(
 fn get_private_key<P: AsRef<Path>>(path: P) -> Result<String, KeyPairError> {
     std::fs::read_to_string(&path)
         .map_err(|e| {
-            KeyPairError::PrivateKeyRead(e, if let Some(path) = path.as_ref().to_str() {
-                path
-            } else {
-                "N/A"
-            }.into())
+            KeyPairError::PrivateKeyRead(e, path.as_ref().to_str().unwrap_or("N/A").into())
         })
 }
 
 fn get_public_key<P: AsRef<Path>>(path: P) -> Result<String, KeyPairError> {
     std::fs::read_to_string(&path)
         .map_err(|e| {
-            KeyPairError::PublicKeyRead(e, if let Some(path) = path.as_ref().to_str() {
-                path
-            } else {
-                "N/A"
-            }.into())
+            KeyPairError::PublicKeyRead(e, path.as_ref().to_str().unwrap_or("N/A").into())
         })
 }
 
 #[derive(thiserror::Error, Debug)]
+#[non_exhaustive]
 pub enum KeyPairError {
-    #[error("failed to read public key, path: {1}—{0}")]
-    PublicKeyRead(std::io::Error, String),
-    #[error("failed to read private key, path: {1}—{0}")]
-    PrivateKeyRead(std::io::Error, String),
-    #[error("failed to generate fingerprint from public key—{0}")]
-    FingerprintGeneration(anyhow::Error),
-    #[error("failed to generate key pair from private key—{0}")]
-    KayPairGeneration(anyhow::Error),
+    #[error("failed to read public key, path: {1}")]
+    PublicKeyRead(#[source] std::io::Error, String),
+    #[error("failed to read private key, path: {1}")]
+    PrivateKeyRead(#[source] std::io::Error, String),
+    #[error("failed to generate fingerprint from public key")]
+    FingerprintGeneration(#[source] anyhow::Error),
+    #[error("failed to generate key pair from private key")]
+    KayPairGeneration(#[source] anyhow::Error),
 }
 
 #[cfg(test)]
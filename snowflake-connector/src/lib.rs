@@ -1,19 +1,104 @@
-use std::{collections::HashMap, path::Path};
+use std::{borrow::Cow, collections::HashMap, path::Path, sync::{Arc, OnceLock, RwLock}, time::Duration};
 use data_manipulation::DataManipulationResult;
 use reqwest::header::{HeaderMap, CONTENT_TYPE, AUTHORIZATION, ACCEPT, USER_AGENT};
 use serde::Serialize;
 use snowflake_deserializer::{*, bindings::*};
 use errors::SnowflakeError;
+use cursor::Cursor;
+use multiple::MultipleSnowflakeSQL;
+use status::StatementOutcome;
+use progress::{Progress, ProgressTracker, ResultStats};
+use shutdown::InFlight;
+use polling::{Backoff, Deadline};
+use tls::TlsConfig;
+use pool::PoolConfig;
+use interpolation::StaticSql;
+use payload::{Payload, StatementBody};
+use partitions::PartitionFetchPolicy;
+use routing::RoutingPolicy;
+use redact::RedactionPolicy;
 
+pub mod account;
+pub mod account_usage;
+pub mod capabilities;
+pub mod change_feed;
+pub mod checkpoint;
+pub mod chunked;
+pub mod compat;
+pub mod cursor;
 pub mod data_manipulation;
+pub mod ddl;
+pub mod error_code;
 pub mod errors;
+pub mod escape;
+pub mod explain;
+pub mod idempotency;
+pub mod in_list;
+pub mod interpolation;
+pub mod join;
+#[cfg(any(feature = "deadpool", feature = "bb8"))]
+pub mod managed;
+pub mod migrations;
+pub mod multiple;
+pub mod named_bindings;
+#[cfg(feature = "parquet")]
+pub mod parquet_export;
+pub mod partitions;
+pub mod payload;
+pub mod polling;
+pub mod profiles;
+pub mod profiling;
+pub mod progress;
+pub mod projection;
+pub mod read_only;
+pub mod redaction;
+pub mod routing;
+pub mod row_filter;
+pub mod schema_drift;
+pub mod secondary_roles;
+pub mod session;
+pub mod show;
+pub mod shutdown;
+pub mod snowpipe;
+pub mod stage;
+pub mod status;
+#[cfg(feature = "axum")]
+pub mod web;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
+mod json;
 mod jwt;
+mod pool;
+mod tls;
+
+pub use reqwest;
+pub use uuid;
 
 #[derive(Debug)]
 pub struct SnowflakeConnector {
     token: String,
-    host: String,
+    host: Arc<str>,
+    in_flight: Arc<InFlight>,
+    tls: TlsConfig,
+    pool: PoolConfig,
+    /// Built on first use, so every statement shares its connections.
+    client: OnceLock<reqwest::Client>,
+    deny_interpolation: bool,
+    read_only: bool,
+    default_database: Option<String>,
+    default_warehouse: Option<String>,
+    routing: Arc<RoutingPolicy>,
+    profiles: Arc<HashMap<String, profiles::StatementProfile>>,
+    partition_policy: PartitionFetchPolicy,
+    redaction: RedactionPolicy,
+    row_filter: Arc<row_filter::RowFilter>,
+    cost_limits: Option<explain::CostLimits>,
+    /// Sentinel of `NULL` cells, see [`null`](snowflake_deserializer::null).
+    null_sentinel: Arc<str>,
+    default_secondary_roles: Option<secondary_roles::SecondaryRoles>,
+    /// Cached by [`session_info`](Self::session_info).
+    session: RwLock<Option<session::SessionInfo>>,
 }
 
 impl SnowflakeConnector {
@@ -32,18 +117,95 @@ impl SnowflakeConnector {
         )?;
         Ok(SnowflakeConnector {
             token,
-            host: format!("https://{host}.snowflakecomputing.com/api/v2/"),
+            host: format!("https://{host}.snowflakecomputing.com/api/v2/").into(),
+            in_flight: Arc::default(),
+            tls: TlsConfig::default(),
+            pool: PoolConfig::default(),
+            client: OnceLock::new(),
+            deny_interpolation: false,
+            read_only: false,
+            default_database: None,
+            default_warehouse: None,
+            routing: Arc::default(),
+            profiles: Arc::default(),
+            partition_policy: PartitionFetchPolicy::default(),
+            redaction: RedactionPolicy::default(),
+            row_filter: Arc::default(),
+            cost_limits: None,
+            null_sentinel: null::DEFAULT_SENTINEL.into(),
+            default_secondary_roles: None,
+            session: RwLock::default(),
         })
     }
 
-    pub fn execute<D: ToString, W: ToString>(
+    /// Database statements run in when none is passed to [`execute`](Self::execute).
+    pub fn with_default_database(mut self, database: impl Into<String>) -> Self {
+        self.default_database = Some(database.into());
+        self
+    }
+
+    /// Text `NULL` cells are read as instead of `null`, so text columns holding `null` are not read as `NULL`,
+    /// ex. `"\u{1}NULL"`, see [`null`](snowflake_deserializer::null).
+    pub fn with_null_sentinel(mut self, sentinel: impl Into<Arc<str>>) -> Self {
+        self.null_sentinel = sentinel.into();
+        self
+    }
+
+    /// Warehouse of [`query`](Self::query), else the user's default warehouse.
+    pub fn with_default_warehouse(mut self, warehouse: impl Into<String>) -> Self {
+        self.default_warehouse = Some(warehouse.into());
+        self
+    }
+
+    pub fn execute(
         &self,
-        database: D,
-        warehouse: W,
-    ) -> SnowflakeExecutor<D, W> {
+        database: impl Into<String>,
+        warehouse: impl Into<String>,
+    ) -> SnowflakeExecutor<'_> {
+        self.executor(Some(database.into()), warehouse.into())
+    }
+
+    /// Run statements in the [default database](Self::with_default_database) if any,
+    /// else statements must use fully qualified names or `USE` a database.
+    pub fn execute_on(&self, warehouse: impl Into<String>) -> SnowflakeExecutor<'_> {
+        self.executor(None, warehouse.into())
+    }
+
+    /// Rows of `statement` with its `bindings`, ex. `connector.query::<User, _>("SELECT * FROM USERS WHERE ID = ?", [1]).await?`.
+    ///
+    /// Runs in the [default database](Self::with_default_database) and [warehouse](Self::with_default_warehouse),
+    /// use [`execute`](Self::execute) for anything else.
+    pub async fn query<T: SnowflakeDeserialize, B: IntoBindings>(&self, statement: &str, bindings: B) -> Result<Vec<T>, SnowflakeError> {
+        let warehouse = self.default_warehouse.clone().unwrap_or_default();
+        let mut sql = self.execute_on(warehouse).sql(statement)?;
+        for binding in bindings.into_bindings() {
+            sql = sql.add_binding(binding);
+        }
+        Ok(sql.select().await?.data)
+    }
+
+    /// HTTP client of the connector, sharing connections between statements.
+    pub(crate) fn client(&self) -> Result<reqwest::Client, SnowflakeError> {
+        if let Some(client) = self.client.get() {
+            return Ok(client.clone());
+        }
+        let client = client(&self.token, &self.tls, &self.pool)?;
+        Ok(self.client.get_or_init(|| client).clone())
+    }
+    /// Drop the built client after a setting of it changed.
+    pub(crate) fn reset_client(&mut self) {
+        self.client = OnceLock::new();
+    }
+
+    fn executor(&self, database: Option<String>, warehouse: String) -> SnowflakeExecutor<'_> {
         SnowflakeExecutor {
-            token: &self.token,
+            connector: self,
             host: &self.host,
+            in_flight: &self.in_flight,
+            deny_interpolation: self.deny_interpolation,
+            read_only: self.read_only,
+            default_database: self.default_database.as_deref(),
+            routing: &self.routing,
             database,
             warehouse,
         }
@@ -51,96 +213,367 @@ impl SnowflakeConnector {
 }
 
 #[derive(Debug)]
-pub struct SnowflakeExecutor<'a, D: ToString, W: ToString> {
-    token: &'a str,
-    host: &'a str,
-    database: D,
-    warehouse: W,
+pub struct SnowflakeExecutor<'a> {
+    connector: &'a SnowflakeConnector,
+    host: &'a Arc<str>,
+    in_flight: &'a Arc<InFlight>,
+    deny_interpolation: bool,
+    read_only: bool,
+    default_database: Option<&'a str>,
+    routing: &'a Arc<RoutingPolicy>,
+    database: Option<String>,
+    warehouse: String,
 }
 
-impl<'a, D: ToString, W: ToString> SnowflakeExecutor<'a, D, W> {
+impl<'a> SnowflakeExecutor<'a> {
     pub fn sql(self, statement: &'a str) -> Result<SnowflakeSQL<'a>, SnowflakeError> {
-        let headers = self.get_headers()
-            .map_err(SnowflakeError::SqlClient)?;
-        let client = reqwest::Client::builder()
-            .default_headers(headers)
-            .build()
-            .map_err(|e| SnowflakeError::SqlClient(e.into()))?;
+        if self.deny_interpolation {
+            interpolation::check(statement)?;
+        }
+        self.statement(statement.into())
+    }
+    /// Like [`sql`](Self::sql) but never rejected by [`SnowflakeConnector::deny_interpolation`].
+    pub fn sql_static(self, statement: StaticSql) -> Result<SnowflakeSQL<'a>, SnowflakeError> {
+        self.statement(statement.as_str().into())
+    }
+    /// Run a statement checked at compile time by `snowflake_query!`.
+    pub async fn query<T: SnowflakeDeserialize>(self, query: query::Query<T>) -> Result<SnowflakeSQLResult<T>, SnowflakeError> {
+        let (statement, bindings) = query.into_parts();
+        let mut sql = self.statement(statement)?;
+        for binding in bindings {
+            sql = sql.add_binding(binding);
+        }
+        sql.select().await
+    }
+    /// Run an insert, update or delete generated by `snowflake_repository!`.
+    pub async fn manipulate(self, manipulation: query::Manipulation) -> Result<DataManipulationResult, SnowflakeError> {
+        let (statement, bindings) = manipulation.into_parts();
+        let mut sql = self.statement(statement)?;
+        for binding in bindings {
+            sql = sql.add_binding(binding);
+        }
+        sql.manipulate().await
+    }
+    /// Run several statements in one request, see [`MultipleSnowflakeSQL`].
+    pub fn multiple_sql(self, statements: &[&str]) -> Result<MultipleSnowflakeSQL<'a>, SnowflakeError> {
+        if self.read_only {
+            statements.iter().try_for_each(|statement| read_only::check(statement))?;
+        }
+        let sql = self.statement(Cow::Borrowed(""))?;
+        Ok(MultipleSnowflakeSQL::new(sql, statements))
+    }
+    /// Run a script of any number of `;` separated statements,
+    /// see [`MultipleSnowflakeSQLResponse::child_handles`](multiple::MultipleSnowflakeSQLResponse::child_handles)
+    /// to retrieve the result of each statement.
+    pub fn script(self, script: &str) -> Result<MultipleSnowflakeSQL<'a>, SnowflakeError> {
+        if self.read_only {
+            read_only::check(script)?;
+        }
+        let sql = self.statement(Cow::Borrowed(""))?;
+        Ok(MultipleSnowflakeSQL::script(sql, script))
+    }
+    /// Database passed to the executor, or else the connector's default.
+    fn database(&self) -> Option<String> {
+        self.database.clone()
+            .or_else(|| self.default_database.map(str::to_owned))
+    }
+    fn statement(&self, statement: Cow<'a, str>) -> Result<SnowflakeSQL<'a>, SnowflakeError> {
+        self.in_flight.check_open()?;
+        if self.read_only {
+            read_only::check(&statement)?;
+        }
         Ok(SnowflakeSQL {
-            client,
-            host: self.host,
+            client: self.connector.client()?,
+            host: self.host.clone(),
             statement: SnowflakeExecutorSQLJSON {
                 statement,
                 timeout: None,
-                database: self.database.to_string(),
-                warehouse: self.warehouse.to_string(),
+                database: self.database(),
+                warehouse: self.warehouse.clone(),
                 role: None,
                 bindings: None,
+                parameters: None,
+                secondary_roles: self.connector.default_secondary_roles.clone(),
+                payload: Payload::default(),
             },
             uuid: uuid::Uuid::new_v4(),
+            progress: ProgressTracker::default(),
+            in_flight: self.in_flight.clone(),
+            routing: self.routing.clone(),
+            profiles: self.connector.profiles.clone(),
+            partition_policy: self.connector.partition_policy.clone(),
+            redaction: self.connector.redaction.clone(),
+            row_filter: self.connector.row_filter.clone(),
+            cost_limits: self.connector.cost_limits,
+            poll_timeout: None,
+            null_sentinel: self.connector.null_sentinel.clone(),
         })
     }
-    fn get_headers(&self) -> Result<HeaderMap, anyhow::Error> {
-        let mut headers = HeaderMap::with_capacity(5);
-        headers.append(CONTENT_TYPE, "application/json".parse()?);
-        headers.append(AUTHORIZATION, format!("Bearer {}", self.token).parse()?);
-        headers.append("X-Snowflake-Authorization-Token-Type", "KEYPAIR_JWT".parse()?);
-        headers.append(ACCEPT, "application/json".parse()?);
-        headers.append(USER_AGENT, concat!(env!("CARGO_PKG_NAME"), '/', env!("CARGO_PKG_VERSION")).parse()?);
-        Ok(headers)
-    }
 }
 
-#[derive(Debug)]
+fn client(token: &str, tls: &TlsConfig, pool: &PoolConfig) -> Result<reqwest::Client, SnowflakeError> {
+    let headers = get_headers(token)
+        .map_err(SnowflakeError::SqlClient)?;
+    pool.apply(tls.apply(reqwest::Client::builder()))
+        .default_headers(headers)
+        .build()
+        .map_err(|e| SnowflakeError::SqlClient(e.into()))
+}
+
+fn get_headers(token: &str) -> Result<HeaderMap, anyhow::Error> {
+    let mut headers = HeaderMap::with_capacity(5);
+    headers.append(CONTENT_TYPE, "application/json".parse()?);
+    headers.append(AUTHORIZATION, format!("Bearer {token}").parse()?);
+    headers.append("X-Snowflake-Authorization-Token-Type", "KEYPAIR_JWT".parse()?);
+    headers.append(ACCEPT, "application/json".parse()?);
+    headers.append(USER_AGENT, concat!(env!("CARGO_PKG_NAME"), '/', env!("CARGO_PKG_VERSION")).parse()?);
+    Ok(headers)
+}
+
+#[derive(Clone, Debug)]
 pub struct SnowflakeSQL<'a> {
     client: reqwest::Client,
-    host: &'a str,
+    host: Arc<str>,
     statement: SnowflakeExecutorSQLJSON<'a>,
     uuid: uuid::Uuid,
+    progress: ProgressTracker,
+    in_flight: Arc<InFlight>,
+    routing: Arc<RoutingPolicy>,
+    profiles: Arc<HashMap<String, profiles::StatementProfile>>,
+    partition_policy: PartitionFetchPolicy,
+    redaction: RedactionPolicy,
+    row_filter: Arc<row_filter::RowFilter>,
+    /// Checked before the statement runs, see [`explain`].
+    cost_limits: Option<explain::CostLimits>,
+    poll_timeout: Option<Duration>,
+    null_sentinel: Arc<str>,
 }
 
 impl<'a> SnowflakeSQL<'a> {
     pub async fn text(self) -> Result<String, SnowflakeError> {
-        self.client
-            .post(self.get_url())
-            .json(&self.statement)
-            .send().await
-            .map_err(|e| SnowflakeError::SqlExecution(e.into()))?
+        let _in_flight = self.in_flight.begin()?;
+        self.send().await?
             .text().await
-            .map_err(|e| SnowflakeError::SqlResultParse(e.into()))
+            .map_err(|e| self.correlate(None, SnowflakeError::SqlResultParse(e.into())))
     }
-    pub async fn select<T: SnowflakeDeserialize>(self) -> Result<SnowflakeSQLResult<T>, SnowflakeError> {
-        self.client
+    /// Body of the response, whatever its status, see [`raw`](Self::raw).
+    pub async fn bytes(self) -> Result<Vec<u8>, SnowflakeError> {
+        let _in_flight = self.in_flight.begin()?;
+        self.send().await?
+            .bytes().await
+            .map(Vec::from)
+            .map_err(|e| self.correlate(None, SnowflakeError::SqlResultParse(e.into())))
+    }
+    /// Response to the statement as is, status, headers and a body that can be streamed,
+    /// for behavior the crate does not cover yet.
+    ///
+    /// Statements still executing are not polled and partitions are not fetched.
+    pub async fn raw(self) -> Result<reqwest::Response, SnowflakeError> {
+        self.in_flight.check_open()?;
+        self.send().await
+    }
+    pub async fn select<T: SnowflakeDeserialize>(mut self) -> Result<SnowflakeSQLResult<T>, SnowflakeError> {
+        self.select_with(SnowflakeSQLResponse::deserialize::<T>, |result| result.data.len()).await
+    }
+    /// Like [`select`](Self::select), fields of type [`intern::Interned`] share the storage of equal values,
+    /// along with how much that saved.
+    pub async fn select_interned<T: SnowflakeDeserialize>(mut self) -> Result<(SnowflakeSQLResult<T>, intern::InternStats), SnowflakeError> {
+        self.select_with(SnowflakeSQLResponse::deserialize_interned::<T>, |(result, _)| result.data.len()).await
+    }
+    /// Like [`select`](Self::select), along with the partitions of the result and what downloading them took.
+    pub async fn select_with_stats<T: SnowflakeDeserialize>(mut self) -> Result<(SnowflakeSQLResult<T>, ResultStats), SnowflakeError> {
+        let result = self.select_with(SnowflakeSQLResponse::deserialize::<T>, |result| result.data.len()).await?;
+        Ok((result, self.progress.stats().clone()))
+    }
+    /// Like [`select`](Self::select) but rows that fail to deserialize are returned
+    /// with their raw cells in [`PartialSQLResult::rejected`] instead of failing the whole result.
+    pub async fn select_partial<T: SnowflakeDeserializeRow>(mut self) -> Result<PartialSQLResult<T>, SnowflakeError> {
+        self.select_with(SnowflakeSQLResponse::deserialize_partial::<T>, |result| result.data.len()).await
+    }
+    /// Execute the statement with every partition and deserialize the response with `deserialize`
+    /// under the redaction policy, `rows` counts what it deserialized.
+    async fn select_with<R>(
+        &mut self,
+        deserialize: impl FnOnce(SnowflakeSQLResponse) -> Result<R, anyhow::Error>,
+        rows: impl FnOnce(&R) -> usize,
+    ) -> Result<R, SnowflakeError> {
+        let response = self.response_with_partitions().await?;
+        let statement_handle = response.statement_handle.clone();
+        let result = redact::with_policy(&self.redaction, || deserialize(response))
+            .map_err(|e| self.correlate(Some(&statement_handle), SnowflakeError::SqlResultParse(e)))?;
+        self.progress.deserialized(rows(&result));
+        Ok(result)
+    }
+    /// Execute the statement and page through its rows, see [`Cursor`].
+    pub async fn cursor(mut self) -> Result<Cursor, SnowflakeError> {
+        let response = self.response().await
+            .map_err(|e| self.correlate(None, e))?;
+        Ok(Cursor::new(self.client, self.host, response, self.partition_policy, self.redaction, self.row_filter, self.progress))
+    }
+    /// Stream the rows into `sender` partition by partition, so consumers can start before the whole result
+    /// was downloaded, see [`Cursor::send_into`].
+    pub async fn select_into<T: SnowflakeDeserialize>(self, sender: tokio::sync::mpsc::Sender<T>) -> Result<usize, SnowflakeError> {
+        self.cursor().await?
+            .send_into(&sender).await
+    }
+    /// Exactly one row, errors if the query returned zero or multiple rows.
+    pub async fn select_one<T: SnowflakeDeserialize>(self) -> Result<T, SnowflakeError> {
+        match self.select_optional().await? {
+            Some(row) => Ok(row),
+            None => Err(SnowflakeError::UnexpectedRowCount(0)),
+        }
+    }
+    /// At most one row, errors if the query returned multiple rows.
+    pub async fn select_optional<T: SnowflakeDeserialize>(self) -> Result<Option<T>, SnowflakeError> {
+        let mut data = self.select::<T>().await?.data;
+        if data.len() > 1 {
+            return Err(SnowflakeError::UnexpectedRowCount(data.len()));
+        }
+        Ok(data.pop())
+    }
+    /// First column of exactly one row, ex. `SELECT COUNT(*) FROM TEST_TABLE`.
+    pub async fn select_scalar<T>(self) -> Result<T, SnowflakeError>
+        where T: DeserializeFromStr, T::Err: Into<anyhow::Error>
+    {
+        self.select_one::<(T,)>().await
+            .map(|(value,)| value)
+    }
+    /// Number of rows the statement returns, runs `SELECT COUNT(*) FROM (statement)`.
+    pub async fn count(mut self) -> Result<u64, SnowflakeError> {
+        self.wrap_statement("SELECT COUNT(*) FROM (", ")");
+        self.select_scalar().await
+    }
+    /// Whether the statement returns any row, runs `SELECT EXISTS(statement)`.
+    pub async fn exists(mut self) -> Result<bool, SnowflakeError> {
+        self.wrap_statement("SELECT EXISTS(", ")");
+        self.select_scalar().await
+    }
+    /// Check that the statement compiles without executing it, runs `EXPLAIN` on the statement.
+    ///
+    /// Syntax errors, unknown objects and missing privileges are returned as [`SnowflakeError::StatementFailed`].
+    /// Use [`describe`](Self::describe) to also get the columns of a query.
+    pub async fn validate(mut self) -> Result<(), SnowflakeError> {
+        self.wrap_statement("EXPLAIN USING TEXT ", "");
+        let _in_flight = self.in_flight.begin()?;
+        let response = self.client
             .post(self.get_url())
-            .json(&self.statement)
+            .json(&StatementBody(&self.statement))
             .send().await
-            .map_err(|e| SnowflakeError::SqlExecution(e.into()))?
-            .json::<SnowflakeSQLResponse>().await
-            .map_err(|e| SnowflakeError::SqlResultParse(e.into()))?
-            .deserialize()
-            .map_err(SnowflakeError::SqlResultParse)
+            .map_err(|e| self.correlate(None, SnowflakeError::SqlExecution(e.into())))?;
+        match status::parse_outcome(response, &self.null_sentinel).await.map_err(|e| self.correlate(None, e))? {
+            StatementOutcome::Success(_) => Ok(()),
+            StatementOutcome::Failed(status) => Err(SnowflakeError::StatementFailed(Box::new(status))),
+            StatementOutcome::TimedOut(status) => Err(SnowflakeError::StatementTimedOut(Box::new(status))),
+            StatementOutcome::Pending(status) => Err(self.correlate(None, SnowflakeError::SqlExecution(
+                anyhow::anyhow!("statement {} did not compile in time—{}", status.statement_handle, status.message),
+            ))),
+        }
+    }
+    /// Columns the query returns, without fetching any row, runs `SELECT * FROM (statement) LIMIT 0`.
+    ///
+    /// Only works for statements that can be used as a subquery, ex. `SELECT` but not `SHOW`.
+    pub async fn describe(mut self) -> Result<MetaData, SnowflakeError> {
+        self.wrap_statement("SELECT * FROM (", ") LIMIT 0");
+        self.response().await
+            .map(|response| response.result_set_meta_data)
+            .map_err(|e| self.correlate(None, e))
     }
     /// Use with `delete`, `insert`, `update` row(s).
-    pub async fn manipulate(self) -> Result<DataManipulationResult, SnowflakeError> {
+    pub async fn manipulate(mut self) -> Result<DataManipulationResult, SnowflakeError> {
+        if self.statement.secondary_roles.is_some() {
+            let response = self.response().await
+                .map_err(|e| self.correlate(None, e))?;
+            return self.client
+                .get(format!("{}statements/{}", self.host, response.statement_handle))
+                .send().await
+                .map_err(|e| self.correlate(Some(&response.statement_handle), SnowflakeError::SqlExecution(e.into())))?
+                .json().await
+                .map_err(|e| self.correlate(Some(&response.statement_handle), SnowflakeError::SqlExecution(e.into())));
+        }
+        self.check_cost().await
+            .map_err(|e| self.correlate(None, e))?;
+        let _in_flight = self.in_flight.begin()?;
         self.client
             .post(self.get_url())
-            .json(&self.statement)
+            .json(&StatementBody(&self.statement))
             .send().await
-            .map_err(|e| SnowflakeError::SqlExecution(e.into()))?
+            .map_err(|e| self.correlate(None, SnowflakeError::SqlExecution(e.into())))?
             .json().await
-            .map_err(|e| SnowflakeError::SqlExecution(e.into()))
+            .map_err(|e| self.correlate(None, SnowflakeError::SqlExecution(e.into())))
     }
     pub fn with_timeout(mut self, timeout: u32) -> SnowflakeSQL<'a> {
         self.statement.timeout = Some(timeout);
         self
     }
-    pub fn with_role<R: ToString>(mut self, role: R) -> SnowflakeSQL<'a> {
-        self.statement.role = Some(role.to_string());
+    /// Stop polling a statement that outlasts the synchronous request after `timeout`,
+    /// returning [`SnowflakeError::StatementTimedOut`] while it may keep running.
+    ///
+    /// Defaults to the statement's [timeout](Self::with_timeout) and a minute more, else [`DEFAULT_POLL_TIMEOUT`](polling::DEFAULT_POLL_TIMEOUT).
+    pub fn with_poll_timeout(mut self, timeout: Duration) -> SnowflakeSQL<'a> {
+        self.poll_timeout = Some(timeout);
+        self
+    }
+    pub fn with_role(mut self, role: impl Into<String>) -> SnowflakeSQL<'a> {
+        self.statement.role = Some(role.into());
+        self
+    }
+    /// Set the `TIMEZONE` session parameter of the statement, ex. `UTC` or `America/Los_Angeles`,
+    /// so `TIMESTAMP_LTZ` values and `CURRENT_TIMESTAMP()` do not depend on the user's or account's default.
+    pub fn with_timezone(self, timezone: impl Into<String>) -> SnowflakeSQL<'a> {
+        self.with_parameter("TIMEZONE", timezone)
+    }
+    /// Set the `QUERY_TAG` session parameter of the statement, ex. to find it in `QUERY_HISTORY`.
+    pub fn with_query_tag(self, query_tag: impl Into<String>) -> SnowflakeSQL<'a> {
+        self.with_parameter("QUERY_TAG", query_tag)
+    }
+    /// Set the session parameter `name` for the statement, ex. `WEEK_START`.
+    pub fn with_parameter(mut self, name: impl Into<String>, value: impl Into<String>) -> SnowflakeSQL<'a> {
+        self.statement.parameters
+            .get_or_insert_with(Default::default)
+            .insert(name.into(), value.into());
+        self
+    }
+    /// Own the statement text, so the statement and the futures it returns are `'static`
+    /// and can be moved into spawned tasks.
+    pub fn into_owned(self) -> SnowflakeSQL<'static> {
+        SnowflakeSQL {
+            client: self.client,
+            host: self.host,
+            statement: self.statement.into_owned(),
+            uuid: self.uuid,
+            progress: self.progress,
+            in_flight: self.in_flight,
+            routing: self.routing,
+            profiles: self.profiles,
+            partition_policy: self.partition_policy,
+            redaction: self.redaction,
+            row_filter: self.row_filter,
+            cost_limits: self.cost_limits,
+            poll_timeout: self.poll_timeout,
+            null_sentinel: self.null_sentinel,
+        }
+    }
+    /// Send the statement with `request_id` instead of a random id, ex. to reuse a correlation id of the caller.
+    ///
+    /// Snowflake treats a resubmitted request id as a retry of the same request.
+    pub fn with_request_id(mut self, request_id: uuid::Uuid) -> SnowflakeSQL<'a> {
+        self.uuid = request_id;
+        self
+    }
+    /// Id the statement is sent with, also part of the errors it returns.
+    pub fn request_id(&self) -> uuid::Uuid {
+        self.uuid
+    }
+    /// Call `progress` as rows are downloaded and deserialized, ex. to draw a progress bar.
+    ///
+    /// Applies to [`select`](Self::select), [`select_partial`](Self::select_partial) and [`cursor`](Self::cursor).
+    pub fn with_progress<F: Fn(Progress) + Send + Sync + 'static>(mut self, progress: F) -> SnowflakeSQL<'a> {
+        self.progress.set_callback(progress);
         self
     }
     pub fn add_binding<T: Into<BindingValue>>(mut self, value: T) -> SnowflakeSQL<'a> {
         let value: BindingValue = value.into();
-        let value_str = value.to_string();
+        let value_str = (!value.is_null()).then(|| value.to_string());
         let value_type: BindingType = value.into();
         let binding = Binding {
             value_type: value_type.to_string(),
@@ -153,31 +586,160 @@ impl<'a> SnowflakeSQL<'a> {
         }
         self
     }
+    async fn send(&self) -> Result<reqwest::Response, SnowflakeError> {
+        self.check_cost().await
+            .map_err(|e| self.correlate(None, e))?;
+        self.client
+            .post(self.get_url())
+            .json(&StatementBody(&self.statement))
+            .send().await
+            .map_err(|e| self.correlate(None, SnowflakeError::SqlExecution(e.into())))
+    }
+    /// Submit the statement without waiting for it to finish.
+    ///
+    /// Cost limits are not checked, the statements of a multi-statement request are checked one by one before.
+    async fn submit(&self) -> Result<StatementOutcome, SnowflakeError> {
+        let _in_flight = self.in_flight.begin()?;
+        let response = self.client
+            .post(format!("{}&async=true", self.get_url()))
+            .json(&StatementBody(&self.statement))
+            .send().await
+            .map_err(|e| SnowflakeError::SqlExecution(e.into()))?;
+        let outcome = status::parse_outcome(response, &self.null_sentinel).await?;
+        self.in_flight.track(&outcome);
+        match outcome {
+            StatementOutcome::Failed(status) => Err(SnowflakeError::StatementFailed(Box::new(status))),
+            outcome => Ok(outcome),
+        }
+    }
+    /// Execute the statement, polling until it finished if it outlasts the synchronous request.
+    async fn response(&mut self) -> Result<SnowflakeSQLResponse, SnowflakeError> {
+        self.check_cost().await?;
+        let _in_flight = self.in_flight.begin()?;
+        let start = std::time::Instant::now();
+        let response = self.client
+            .post(self.get_url())
+            .json(&StatementBody(&self.statement))
+            .send().await
+            .map_err(|e| SnowflakeError::SqlExecution(e.into()))?;
+        let (mut outcome, mut bytes) = status::parse_outcome_sized(response, &self.null_sentinel).await?;
+        let deadline = Deadline::after(self.poll_timeout());
+        let backoff = Backoff::default();
+        let mut attempt = 0;
+        // Result of the statement rather than of the request preceding it with `USE SECONDARY ROLES`.
+        let mut follow_child = self.statement.secondary_roles.is_some();
+        loop {
+            // Pending statements can be cancelled by `shutdown` until they finished.
+            self.in_flight.track(&outcome);
+            match outcome {
+                StatementOutcome::Success(response) if follow_child => {
+                    follow_child = false;
+                    let child = response.statement_handles.last()
+                        .ok_or_else(|| SnowflakeError::SqlResultParse(anyhow::anyhow!(
+                            "statement {} with secondary roles returned no child statements", response.statement_handle,
+                        )))?;
+                    (outcome, bytes) = status::fetch_status_sized(&self.client, &self.host, child, &self.null_sentinel).await?;
+                },
+                StatementOutcome::Success(response) => {
+                    self.progress.response(&response, bytes, start.elapsed());
+                    return Ok(*response);
+                },
+                StatementOutcome::Failed(status) => return Err(SnowflakeError::StatementFailed(Box::new(status))),
+                StatementOutcome::TimedOut(status) => return Err(SnowflakeError::StatementTimedOut(Box::new(status))),
+                StatementOutcome::Pending(status) if deadline.has_passed() => return Err(SnowflakeError::StatementTimedOut(Box::new(status))),
+                StatementOutcome::Pending(status) => {
+                    tokio::time::sleep(backoff.delay(attempt).min(deadline.remaining())).await;
+                    attempt += 1;
+                    (outcome, bytes) = status::fetch_status_sized(&self.client, &self.host, &status.statement_handle, &self.null_sentinel).await?;
+                },
+            }
+        }
+    }
+    fn poll_timeout(&self) -> Duration {
+        self.poll_timeout
+            .or_else(|| self.statement.timeout.map(|timeout| Duration::from_secs(u64::from(timeout) + 60)))
+            .unwrap_or(polling::DEFAULT_POLL_TIMEOUT)
+    }
+    async fn response_with_partitions(&mut self) -> Result<SnowflakeSQLResponse, SnowflakeError> {
+        let response = self.response().await
+            .map_err(|e| self.correlate(None, e))?;
+        let statement_handle = response.statement_handle.clone();
+        let mut response = partitions::fetch_and_merge_partitions(&self.client, &self.host, response, &self.partition_policy, &mut self.progress).await
+            .map_err(|e| self.correlate(Some(&statement_handle), e))?;
+        self.row_filter.apply(&mut response);
+        Ok(response)
+    }
+    /// Attach the request id and statement handle to errors, so they can be correlated with Snowflake's logs.
+    fn correlate(&self, statement_handle: Option<&str>, error: SnowflakeError) -> SnowflakeError {
+        let context = match statement_handle {
+            Some(statement_handle) => format!("request {} statement {statement_handle}", self.uuid),
+            None => format!("request {}", self.uuid),
+        };
+        match error {
+            SnowflakeError::SqlExecution(e) => SnowflakeError::SqlExecution(e.context(context)),
+            SnowflakeError::SqlResultParse(e) => SnowflakeError::SqlResultParse(e.context(context)),
+            error => error,
+        }
+    }
+    fn wrap_statement(&mut self, prefix: &str, suffix: &str) {
+        let statement = self.statement.statement.trim_end().trim_end_matches(';');
+        // On its own line, a statement ending in a `--` comment would comment out the suffix.
+        let separator = if suffix.is_empty() { "" } else { "\n" };
+        self.statement.statement = format!("{prefix}{statement}{separator}{suffix}").into();
+    }
     fn get_url(&self) -> String {
         // TODO: make another return type that allows retrying by calling same statement again with retry flag!
-        format!("{}statements?nullable=false&requestId={}", self.host, self.uuid)
+        format!("{}statements?nullable=true&requestId={}", self.host, self.uuid)
     }
 }
 
-#[derive(Serialize, Debug)]
+#[derive(Serialize, Clone, Debug)]
 pub struct SnowflakeExecutorSQLJSON<'a> {
-    statement: &'a str,
+    statement: Cow<'a, str>,
     timeout: Option<u32>,
-    database: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    database: Option<String>,
+    /// Empty for the user's default warehouse.
+    #[serde(skip_serializing_if = "String::is_empty")]
     warehouse: String,
     role: Option<String>,
     bindings: Option<HashMap<String, Binding>>,
+    parameters: Option<HashMap<String, String>>,
+    /// Sent as a statement of its own, see [`secondary_roles`].
+    #[serde(skip)]
+    secondary_roles: Option<secondary_roles::SecondaryRoles>,
+    #[serde(skip)]
+    payload: Payload,
 }
 
-#[derive(Serialize, Debug)]
+impl SnowflakeExecutorSQLJSON<'_> {
+    fn into_owned(self) -> SnowflakeExecutorSQLJSON<'static> {
+        SnowflakeExecutorSQLJSON {
+            statement: Cow::Owned(self.statement.into_owned()),
+            timeout: self.timeout,
+            database: self.database,
+            warehouse: self.warehouse,
+            role: self.role,
+            bindings: self.bindings,
+            parameters: self.parameters,
+            secondary_roles: self.secondary_roles,
+            payload: self.payload,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
 pub struct Binding {
     #[serde(rename = "type")]
     value_type: String,
-    value: String,
+    /// `None` binds `NULL`.
+    value: Option<String>,
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::test_util::MockSnowflake;
+
     use super::*;
 
     #[test]
@@ -205,6 +767,102 @@ mod tests {
         }
         Ok(())
     }
+
+    #[tokio::test]
+    async fn wrap_statement() -> Result<(), anyhow::Error> {
+        let sql = MockSnowflake::start().await.connector();
+        let mut sql = sql.execute("DB", "WH")
+            .sql("SELECT * FROM TEST_TABLE WHERE id = ?; ")?;
+        sql.wrap_statement("SELECT COUNT(*) FROM (", ")");
+        assert_eq!(sql.statement.statement, "SELECT COUNT(*) FROM (SELECT * FROM TEST_TABLE WHERE id = ?\n)");
+        sql.wrap_statement("EXPLAIN USING TEXT ", "");
+        assert!(sql.statement.statement.starts_with("EXPLAIN USING TEXT SELECT COUNT(*)"));
+        sql.statement.statement = "SELECT 1 -- latest".into();
+        sql.wrap_statement("SELECT * FROM (", ") LIMIT 0");
+        assert_eq!(sql.statement.statement, "SELECT * FROM (SELECT 1 -- latest\n) LIMIT 0");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn default_database() -> Result<(), anyhow::Error> {
+        let connector = MockSnowflake::start().await.connector();
+        let sql = connector.execute_on("WH").sql("SELECT 1 FROM DB.PUBLIC.T")?;
+        assert!(serde_json::to_value(StatementBody(&sql.statement))?.get("database").is_none());
+        let connector = connector.with_default_database("DB");
+        assert_eq!(connector.execute_on("WH").sql("SELECT 1")?.statement.database.as_deref(), Some("DB"));
+        assert_eq!(connector.execute("OTHER", "WH").sql("SELECT 1")?.statement.database.as_deref(), Some("OTHER"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn query() -> Result<(), anyhow::Error> {
+        let snowflake = test_util::MockSnowflake::start().await;
+        snowflake.mount(&test_util::StatementFixture::new()
+            .column("ID", "fixed")
+            .column("NAME", "text")
+            .row([Some("1"), Some("a")])).await;
+        let connector = snowflake.connector();
+        let rows = connector.query::<(i64, String), _>("SELECT ID, NAME FROM T WHERE ID = ? AND NAME = ?", (1, "a")).await?;
+        assert_eq!(rows, [(1, "a".to_owned())]);
+        assert_eq!(connector.query::<(i64, String), _>("SELECT ID, NAME FROM T", ()).await?.len(), 1);
+        let sql = connector.execute_on("").sql("SELECT 1")?;
+        assert!(serde_json::to_value(StatementBody(&sql.statement))?.get("warehouse").is_none());
+        let sql = connector.execute_on("").sql("SELECT CURRENT_TIMESTAMP()")?.with_timezone("UTC");
+        assert_eq!(serde_json::to_value(StatementBody(&sql.statement))?["parameters"]["TIMEZONE"], "UTC");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn read_only() -> Result<(), anyhow::Error> {
+        let connector = MockSnowflake::start().await.connector().read_only(true);
+        assert!(connector.execute("DB", "WH").sql("SELECT 'DROP' FROM T").is_ok());
+        assert!(matches!(
+            connector.execute("DB", "WH").sql("SELECT 1; DELETE FROM T"),
+            Err(SnowflakeError::InvalidStatement(_)),
+        ));
+        assert!(connector.execute("DB", "WH").multiple_sql(&["SELECT 1", "TRUNCATE T"]).is_err());
+        assert!(connector.execute("DB", "WH").script("BEGIN; INSERT INTO T VALUES (1); COMMIT;").is_err());
+        assert!(connector.pipe("DB.PUBLIC.P").is_err());
+        assert!(connector.execute("DB", "WH").allow_writes().sql("DELETE FROM T").is_ok());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn owned_is_send() -> Result<(), anyhow::Error> {
+        fn assert_send<T: Send + 'static>(_: &T) {}
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<SnowflakeConnector>();
+        assert_send_sync::<SnowflakeSQL<'static>>();
+        assert_send_sync::<Cursor>();
+        assert_send_sync::<multiple::MultipleSnowflakeSQLResponse>();
+        assert_send_sync::<errors::SnowflakeError>();
+
+        let connector = MockSnowflake::start().await.connector();
+        let statement = String::from("SELECT 1");
+        let sql = connector.execute("DB", "WH")
+            .sql(&statement)?
+            .into_owned();
+        drop(statement);
+        assert_send(&sql.clone().select::<(u32,)>());
+        assert_send(&sql.cursor());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn request_id() -> Result<(), anyhow::Error> {
+        let sql = MockSnowflake::start().await.connector();
+        let request_id = uuid::Uuid::new_v4();
+        let sql = sql.execute("DB", "WH")
+            .sql("SELECT 1")?
+            .with_request_id(request_id);
+        assert!(sql.get_url().ends_with(&format!("requestId={request_id}")));
+        let error = sql.correlate(Some("01a2"), SnowflakeError::SqlExecution(anyhow::anyhow!("timed out")));
+        assert_eq!(
+            format!("{:#}", anyhow::Error::from(error)),
+            format!("could not execute sql statement: request {request_id} statement 01a2: timed out"),
+        );
+        Ok(())
+    }
 }
 
 // Features
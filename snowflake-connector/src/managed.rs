@@ -0,0 +1,199 @@
+//! Connectors managed by deadpool or bb8, for applications that already pool their other resources with them.
+//!
+//! Connectors are rebuilt before their token expires and can be checked with `SELECT 1` when handed out.
+//!
+//! ```no_run
+//! # use snowflake_connector::{managed::ConnectorManager, SnowflakeConnector};
+//! # #[cfg(feature = "deadpool")]
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let manager = ConnectorManager::new(|| SnowflakeConnector::try_new(
+//!     "PUBLIC/KEY/PATH",
+//!     "PRIVATE/KEY/PATH",
+//!     "COMPANY.ACCOUNT".into(),
+//!     "ACCOUNT".into(),
+//!     "USER@EXAMPLE.COM".into(),
+//! ))
+//! .with_health_check("WH");
+//! let pool = deadpool::managed::Pool::<ConnectorManager>::builder(manager).max_size(4).build()?;
+//! let connector = pool.get().await.map_err(|e| e.to_string())?;
+//! let count: u64 = connector.execute("DB", "WH").sql("SELECT COUNT(*) FROM USERS")?.select_scalar().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{ops::Deref, time::{Duration, Instant}};
+
+use crate::{errors::SnowflakeError, SnowflakeConnector};
+
+/// How long a connector is used before it is rebuilt by default, its token expires after an hour.
+pub const DEFAULT_TOKEN_LIFETIME: Duration = Duration::from_secs(50 * 60);
+
+type Factory = Box<dyn Fn() -> Result<SnowflakeConnector, SnowflakeError> + Send + Sync>;
+
+/// Creates connectors for a pool, see [`deadpool::managed::Manager`] and [`bb8::ManageConnection`].
+pub struct ConnectorManager {
+    factory: Factory,
+    token_lifetime: Duration,
+    health_check: Option<String>,
+}
+
+/// Connector of a pool along with when its token was created.
+#[derive(Debug)]
+pub struct ManagedConnector {
+    connector: SnowflakeConnector,
+    created: Instant,
+}
+
+impl ConnectorManager {
+    /// Connectors are built with `factory`, ex. [`SnowflakeConnector::try_new`] followed by its settings.
+    pub fn new<F: Fn() -> Result<SnowflakeConnector, SnowflakeError> + Send + Sync + 'static>(factory: F) -> Self {
+        ConnectorManager {
+            factory: Box::new(factory),
+            token_lifetime: DEFAULT_TOKEN_LIFETIME,
+            health_check: None,
+        }
+    }
+    /// Rebuild connectors older than `lifetime` when they are recycled, [`DEFAULT_TOKEN_LIFETIME`] by default.
+    pub fn with_token_lifetime(mut self, lifetime: Duration) -> Self {
+        self.token_lifetime = lifetime;
+        self
+    }
+    /// Run `SELECT 1` on `warehouse` before handing out a connector, connectors it fails for are discarded.
+    pub fn with_health_check(mut self, warehouse: impl Into<String>) -> Self {
+        self.health_check = Some(warehouse.into());
+        self
+    }
+    fn create(&self) -> Result<ManagedConnector, SnowflakeError> {
+        Ok(ManagedConnector {
+            connector: (self.factory)()?,
+            created: Instant::now(),
+        })
+    }
+    /// Rebuild `connector` if its token is about to expire, then run the health check if any.
+    async fn recycle(&self, connector: &mut ManagedConnector) -> Result<(), SnowflakeError> {
+        if connector.is_expired(self.token_lifetime) {
+            *connector = self.create()?;
+        }
+        if let Some(warehouse) = &self.health_check {
+            connector.execute_on(warehouse)
+                .sql("SELECT 1")?
+                .select_scalar::<i64>().await?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ConnectorManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ConnectorManager")
+            .field("token_lifetime", &self.token_lifetime)
+            .field("health_check", &self.health_check)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ManagedConnector {
+    /// When the connector and its token were created.
+    pub fn created(&self) -> Instant {
+        self.created
+    }
+    fn is_expired(&self, lifetime: Duration) -> bool {
+        self.created.elapsed() >= lifetime
+    }
+}
+
+impl Deref for ManagedConnector {
+    type Target = SnowflakeConnector;
+    fn deref(&self) -> &SnowflakeConnector {
+        &self.connector
+    }
+}
+
+#[cfg(feature = "deadpool")]
+impl deadpool::managed::Manager for ConnectorManager {
+    type Type = ManagedConnector;
+    type Error = SnowflakeError;
+
+    async fn create(&self) -> Result<ManagedConnector, SnowflakeError> {
+        ConnectorManager::create(self)
+    }
+    async fn recycle(
+        &self,
+        connector: &mut ManagedConnector,
+        _: &deadpool::managed::Metrics,
+    ) -> deadpool::managed::RecycleResult<SnowflakeError> {
+        ConnectorManager::recycle(self, connector).await
+            .map_err(deadpool::managed::RecycleError::Backend)
+    }
+}
+
+#[cfg(feature = "bb8")]
+impl bb8::ManageConnection for ConnectorManager {
+    type Connection = ManagedConnector;
+    type Error = SnowflakeError;
+
+    async fn connect(&self) -> Result<ManagedConnector, SnowflakeError> {
+        self.create()
+    }
+    async fn is_valid(&self, connector: &mut ManagedConnector) -> Result<(), SnowflakeError> {
+        self.recycle(connector).await
+    }
+    fn has_broken(&self, _: &mut ManagedConnector) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn recycled() -> Result<(), anyhow::Error> {
+        let snowflake = std::sync::Arc::new(MockSnowflake::start().await);
+        snowflake.mount(&StatementFixture::new().column("1", "fixed").row([Some("1")])).await;
+        let manager = {
+            let snowflake = snowflake.clone();
+            ConnectorManager::new(move || Ok(snowflake.connector()))
+        };
+        let manager = manager.with_token_lifetime(Duration::ZERO).with_health_check("WH");
+        let mut connector = manager.create()?;
+        let created = connector.created();
+        manager.recycle(&mut connector).await?;
+        assert!(connector.created() > created);
+
+        let manager = ConnectorManager::new(move || Ok(snowflake.connector()));
+        let mut connector = manager.create()?;
+        let created = connector.created();
+        manager.recycle(&mut connector).await?;
+        assert_eq!(connector.created(), created);
+        Ok(())
+    }
+
+    #[cfg(feature = "deadpool")]
+    #[tokio::test]
+    async fn deadpool() -> Result<(), anyhow::Error> {
+        let snowflake = std::sync::Arc::new(MockSnowflake::start().await);
+        snowflake.mount(&StatementFixture::new().column("1", "fixed").row([Some("1")])).await;
+        let manager = ConnectorManager::new(move || Ok(snowflake.connector())).with_health_check("WH");
+        let pool = deadpool::managed::Pool::<ConnectorManager>::builder(manager).max_size(1).build()?;
+        for _ in 0..2 {
+            let connector = pool.get().await.map_err(|e| anyhow::anyhow!("{e}"))?;
+            assert_eq!(connector.execute_on("WH").sql("SELECT 1")?.select_scalar::<i64>().await?, 1);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "bb8")]
+    #[tokio::test]
+    async fn bb8() -> Result<(), anyhow::Error> {
+        let snowflake = std::sync::Arc::new(MockSnowflake::start().await);
+        snowflake.mount(&StatementFixture::new().column("1", "fixed").row([Some("1")])).await;
+        let manager = ConnectorManager::new(move || Ok(snowflake.connector())).with_health_check("WH");
+        let pool = bb8::Pool::builder().max_size(1).build(manager).await?;
+        let connector = pool.get().await.map_err(|e| anyhow::anyhow!("{e:?}"))?;
+        assert_eq!(connector.execute_on("WH").sql("SELECT 1")?.select_scalar::<i64>().await?, 1);
+        Ok(())
+    }
+}
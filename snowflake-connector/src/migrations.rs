@@ -0,0 +1,325 @@
+//! Versioned migrations, applied in order and recorded in a table of the database they run in.
+//!
+//! SQL migrations run in one request along with recording them, inside a transaction,
+//! so a failed migration is neither partially applied nor recorded. DDL is not transactional in Snowflake though,
+//! each DDL statement commits on its own along with what ran before it in the transaction,
+//! keep migrations with DDL to one DDL statement each.
+//! Function migrations are recorded after they returned, make them safe to run again.
+//!
+//! ```no_run
+//! # use snowflake_connector::{migrations::Migration, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! // Or read files named `V<version>__<name>.sql` at compile time,
+//! // ex. `embed_migrations!("migrations/V1__create_orders.sql", "migrations/V2__add_totals.sql")?`.
+//! let mut migrations = vec![
+//!     Migration::sql(1, "create_orders", "CREATE TABLE ORDERS (ID NUMBER, TOTAL NUMBER(12,2))"),
+//!     Migration::sql(2, "add_notes", "ALTER TABLE ORDERS ADD COLUMN NOTE VARCHAR"),
+//! ];
+//! migrations.push(Migration::function(3, "backfill_totals", |connector| Box::pin(async move {
+//!     connector.execute("DB", "WH").sql("UPDATE ORDERS SET TOTAL = 0 WHERE TOTAL IS NULL")?.manipulate().await?;
+//!     Ok(())
+//! })));
+//! let migrator = connector.migrator("DB", "WH", migrations);
+//! for migration in migrator.plan().await? {
+//!     println!("pending V{} {}", migration.version, migration.name);
+//! }
+//! migrator.apply().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{borrow::Cow, fmt, sync::Arc, time::Duration};
+
+use futures_util::future::BoxFuture;
+
+use crate::{
+    ddl::{ColumnDefinition, CreateTable},
+    error_code::ErrorCode,
+    errors::SnowflakeError,
+    escape::{escape_qualified_name, escape_string_literal},
+    polling::{Backoff, Deadline},
+    status::StatementOutcome,
+    SnowflakeConnector,
+};
+
+/// Table migrations are recorded in by default, in the schema statements run in.
+pub const DEFAULT_MIGRATIONS_TABLE: &str = "SNOWFLAKE_CONNECTOR_MIGRATIONS";
+
+/// Namespace of [`Migration::checksum`].
+const CHECKSUM_NAMESPACE: uuid::Uuid = uuid::uuid!("8f0c2b1e-4a67-4f0e-b6d2-1c9a3e5f7d40");
+
+type MigrationFn = Arc<dyn for<'c> Fn(&'c SnowflakeConnector) -> BoxFuture<'c, Result<(), SnowflakeError>> + Send + Sync>;
+
+#[derive(Clone)]
+pub struct Migration {
+    pub version: u64,
+    pub name: String,
+    step: Step,
+}
+
+#[derive(Clone)]
+enum Step {
+    Sql(Cow<'static, str>),
+    Function(MigrationFn),
+}
+
+impl Migration {
+    /// Migration running `sql`, which may be several `;` separated statements.
+    pub fn sql(version: u64, name: impl Into<String>, sql: impl Into<Cow<'static, str>>) -> Self {
+        Migration { version, name: name.into(), step: Step::Sql(sql.into()) }
+    }
+    /// Migration running `f`, ex. to transform rows in Rust.
+    pub fn function<F>(version: u64, name: impl Into<String>, f: F) -> Self
+        where F: for<'c> Fn(&'c SnowflakeConnector) -> BoxFuture<'c, Result<(), SnowflakeError>> + Send + Sync + 'static
+    {
+        Migration { version, name: name.into(), step: Step::Function(Arc::new(f)) }
+    }
+    /// SQL migration of a file named `V<version>__<name>.sql` as Flyway and refinery name them,
+    /// `path` may include directories.
+    pub fn from_file(path: &str, sql: impl Into<Cow<'static, str>>) -> Result<Self, SnowflakeError> {
+        let invalid = || SnowflakeError::InvalidStatement(anyhow::anyhow!(
+            "migration file {path} is not named V<version>__<name>.sql",
+        ));
+        let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        let (version, name) = file_name
+            .strip_suffix(".sql")
+            .and_then(|stem| stem.strip_prefix(['V', 'v']))
+            .and_then(|stem| stem.split_once("__"))
+            .ok_or_else(invalid)?;
+        let version = version.parse().map_err(|_| invalid())?;
+        Ok(Migration::sql(version, name, sql))
+    }
+    /// Changes when the SQL of a migration changes, empty for function migrations.
+    pub fn checksum(&self) -> String {
+        match &self.step {
+            Step::Sql(sql) => uuid::Uuid::new_v5(&CHECKSUM_NAMESPACE, sql.trim().as_bytes()).to_string(),
+            Step::Function(_) => String::new(),
+        }
+    }
+}
+
+impl fmt::Debug for Migration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Migration")
+            .field("version", &self.version)
+            .field("name", &self.name)
+            .field("checksum", &self.checksum())
+            .finish()
+    }
+}
+
+/// SQL migrations of the files at `paths`, read at compile time relative to the calling file,
+/// see [`Migration::from_file`].
+#[macro_export]
+macro_rules! embed_migrations {
+    ($($path:literal),+ $(,)?) => {
+        [$(($path, include_str!($path))),+]
+            .into_iter()
+            .map(|(path, sql)| $crate::migrations::Migration::from_file(path, sql))
+            .collect::<Result<Vec<_>, _>>()
+    };
+}
+
+/// Applies migrations in a database, see [`SnowflakeConnector::migrator`].
+#[derive(Debug)]
+pub struct Migrator<'a> {
+    connector: &'a SnowflakeConnector,
+    database: String,
+    warehouse: String,
+    table: String,
+    migrations: Vec<Migration>,
+    timeout: Duration,
+}
+
+impl SnowflakeConnector {
+    /// Apply `migrations` in `database`, in the order of their version.
+    pub fn migrator(&self, database: impl Into<String>, warehouse: impl Into<String>, mut migrations: Vec<Migration>) -> Migrator<'_> {
+        migrations.sort_by_key(|migration| migration.version);
+        Migrator {
+            connector: self,
+            database: database.into(),
+            warehouse: warehouse.into(),
+            table: DEFAULT_MIGRATIONS_TABLE.into(),
+            migrations,
+            timeout: Duration::from_secs(600),
+        }
+    }
+}
+
+impl<'a> Migrator<'a> {
+    /// Record migrations in `table`, [`DEFAULT_MIGRATIONS_TABLE`] by default.
+    pub fn with_table(mut self, table: impl Into<String>) -> Self {
+        self.table = table.into();
+        self
+    }
+    /// How long a SQL migration may run, 10 minutes by default.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    /// Migrations not applied yet, in the order [`apply`](Self::apply) runs them.
+    ///
+    /// Errors if two migrations share a version, or a SQL migration changed since it was applied.
+    pub async fn plan(&self) -> Result<Vec<&Migration>, SnowflakeError> {
+        if let Some(pair) = self.migrations.windows(2).find(|pair| pair[0].version == pair[1].version) {
+            return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                "migrations {} and {} share version {}", pair[0].name, pair[1].name, pair[0].version,
+            )));
+        }
+        let applied = self.applied().await?;
+        let mut pending = Vec::new();
+        for migration in &self.migrations {
+            match applied.iter().find(|(version, _)| *version == migration.version) {
+                Some((_, checksum)) if *checksum != migration.checksum() => {
+                    return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                        "migration V{} {} changed since it was applied", migration.version, migration.name,
+                    )));
+                },
+                Some(_) => {},
+                None => pending.push(migration),
+            }
+        }
+        Ok(pending)
+    }
+    /// Apply the migrations of the [plan](Self::plan), stops at the first that fails.
+    pub async fn apply(&self) -> Result<Vec<&Migration>, SnowflakeError> {
+        let pending = self.plan().await?;
+        if pending.is_empty() {
+            return Ok(pending);
+        }
+        let table = CreateTable::new(&self.table)
+            .column(ColumnDefinition::new("VERSION", "NUMBER(38,0)").not_null())
+            .column(ColumnDefinition::new("NAME", "VARCHAR").not_null())
+            .column(ColumnDefinition::new("CHECKSUM", "VARCHAR").not_null())
+            .column(ColumnDefinition::new("APPLIED_ON", "TIMESTAMP_LTZ").not_null())
+            .if_not_exists();
+        self.connector.execute(&self.database, &self.warehouse).create_table(&table).await?;
+        for migration in &pending {
+            match &migration.step {
+                Step::Sql(sql) => {
+                    // One part per line, a migration ending in a `--` comment would comment out the rest.
+                    let script = format!("BEGIN;\n{}\n;\n{};\nCOMMIT;", sql.trim().trim_end_matches(';'), self.record(migration));
+                    self.connector.execute(&self.database, &self.warehouse)
+                        .script(&script)?
+                        .run().await?
+                        .wait_all(Deadline::after(self.timeout), Backoff::default()).await?
+                        .into_iter()
+                        .try_for_each(StatementOutcome::succeeded)?;
+                },
+                Step::Function(f) => {
+                    f(self.connector).await?;
+                    // The record is built by the crate, so it is not checked for interpolated values.
+                    self.connector.execute(&self.database, &self.warehouse)
+                        .statement(Cow::Owned(self.record(migration)))?
+                        .manipulate().await?;
+                },
+            }
+        }
+        Ok(pending)
+    }
+    /// Versions and checksums of the recorded migrations, none if the table does not exist yet.
+    async fn applied(&self) -> Result<Vec<(u64, String)>, SnowflakeError> {
+        let statement = format!("SELECT VERSION, CHECKSUM FROM {}", escape_qualified_name(&self.table));
+        let applied = self.connector.execute(&self.database, &self.warehouse)
+            .sql(&statement)?
+            .select::<(u64, String)>().await;
+        match applied {
+            Ok(applied) => Ok(applied.data),
+            Err(e) if e.error_code() == Some(ErrorCode::ObjectNotFound) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+    fn record(&self, migration: &Migration) -> String {
+        format!(
+            "INSERT INTO {} (VERSION, NAME, CHECKSUM, APPLIED_ON) SELECT {}, {}, {}, CURRENT_TIMESTAMP()",
+            escape_qualified_name(&self.table),
+            migration.version,
+            escape_string_literal(&migration.name),
+            escape_string_literal(&migration.checksum()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{matchers::{body_string_contains, method}, Mock, ResponseTemplate};
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[test]
+    fn file_names() -> Result<(), SnowflakeError> {
+        let migration = Migration::from_file("migrations/V12__add_totals.sql", "ALTER TABLE ORDERS ADD COLUMN TOTAL NUMBER")?;
+        assert_eq!((migration.version, migration.name.as_str()), (12, "add_totals"));
+        assert_eq!(migration.checksum(), Migration::sql(1, "other", " ALTER TABLE ORDERS ADD COLUMN TOTAL NUMBER\n").checksum());
+        assert!(Migration::from_file("add_totals.sql", "").is_err());
+        assert!(Migration::from_file("Vx__add_totals.sql", "").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn apply() -> Result<(), anyhow::Error> {
+        let create = Migration::sql(1, "create_orders", "CREATE TABLE ORDERS (ID INT);");
+        let snowflake = MockSnowflake::start().await;
+        let applied = StatementFixture::new()
+            .column("VERSION", "fixed")
+            .column("CHECKSUM", "text")
+            .row([Some("1".to_owned()), Some(create.checksum())]);
+        Mock::given(method("POST"))
+            .and(body_string_contains("SELECT VERSION, CHECKSUM"))
+            .respond_with(applied.success())
+            .with_priority(1)
+            .mount(&snowflake.server).await;
+        snowflake.mount(&StatementFixture::new()).await;
+        let connector = snowflake.connector();
+
+        let migrations = vec![
+            Migration::sql(2, "add_totals", "ALTER TABLE ORDERS ADD COLUMN TOTAL NUMBER -- in cents"),
+            create.clone(),
+        ];
+        let migrator = connector.migrator("DB", "WH", migrations);
+        let pending = migrator.apply().await?;
+        assert_eq!(pending.iter().map(|migration| migration.version).collect::<Vec<_>>(), [2]);
+        let requests = snowflake.server.received_requests().await.unwrap_or_default();
+        let script = requests.iter()
+            .map(|request| String::from_utf8_lossy(&request.body).into_owned())
+            .find(|body| body.contains("BEGIN"))
+            .expect("migration script");
+        assert!(script.contains(
+            r"BEGIN;\nALTER TABLE ORDERS ADD COLUMN TOTAL NUMBER -- in cents\n;\nINSERT INTO SNOWFLAKE_CONNECTOR_MIGRATIONS (VERSION, NAME, CHECKSUM, APPLIED_ON) SELECT 2, 'add_totals'",
+        ), "{script}");
+        assert!(script.contains(r"CURRENT_TIMESTAMP();\nCOMMIT;"), "{script}");
+
+        let changed = vec![Migration::sql(1, "create_orders", "CREATE TABLE ORDERS (ID BIGINT);")];
+        assert!(connector.migrator("DB", "WH", changed).plan().await.is_err());
+        let duplicate = vec![create.clone(), create];
+        assert!(connector.migrator("DB", "WH", duplicate).plan().await.is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn function_denying_interpolation() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("INSERT INTO SNOWFLAKE_CONNECTOR_MIGRATIONS"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "message": "successfully executed",
+                "stats": { "numRowsInserted": 1, "numRowsDeleted": 0, "numRowsUpdated": 0, "numDmlDuplicates": 0 },
+                "statementHandle": "handle",
+                "requestId": "request",
+            })))
+            .with_priority(1)
+            .mount(&snowflake.server).await;
+        snowflake.mount(&StatementFixture::new()).await;
+        let connector = snowflake.connector().deny_interpolation(true);
+        let migrations = vec![Migration::function(1, "backfill", |_| Box::pin(async { Ok(()) }))];
+        connector.migrator("DB", "WH", migrations).apply().await?;
+        let requests = snowflake.server.received_requests().await.unwrap_or_default();
+        assert!(
+            requests.iter().any(|request| String::from_utf8_lossy(&request.body).contains("SELECT 1, 'backfill'")),
+            "the function migration is recorded",
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,449 @@
+use std::{collections::HashMap, sync::Arc};
+
+use snowflake_deserializer::{bindings::BindingValue, redact::{self, RedactionPolicy}, SnowflakeDeserialize, SnowflakeSQLResult};
+
+use crate::{
+    errors::SnowflakeError,
+    partitions::{self, PartitionFetchPolicy},
+    polling::{Backoff, Deadline},
+    progress::ProgressTracker,
+    row_filter::RowFilter,
+    shutdown::InFlight,
+    status::{self, StatementOutcome},
+    Binding, SnowflakeConnector, SnowflakeSQL,
+};
+
+/// Several statements sent in a single request, executed in order by Snowflake.
+///
+/// Statements run asynchronously, [`MultipleSnowflakeSQL::run`] returns as soon
+/// as Snowflake accepted them.
+///
+/// Statements given their own warehouse or role with [`MultipleSnowflakeSQL::with_statement_warehouse`]
+/// or [`MultipleSnowflakeSQL::with_statement_role`] are split into separate requests,
+/// consecutive statements sharing the same warehouse and role stay in one request.
+/// Each request is only submitted once the previous one succeeded, so statements still run in order,
+/// and [`run`](MultipleSnowflakeSQL::run) returns once the last one was accepted.
+#[derive(Debug)]
+pub struct MultipleSnowflakeSQL<'a> {
+    sql: SnowflakeSQL<'a>,
+    statements: Vec<StatementEntry>,
+}
+
+#[derive(Debug)]
+struct StatementEntry {
+    statement: String,
+    /// Unknown for scripts, see [`MultipleSnowflakeSQL::script`].
+    count: Option<usize>,
+    warehouse: Option<String>,
+    role: Option<String>,
+}
+
+impl<'a> MultipleSnowflakeSQL<'a> {
+    pub(crate) fn new(sql: SnowflakeSQL<'a>, statements: &[&str]) -> Self {
+        MultipleSnowflakeSQL {
+            sql,
+            statements: statements.iter()
+                .map(|statement| StatementEntry {
+                    statement: statement.to_string(),
+                    count: Some(1),
+                    warehouse: None,
+                    role: None,
+                })
+                .collect(),
+        }
+    }
+    /// Script of any number of `;` separated statements, sent with `MULTI_STATEMENT_COUNT=0`.
+    pub(crate) fn script(sql: SnowflakeSQL<'a>, script: &str) -> Self {
+        MultipleSnowflakeSQL {
+            sql,
+            statements: vec![StatementEntry {
+                statement: script.to_string(),
+                count: None,
+                warehouse: None,
+                role: None,
+            }],
+        }
+    }
+    pub fn with_timeout(mut self, timeout: u32) -> MultipleSnowflakeSQL<'a> {
+        self.sql = self.sql.with_timeout(timeout);
+        self
+    }
+    pub fn with_role(mut self, role: impl Into<String>) -> MultipleSnowflakeSQL<'a> {
+        self.sql = self.sql.with_role(role);
+        self
+    }
+    /// See [`SnowflakeSQL::with_timezone`], used by every statement.
+    pub fn with_timezone(mut self, timezone: impl Into<String>) -> MultipleSnowflakeSQL<'a> {
+        self.sql = self.sql.with_timezone(timezone);
+        self
+    }
+    /// Run statement `index` on a different warehouse than the other statements.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn with_statement_warehouse(mut self, index: usize, warehouse: impl Into<String>) -> MultipleSnowflakeSQL<'a> {
+        self.statements[index].warehouse = Some(warehouse.into());
+        self
+    }
+    /// Run statement `index` with a different role than the other statements.
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn with_statement_role(mut self, index: usize, role: impl Into<String>) -> MultipleSnowflakeSQL<'a> {
+        self.statements[index].role = Some(role.into());
+        self
+    }
+    pub(crate) fn map_sql<F: FnOnce(SnowflakeSQL<'a>) -> SnowflakeSQL<'a>>(mut self, f: F) -> MultipleSnowflakeSQL<'a> {
+        self.sql = f(self.sql);
+        self
+    }
+    pub(crate) fn try_map_sql<F>(mut self, f: F) -> Result<MultipleSnowflakeSQL<'a>, SnowflakeError>
+        where F: FnOnce(SnowflakeSQL<'a>) -> Result<SnowflakeSQL<'a>, SnowflakeError>
+    {
+        self.sql = f(self.sql)?;
+        Ok(self)
+    }
+    /// See [`SnowflakeSQL::into_owned`].
+    pub fn into_owned(self) -> MultipleSnowflakeSQL<'static> {
+        MultipleSnowflakeSQL {
+            sql: self.sql.into_owned(),
+            statements: self.statements,
+        }
+    }
+    /// Bindings are positional across all statements.
+    pub fn add_binding<T: Into<BindingValue>>(mut self, value: T) -> MultipleSnowflakeSQL<'a> {
+        self.sql = self.sql.add_binding(value);
+        self
+    }
+    pub async fn run(self) -> Result<MultipleSnowflakeSQLResponse, SnowflakeError> {
+        let client = self.sql.client.clone();
+        let host = self.sql.host.clone();
+        let in_flight = self.sql.in_flight.clone();
+        let partition_policy = self.sql.partition_policy.clone();
+        let redaction = self.sql.redaction.clone();
+        let row_filter = self.sql.row_filter.clone();
+        let null_sentinel = self.sql.null_sentinel.clone();
+        let skipped_children = usize::from(self.sql.statement.secondary_roles.is_some());
+        self.check_cost().await?;
+        let mut outcomes: Vec<StatementOutcome> = Vec::new();
+        for sql in self.into_requests()? {
+            if let Some(previous) = outcomes.last_mut() {
+                wait_for(&sql, previous).await?;
+            }
+            outcomes.push(sql.submit().await?);
+        }
+        Ok(MultipleSnowflakeSQLResponse {
+            client,
+            host,
+            outcomes,
+            in_flight,
+            partition_policy,
+            redaction,
+            row_filter,
+            null_sentinel,
+            skipped_children,
+        })
+    }
+    /// Check each statement against the cost limits, if any, before any of them runs.
+    ///
+    /// Scripts are not checked, their statements are not known before they run.
+    async fn check_cost(&self) -> Result<(), SnowflakeError> {
+        if self.sql.cost_limits.is_none() {
+            return Ok(());
+        }
+        let mut bindings = bindings_in_order(self.sql.statement.bindings.clone()).into_iter();
+        for entry in &self.statements {
+            let entry_bindings: HashMap<_, _> = bindings.by_ref()
+                .take(count_placeholders(&entry.statement))
+                .enumerate()
+                .map(|(i, binding)| ((i + 1).to_string(), binding))
+                .collect();
+            if entry.count.is_none() {
+                continue;
+            }
+            let mut sql = self.sql.clone();
+            sql.statement.statement = entry.statement.clone().into();
+            sql.statement.bindings = (!entry_bindings.is_empty()).then_some(entry_bindings);
+            if let Some(warehouse) = &entry.warehouse {
+                sql.statement.warehouse = warehouse.clone();
+            }
+            if let Some(role) = &entry.role {
+                sql.statement.role = Some(role.clone());
+            }
+            sql.check_cost().await?;
+        }
+        Ok(())
+    }
+    /// One request per run of consecutive statements sharing a warehouse and role.
+    fn into_requests(self) -> Result<Vec<SnowflakeSQL<'a>>, SnowflakeError> {
+        let mut groups: Vec<&[StatementEntry]> = Vec::new();
+        let mut rest = self.statements.as_slice();
+        while let Some(first) = rest.first() {
+            let len = rest.iter()
+                .take_while(|entry| entry.warehouse == first.warehouse && entry.role == first.role)
+                .count();
+            let (group, remaining) = rest.split_at(len);
+            groups.push(group);
+            rest = remaining;
+        }
+        let mut bindings = bindings_in_order(self.sql.statement.bindings.clone());
+        if groups.len() > 1 {
+            let placeholders: usize = self.statements.iter()
+                .map(|entry| count_placeholders(&entry.statement))
+                .sum();
+            if placeholders != bindings.len() {
+                return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                    "statements contain {placeholders} placeholder(s) but {} binding(s) were added",
+                    bindings.len(),
+                )));
+            }
+        }
+        let mut requests = Vec::with_capacity(groups.len());
+        for group in groups {
+            let mut sql = self.sql.clone();
+            let statements: Vec<&str> = group.iter().map(|entry| entry.statement.as_str()).collect();
+            sql.statement.statement = statements.join(";\n").into();
+            sql.statement.parameters
+                .get_or_insert_with(Default::default)
+                .insert("MULTI_STATEMENT_COUNT".into(), group.iter()
+                    .map(|entry| entry.count)
+                    .sum::<Option<usize>>()
+                    .unwrap_or(0)
+                    .to_string());
+            if let Some(warehouse) = &group[0].warehouse {
+                sql.statement.warehouse = warehouse.clone();
+            }
+            if let Some(role) = &group[0].role {
+                sql.statement.role = Some(role.clone());
+            }
+            let placeholders = if requests.is_empty() && group.len() == self.statements.len() {
+                bindings.len()
+            } else {
+                statements.iter().map(|statement| count_placeholders(statement)).sum()
+            };
+            let group_bindings: HashMap<_, _> = bindings.drain(..placeholders)
+                .enumerate()
+                .map(|(i, binding)| ((i + 1).to_string(), binding))
+                .collect();
+            sql.statement.bindings = (!group_bindings.is_empty()).then_some(group_bindings);
+            requests.push(sql);
+        }
+        Ok(requests)
+    }
+}
+
+/// Poll `outcome` until its statement finished, errors unless it succeeded.
+async fn wait_for(sql: &SnowflakeSQL<'_>, outcome: &mut StatementOutcome) -> Result<(), SnowflakeError> {
+    let deadline = Deadline::after(sql.poll_timeout());
+    let backoff = Backoff::default();
+    let mut attempt = 0;
+    while outcome.is_pending() && !deadline.has_passed() {
+        tokio::time::sleep(backoff.delay(attempt).min(deadline.remaining())).await;
+        attempt += 1;
+        *outcome = status::fetch_status(&sql.client, &sql.host, outcome.statement_handle(), &sql.null_sentinel).await?;
+        sql.in_flight.track(outcome);
+    }
+    match outcome {
+        StatementOutcome::Success(_) => Ok(()),
+        StatementOutcome::Failed(status) => Err(SnowflakeError::StatementFailed(Box::new(status.clone()))),
+        StatementOutcome::Pending(status) |
+        StatementOutcome::TimedOut(status)
+            => Err(SnowflakeError::StatementTimedOut(Box::new(status.clone()))),
+    }
+}
+
+fn bindings_in_order(bindings: Option<HashMap<String, Binding>>) -> Vec<Binding> {
+    let mut bindings: Vec<(usize, Binding)> = bindings.unwrap_or_default()
+        .into_iter()
+        .filter_map(|(index, binding)| Some((index.parse().ok()?, binding)))
+        .collect();
+    bindings.sort_by_key(|(index, _)| *index);
+    bindings.into_iter().map(|(_, binding)| binding).collect()
+}
+
+/// Number of `?` placeholders, ignoring string literals, quoted identifiers and comments.
+fn count_placeholders(statement: &str) -> usize {
+    let mut count = 0;
+    let mut chars = statement.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '?' => count += 1,
+            '\'' | '"' => {
+                while let Some(next) = chars.next() {
+                    if next == '\\' && c == '\'' {
+                        chars.next();
+                    } else if next == c {
+                        break;
+                    }
+                }
+            },
+            '-' if chars.peek() == Some(&'-') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            },
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            },
+            _ => {},
+        }
+    }
+    count
+}
+
+/// Submitted statements, poll with [`MultipleSnowflakeSQLResponse::complete`]
+/// or wait for them with [`MultipleSnowflakeSQLResponse::wait_all`].
+#[derive(Debug)]
+pub struct MultipleSnowflakeSQLResponse {
+    client: reqwest::Client,
+    host: Arc<str>,
+    outcomes: Vec<StatementOutcome>,
+    in_flight: Arc<InFlight>,
+    partition_policy: PartitionFetchPolicy,
+    redaction: RedactionPolicy,
+    row_filter: Arc<RowFilter>,
+    null_sentinel: Arc<str>,
+    /// Leading child statements of each request the caller did not send, ex. `USE SECONDARY ROLES`.
+    skipped_children: usize,
+}
+
+impl MultipleSnowflakeSQLResponse {
+    /// Resume tracking statements from handles saved with [`MultipleSnowflakeSQLResponse::into_handles`],
+    /// ex. after a restart. Polls each statement once.
+    pub async fn from_handles<H: AsRef<str>>(
+        connector: &SnowflakeConnector,
+        handles: &[H],
+    ) -> Result<MultipleSnowflakeSQLResponse, SnowflakeError> {
+        connector.in_flight.check_open()?;
+        let client = connector.client()?;
+        let mut outcomes = Vec::with_capacity(handles.len());
+        for handle in handles {
+            let outcome = status::fetch_status(&client, &connector.host, handle.as_ref(), &connector.null_sentinel).await?;
+            connector.in_flight.track(&outcome);
+            outcomes.push(outcome);
+        }
+        Ok(MultipleSnowflakeSQLResponse {
+            client,
+            host: connector.host.clone(),
+            outcomes,
+            in_flight: connector.in_flight.clone(),
+            partition_policy: connector.partition_policy.clone(),
+            redaction: connector.redaction.clone(),
+            row_filter: connector.row_filter.clone(),
+            null_sentinel: connector.null_sentinel.clone(),
+            skipped_children: 0,
+        })
+    }
+    /// Statement handles of every request sent, in order, to persist and later
+    /// resume with [`MultipleSnowflakeSQLResponse::from_handles`].
+    pub fn into_handles(self) -> Vec<String> {
+        self.handles()
+            .map(str::to_owned)
+            .collect()
+    }
+    pub fn handles(&self) -> impl Iterator<Item = &str> {
+        self.outcomes.iter().map(StatementOutcome::statement_handle)
+    }
+    /// One outcome per request sent, see [`MultipleSnowflakeSQL`].
+    pub fn outcomes(&self) -> &[StatementOutcome] {
+        &self.outcomes
+    }
+    /// Handles of every child statement, in order, of the requests that succeeded.
+    pub fn child_handles(&self) -> impl Iterator<Item = &str> {
+        self.outcomes.iter()
+            .flat_map(|outcome| outcome.child_handles().iter().skip(self.skipped_children))
+            .map(String::as_str)
+    }
+    /// Result of a single child statement, see [`MultipleSnowflakeSQLResponse::child_handles`].
+    pub async fn fetch_child<T: SnowflakeDeserialize>(&self, handle: &str) -> Result<SnowflakeSQLResult<T>, SnowflakeError> {
+        match status::fetch_status(&self.client, &self.host, handle, &self.null_sentinel).await? {
+            StatementOutcome::Success(response) => {
+                let mut response = partitions::fetch_and_merge_partitions(&self.client, &self.host, *response, &self.partition_policy, &mut ProgressTracker::default()).await?;
+                self.row_filter.apply(&mut response);
+                redact::with_policy(&self.redaction, || response.deserialize())
+                    .map_err(SnowflakeError::SqlResultParse)
+            },
+            StatementOutcome::Failed(status) => Err(SnowflakeError::StatementFailed(Box::new(status))),
+            StatementOutcome::TimedOut(status) => Err(SnowflakeError::StatementTimedOut(Box::new(status))),
+            StatementOutcome::Pending(status) => Err(SnowflakeError::SqlExecution(
+                anyhow::anyhow!("statement {} is still running—{}", status.statement_handle, status.message),
+            )),
+        }
+    }
+    /// Poll every unfinished statement once, returns whether all are finished.
+    pub async fn complete(&mut self) -> Result<bool, SnowflakeError> {
+        let mut complete = true;
+        for outcome in self.outcomes.iter_mut().filter(|outcome| outcome.is_pending()) {
+            *outcome = status::fetch_status(&self.client, &self.host, outcome.statement_handle(), &self.null_sentinel).await?;
+            self.in_flight.track(outcome);
+            complete &= !outcome.is_pending();
+        }
+        Ok(complete)
+    }
+    /// Poll until every statement finished or the deadline passed,
+    /// statements still running at the deadline are left pending.
+    pub async fn wait_all(mut self, deadline: Deadline, backoff: Backoff) -> Result<Vec<StatementOutcome>, SnowflakeError> {
+        let mut attempt = 0;
+        while !self.complete().await? && !deadline.has_passed() {
+            tokio::time::sleep(backoff.delay(attempt).min(deadline.remaining())).await;
+            attempt += 1;
+        }
+        Ok(self.outcomes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockSnowflake;
+
+    #[tokio::test]
+    async fn split_by_warehouse() -> Result<(), anyhow::Error> {
+        let connector = MockSnowflake::start().await.connector();
+        let requests = connector.execute("DB", "WH")
+            .multiple_sql(&[
+                "INSERT INTO A VALUES (?, '?')",
+                "INSERT INTO B VALUES (?) -- ?",
+                "SELECT * FROM C WHERE id IN (?, ?)",
+            ])?
+            .with_statement_warehouse(2, "BIG_WH")
+            .add_binding(1)
+            .add_binding(2)
+            .add_binding(3)
+            .add_binding(4)
+            .into_requests()?;
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].statement.warehouse, "WH");
+        assert_eq!(requests[0].statement.bindings.as_ref().map(HashMap::len), Some(2));
+        assert_eq!(requests[1].statement.warehouse, "BIG_WH");
+        assert_eq!(requests[1].statement.bindings.as_ref().unwrap()["2"].value.as_deref(), Some("4"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn requests_in_order() -> Result<(), anyhow::Error> {
+        use crate::test_util::{MockSnowflake, StatementFixture};
+
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount_pending_then_success(&StatementFixture::new(), 1).await;
+        let connector = snowflake.connector();
+        let response = connector.execute("DB", "WH")
+            .multiple_sql(&["INSERT INTO A VALUES (1)", "SELECT * FROM A"])?
+            .with_statement_warehouse(1, "BIG_WH")
+            .run().await?;
+        assert_eq!(response.outcomes().len(), 2);
+
+        let requests = snowflake.server.received_requests().await.unwrap_or_default();
+        let methods: Vec<_> = requests.iter().map(|request| request.method.as_str()).collect();
+        assert_eq!(methods, ["POST", "GET", "GET", "POST"], "second request waits for the first to succeed");
+        assert!(String::from_utf8_lossy(&requests[3].body).contains("BIG_WH"));
+        Ok(())
+    }
+}
@@ -0,0 +1,118 @@
+//! Bind values to `:name` placeholders, they are rewritten to the positional `:1` placeholders Snowflake accepts.
+//!
+//! ```no_run
+//! # use snowflake_connector::SnowflakeConnector;
+//! # use snowflake_deserializer::named_bindings;
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let users = connector.execute("DB", "WH")
+//!     .sql("SELECT * FROM USERS WHERE ID = :id OR MANAGER_ID = :id AND NAME = :name")?
+//!     .add_named_bindings(named_bindings! { "id" => 1, "name" => "jo" })?
+//!     .select::<(i64, String)>().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use snowflake_deserializer::bindings::NamedBindings;
+
+use crate::{errors::SnowflakeError, SnowflakeSQL};
+
+impl<'a> SnowflakeSQL<'a> {
+    /// Bind `bindings` after any bound with [`add_binding`](Self::add_binding),
+    /// errors if the statement has a `:name` placeholder that is not in `bindings`.
+    ///
+    /// Placeholders in string literals, quoted identifiers and comments are left as is, so are `::` casts.
+    pub fn add_named_bindings(mut self, bindings: NamedBindings) -> Result<SnowflakeSQL<'a>, SnowflakeError> {
+        let offset = self.statement.bindings.as_ref().map_or(0, |bindings| bindings.len());
+        let statement = positional(&self.statement.statement, |name| {
+            bindings.position(name).map(|position| offset + position + 1)
+        })?;
+        self.statement.statement = statement.into();
+        Ok(bindings.into_values().into_iter().fold(self, SnowflakeSQL::add_binding))
+    }
+}
+
+/// `statement` with each `:name` replaced by `:` and the position `position` returns for it.
+fn positional(statement: &str, position: impl Fn(&str) -> Option<usize>) -> Result<String, SnowflakeError> {
+    let mut rewritten = String::with_capacity(statement.len());
+    let mut rest = statement;
+    while let Some(c) = rest.chars().next() {
+        let skipped = match c {
+            '\'' | '"' => quoted(rest, c),
+            '-' if rest.starts_with("--") => rest.find('\n').unwrap_or(rest.len()),
+            '/' if rest.starts_with("/*") => rest.find("*/").map_or(rest.len(), |end| end + 2),
+            '$' if rest.starts_with("$$") => rest[2..].find("$$").map_or(rest.len(), |end| end + 4),
+            ':' if rest.starts_with("::") => 2,
+            ':' => {
+                let name_length = rest[1..]
+                    .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                    .unwrap_or(rest.len() - 1);
+                let name = &rest[1..1 + name_length];
+                if name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+                    let position = position(name).ok_or_else(|| SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                        "no binding for placeholder :{name}",
+                    )))?;
+                    rewritten.push(':');
+                    rewritten.push_str(&position.to_string());
+                    rest = &rest[1 + name_length..];
+                    continue;
+                }
+                1
+            },
+            c => c.len_utf8(),
+        };
+        rewritten.push_str(&rest[..skipped]);
+        rest = &rest[skipped..];
+    }
+    Ok(rewritten)
+}
+
+/// Length of the literal or identifier quoted with `quote` at the start of `text`, doubled quotes escape it.
+pub(crate) fn quoted(text: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' && quote == '\'' {
+            escaped = true;
+        } else if c == quote {
+            if text[i + 1..].starts_with(quote) {
+                escaped = true;
+            } else {
+                return i + 1;
+            }
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use snowflake_deserializer::named_bindings;
+
+    use crate::test_util::MockSnowflake;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn rewritten() -> Result<(), anyhow::Error> {
+        let bindings = named_bindings! { "id" => 1, "name" => None::<String> };
+        let position = |name: &str| bindings.position(name).map(|position| position + 1);
+        assert_eq!(
+            positional("SELECT ':id', \"a:id\", X::TEXT FROM T WHERE ID = :id OR NAME = :name -- :other", position)?,
+            "SELECT ':id', \"a:id\", X::TEXT FROM T WHERE ID = :1 OR NAME = :2 -- :other",
+        );
+        assert_eq!(positional("SELECT 'it''s :id', :1, :id", position)?, "SELECT 'it''s :id', :1, :1");
+        assert!(positional("SELECT :missing", position).is_err());
+
+        let connector = MockSnowflake::start().await.connector();
+        let sql = connector.execute("DB", "WH")
+            .sql("SELECT * FROM T WHERE A = ? AND ID = :id AND NAME = :name")?
+            .add_binding(0)
+            .add_named_bindings(bindings)?;
+        assert_eq!(sql.statement.statement, "SELECT * FROM T WHERE A = ? AND ID = :2 AND NAME = :3");
+        let bindings = sql.statement.bindings.as_ref().unwrap();
+        assert_eq!(bindings["2"].value.as_deref(), Some("1"));
+        assert_eq!(bindings["3"].value, None);
+        Ok(())
+    }
+}
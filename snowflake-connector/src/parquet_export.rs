@@ -0,0 +1,253 @@
+//! Write results as Parquet files, typed by the columns of the result, enabled by the `parquet` feature.
+//!
+//! | Snowflake | Parquet (Arrow) |
+//! |---|---|
+//! | `NUMBER(p,0)`, `p <= 18` | `INT64` |
+//! | other `NUMBER(p,s)` | `DECIMAL(p,s)` |
+//! | `FLOAT` | `DOUBLE` |
+//! | `BOOLEAN` | `BOOLEAN` |
+//! | `DATE` | `DATE` |
+//! | `TIME` | `TIME(NANOS)` |
+//! | `TIMESTAMP_NTZ` | `TIMESTAMP(NANOS)` |
+//! | `TIMESTAMP_LTZ`, `TIMESTAMP_TZ` | `TIMESTAMP(NANOS)` in UTC, the offset is dropped |
+//! | `BINARY` | `BINARY` |
+//! | text, `VARIANT`, `OBJECT`, `ARRAY` and others | `STRING`, semi-structured values as JSON |
+
+use std::{io::Write, sync::Arc};
+
+use anyhow::Context;
+use arrow_array::{
+    builder::{
+        BinaryBuilder, BooleanBuilder, Date32Builder, Decimal128Builder, Float64Builder, Int64Builder, StringBuilder,
+        Time64NanosecondBuilder, TimestampNanosecondBuilder,
+    },
+    ArrayRef, RecordBatch,
+};
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use chrono::{DateTime, FixedOffset, NaiveDateTime, NaiveTime, Timelike};
+use parquet::arrow::ArrowWriter;
+use snowflake_deserializer::{coercion::ColumnType, DeserializeFromStr, RowType};
+
+use crate::{cursor::Cursor, errors::SnowflakeError, SnowflakeSQL};
+
+impl SnowflakeSQL<'_> {
+    /// Write every row to `writer` as a Parquet file, a row group per partition, returns the number of rows.
+    ///
+    /// Partitions are downloaded one at a time, so the result never has to fit in memory.
+    pub async fn write_parquet<W: Write + Send>(self, writer: W) -> Result<usize, SnowflakeError> {
+        self.cursor().await?.write_parquet(writer).await
+    }
+}
+
+impl Cursor {
+    /// Write the rows not handed out yet to `writer` as a Parquet file, returns the number of rows.
+    pub async fn write_parquet<W: Write + Send>(&mut self, writer: W) -> Result<usize, SnowflakeError> {
+        let columns = self.description().to_vec();
+        let schema = schema(&columns);
+        let mut writer = ArrowWriter::try_new(writer, schema.clone(), None)
+            .map_err(|e| SnowflakeError::SqlResultParse(e.into()))?;
+        let mut rows = 0;
+        while let Some(partition) = self.fetch_raw().await? {
+            if partition.is_empty() {
+                continue;
+            }
+            rows += partition.len();
+            let batch = record_batch(&schema, &columns, &partition, self.null_sentinel())
+                .map_err(SnowflakeError::SqlResultParse)?;
+            writer.write(&batch)
+                .and_then(|()| writer.flush())
+                .map_err(|e| SnowflakeError::SqlResultParse(e.into()))?;
+        }
+        writer.close()
+            .map_err(|e| SnowflakeError::SqlResultParse(e.into()))?;
+        Ok(rows)
+    }
+}
+
+fn data_type(column: &RowType) -> DataType {
+    match ColumnType::from(column) {
+        ColumnType::Number { precision, scale: 0 } if precision <= 18 => DataType::Int64,
+        ColumnType::Number { precision, scale } => DataType::Decimal128(precision.clamp(1, 38) as u8, scale as i8),
+        ColumnType::Real => DataType::Float64,
+        ColumnType::Boolean => DataType::Boolean,
+        ColumnType::Date => DataType::Date32,
+        ColumnType::Time { .. } => DataType::Time64(TimeUnit::Nanosecond),
+        ColumnType::TimestampNtz { .. } => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        ColumnType::TimestampLtz { .. } |
+        ColumnType::TimestampTz { .. }
+            => DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into())),
+        ColumnType::Binary => DataType::Binary,
+        ColumnType::Text { .. } |
+        ColumnType::Variant |
+        ColumnType::Object |
+        ColumnType::Array |
+        ColumnType::Other(_)
+            => DataType::Utf8,
+    }
+}
+
+fn schema(columns: &[RowType]) -> SchemaRef {
+    Arc::new(Schema::new(columns.iter()
+        .map(|column| Field::new(&column.name, data_type(column), column.nullable))
+        .collect::<Vec<_>>()))
+}
+
+fn record_batch(schema: &SchemaRef, columns: &[RowType], rows: &[Vec<String>], null_sentinel: &str) -> Result<RecordBatch, anyhow::Error> {
+    let arrays = columns.iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let cells = rows.iter().map(|row| {
+                let cell = row.get(index).map(String::as_str).unwrap_or_default();
+                (!(column.nullable && cell == null_sentinel)).then_some(cell)
+            });
+            array(schema.field(index).data_type(), cells)
+                .with_context(|| format!("column {}", column.name))
+        })
+        .collect::<Result<Vec<ArrayRef>, _>>()?;
+    Ok(RecordBatch::try_new(schema.clone(), arrays)?)
+}
+
+fn array<'c>(data_type: &DataType, cells: impl Iterator<Item = Option<&'c str>>) -> Result<ArrayRef, anyhow::Error> {
+    macro_rules! build {
+        ($builder: expr, $parse: expr) => {{
+            let mut builder = $builder;
+            for cell in cells {
+                builder.append_option(cell.map($parse).transpose()?);
+            }
+            Arc::new(builder.finish()) as ArrayRef
+        }};
+    }
+    Ok(match data_type {
+        DataType::Int64 => build!(Int64Builder::new(), |cell: &str| cell.parse::<i64>()),
+        DataType::Decimal128(precision, scale) => {
+            let scale = *scale;
+            let array = build!(Decimal128Builder::new(), |cell: &str| parse_decimal(cell, scale as u32));
+            let array = array.as_any().downcast_ref::<arrow_array::Decimal128Array>()
+                .expect("built as a decimal array")
+                .clone()
+                .with_precision_and_scale(*precision, scale)?;
+            Arc::new(array)
+        },
+        DataType::Float64 => build!(Float64Builder::new(), |cell: &str| cell.parse::<f64>()),
+        DataType::Boolean => build!(BooleanBuilder::new(), bool::deserialize_from_str),
+        DataType::Date32 => build!(Date32Builder::new(), |cell: &str| cell.parse::<i32>()),
+        DataType::Time64(_) => build!(Time64NanosecondBuilder::new(), |cell: &str| NaiveTime::deserialize_from_str(cell)
+            .map(|time| time.num_seconds_from_midnight() as i64 * 1_000_000_000 + time.nanosecond() as i64)),
+        DataType::Timestamp(_, None) => build!(TimestampNanosecondBuilder::new(), |cell: &str| NaiveDateTime::deserialize_from_str(cell)
+            .and_then(|timestamp| timestamp.and_utc().timestamp_nanos_opt().context("timestamp out of range"))),
+        DataType::Timestamp(_, Some(zone)) => build!(
+            TimestampNanosecondBuilder::new().with_timezone(zone.clone()),
+            |cell: &str| DateTime::<FixedOffset>::deserialize_from_str(cell)
+                .and_then(|timestamp| timestamp.timestamp_nanos_opt().context("timestamp out of range"))
+        ),
+        DataType::Binary => build!(BinaryBuilder::new(), decode_hex),
+        _ => build!(StringBuilder::new(), |cell: &str| Ok::<_, anyhow::Error>(cell)),
+    })
+}
+
+/// `NUMBER` cell as the unscaled integer of a decimal with `scale` digits after the point, ex. `-1.5` at scale 2 is `-150`.
+fn parse_decimal(cell: &str, scale: u32) -> Result<i128, anyhow::Error> {
+    let (negative, digits) = match cell.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, cell),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    let scale = scale as usize;
+    let fraction = if fraction.len() > scale { fraction.trim_end_matches('0') } else { fraction };
+    if fraction.len() > scale {
+        anyhow::bail!("{cell} has more than {scale} decimal places");
+    }
+    let unscaled: i128 = format!("{integer}{fraction:0<scale$}").parse()
+        .with_context(|| format!("{cell} is not a number"))?;
+    Ok(if negative { -unscaled } else { unscaled })
+}
+
+/// `BINARY` cells are sent hex encoded.
+fn decode_hex(cell: &str) -> Result<Vec<u8>, anyhow::Error> {
+    if !cell.len().is_multiple_of(2) {
+        anyhow::bail!("{cell} is not hex encoded");
+    }
+    (0..cell.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&cell[index..index + 2], 16)
+            .with_context(|| format!("{cell} is not hex encoded")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{cast::AsArray, types::{Decimal128Type, TimestampNanosecondType}, Array};
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[test]
+    fn decimals() -> Result<(), anyhow::Error> {
+        assert_eq!(parse_decimal("-1.5", 2)?, -150);
+        assert_eq!(parse_decimal("12", 0)?, 12);
+        assert_eq!(parse_decimal("99999999999999999999999999999999999999", 0)?, 99999999999999999999999999999999999999);
+        assert_eq!(parse_decimal("1.500", 2)?, 150);
+        assert!(parse_decimal("1.234", 2).is_err());
+        assert_eq!(decode_hex("00ff")?, [0, 255]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn write_parquet() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&StatementFixture::new()
+            .column("ID", "fixed")
+            .column("NAME", "text")
+            .row([Some("1"), Some("a")])
+            .row([Some("2"), None])
+            .partition()
+            .row([Some("3"), Some("c")])).await;
+        let path = std::env::temp_dir().join(format!("snowflake-{}.parquet", uuid::Uuid::new_v4()));
+        let rows = snowflake.connector()
+            .execute("DB", "WH")
+            .sql("SELECT ID, NAME FROM T")?
+            .write_parquet(std::fs::File::create(&path)?).await?;
+        assert_eq!(rows, 3);
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path)?)?.build()?;
+        let batches = reader.collect::<Result<Vec<_>, _>>()?;
+        let ids: Vec<i128> = batches.iter().flat_map(|batch| batch.column(0).as_primitive::<Decimal128Type>().values().to_vec()).collect();
+        assert_eq!(ids, [1, 2, 3]);
+        let names: Vec<Option<&str>> = batches.iter().flat_map(|batch| batch.column(1).as_string::<i32>().iter()).collect();
+        assert_eq!(names, [Some("a"), None, Some("c")]);
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn typed_columns() -> Result<(), anyhow::Error> {
+        let column = |name: &str, data_type: &str, precision: Option<u32>, scale: Option<i32>| RowType {
+            name: name.into(),
+            database: "DB".into(),
+            schema: "PUBLIC".into(),
+            table: "T".into(),
+            precision,
+            byte_length: None,
+            data_type: data_type.into(),
+            scale,
+            nullable: true,
+        };
+        let columns = [
+            column("AMOUNT", "fixed", Some(38), Some(2)),
+            column("AT", "timestamp_tz", None, Some(9)),
+        ];
+        let schema = schema(&columns);
+        let batch = record_batch(&schema, &columns, &[
+            vec!["-1.50".into(), "1616173619.000000000 1500".into()],
+            vec!["null".into(), "null".into()],
+        ], snowflake_deserializer::null::DEFAULT_SENTINEL)?;
+        let amounts = batch.column(0).as_primitive::<Decimal128Type>();
+        assert_eq!(amounts.value(0), -150);
+        assert!(amounts.is_null(1));
+        let at = batch.column(1).as_primitive::<TimestampNanosecondType>();
+        assert_eq!(at.value(0), 1_616_173_619_000_000_000);
+        Ok(())
+    }
+}
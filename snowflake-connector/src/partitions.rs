@@ -0,0 +1,143 @@
+//! Downloading the partitions of large results, see [`PartitionFetchPolicy`].
+
+use std::{sync::Arc, time::{Duration, Instant}};
+
+use serde::{de::DeserializeOwned, Deserialize};
+use snowflake_deserializer::{null, SnowflakeSQLResponse};
+
+use crate::{errors::SnowflakeError, json, polling::Backoff, progress::ProgressTracker, SnowflakeConnector, SnowflakeSQL};
+
+/// How partitions are downloaded, set with [`SnowflakeConnector::with_partition_fetch_policy`].
+///
+/// A partition is fetched again after a connection error, a timeout, or a `429` or `5xx` status,
+/// other errors fail right away.
+#[derive(Clone, Debug)]
+pub struct PartitionFetchPolicy {
+    /// Attempts after the first, per partition.
+    pub retries: u32,
+    pub backoff: Backoff,
+    /// Time limit of each attempt, `None` only limits it by the client.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for PartitionFetchPolicy {
+    fn default() -> Self {
+        PartitionFetchPolicy {
+            retries: 3,
+            backoff: Backoff::default(),
+            timeout: None,
+        }
+    }
+}
+
+impl SnowflakeConnector {
+    pub fn with_partition_fetch_policy(mut self, policy: PartitionFetchPolicy) -> Self {
+        self.partition_policy = policy;
+        self
+    }
+}
+
+impl<'a> SnowflakeSQL<'a> {
+    /// Override the connector's [`PartitionFetchPolicy`] for this statement.
+    pub fn with_partition_fetch_policy(mut self, policy: PartitionFetchPolicy) -> SnowflakeSQL<'a> {
+        self.partition_policy = policy;
+        self
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct PartitionResponse {
+    #[serde(deserialize_with = "snowflake_deserializer::null::deserialize_rows")]
+    data: Vec<Vec<String>>,
+}
+
+/// Parse the body as JSON with `NULL` cells as `null_sentinel`, along with the size of the body in bytes.
+pub(crate) async fn read_json<T: DeserializeOwned>(response: reqwest::Response, null_sentinel: &Arc<str>) -> Result<(T, usize), SnowflakeError> {
+    let body = response.bytes().await
+        .map_err(|e| SnowflakeError::SqlExecution(e.into()))?;
+    let value = null::with_sentinel(null_sentinel, || json::from_slice(&body))
+        .map_err(SnowflakeError::SqlResultParse)?;
+    Ok((value, body.len()))
+}
+
+/// Rows of partition `partition` of an already executed statement.
+pub(crate) async fn fetch_partition(
+    client: &reqwest::Client,
+    host: &str,
+    statement_handle: &str,
+    partition: usize,
+    policy: &PartitionFetchPolicy,
+    progress: &mut ProgressTracker,
+    null_sentinel: &Arc<str>,
+) -> Result<Vec<Vec<String>>, SnowflakeError> {
+    let start = Instant::now();
+    let mut attempt = 0;
+    loop {
+        match fetch_partition_once(client, host, statement_handle, partition, policy.timeout, null_sentinel).await {
+            Ok((response, bytes)) => {
+                progress.partition(partition, response.data.len(), bytes, start.elapsed());
+                return Ok(response.data);
+            },
+            Err(Attempt { retryable: true, .. }) if attempt < policy.retries => {
+                tokio::time::sleep(policy.backoff.delay(attempt)).await;
+                attempt += 1;
+            },
+            Err(Attempt { error, .. }) => return Err(error),
+        }
+    }
+}
+
+struct Attempt {
+    error: SnowflakeError,
+    retryable: bool,
+}
+
+async fn fetch_partition_once(
+    client: &reqwest::Client,
+    host: &str,
+    statement_handle: &str,
+    partition: usize,
+    timeout: Option<Duration>,
+    null_sentinel: &Arc<str>,
+) -> Result<(PartitionResponse, usize), Attempt> {
+    let transport = |e: reqwest::Error| Attempt {
+        retryable: e.is_timeout() || e.is_connect() || e.is_request() || e.is_body()
+            || e.status().is_some_and(|status| status.is_server_error() || status.as_u16() == 429),
+        error: SnowflakeError::SqlExecution(e.into()),
+    };
+    let mut request = client
+        .get(format!("{host}statements/{statement_handle}"))
+        .query(&[("partition", partition)]);
+    if let Some(timeout) = timeout {
+        request = request.timeout(timeout);
+    }
+    let response = request
+        .send().await
+        .map_err(transport)?
+        .error_for_status()
+        .map_err(transport)?;
+    let body = response.bytes().await
+        .map_err(transport)?;
+    let value = null::with_sentinel(null_sentinel, || json::from_slice(&body))
+        .map_err(|e| Attempt { error: SnowflakeError::SqlResultParse(e), retryable: false })?;
+    Ok((value, body.len()))
+}
+
+/// Append the rows of every partition after the first to the response.
+pub(crate) async fn fetch_and_merge_partitions(
+    client: &reqwest::Client,
+    host: &str,
+    mut response: SnowflakeSQLResponse,
+    policy: &PartitionFetchPolicy,
+    progress: &mut ProgressTracker,
+) -> Result<SnowflakeSQLResponse, SnowflakeError> {
+    let partitions = &response.result_set_meta_data.partition_info;
+    let partition_count = partitions.len();
+    let remaining_rows: usize = partitions.iter().skip(1).map(|partition| partition.row_count).sum();
+    response.data.reserve_exact(remaining_rows);
+    for partition in 1..partition_count {
+        let data = fetch_partition(client, host, &response.statement_handle, partition, policy, progress, &response.null_sentinel).await?;
+        response.data.extend(data);
+    }
+    Ok(response)
+}
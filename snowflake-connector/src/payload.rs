@@ -0,0 +1,165 @@
+//! Customize the JSON body statements are sent with, ex. to pass fields or parameters
+//! Snowflake added that the crate does not know about yet.
+//!
+//! ```no_run
+//! # async fn example(connector: snowflake_connector::SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let response = connector.execute("DB", "WH")
+//!     .sql("SELECT * FROM TEST_TABLE")?
+//!     .with_extra_field("describeOnly", true)
+//!     .text().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{fmt, sync::Arc};
+
+use serde::{ser::Error, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::{errors::SnowflakeError, multiple::MultipleSnowflakeSQL, secondary_roles, SnowflakeExecutorSQLJSON, SnowflakeSQL};
+
+/// Value of bindings in a redacted [`SnowflakeSQL::to_request_json`].
+const REDACTED: &str = "***";
+
+/// Changes the body of a statement right before it is sent.
+///
+/// Implemented for closures taking the body as a JSON object.
+pub trait StatementSerializer: Send + Sync {
+    fn serialize(&self, body: &mut Map<String, Value>);
+}
+
+impl<F: Fn(&mut Map<String, Value>) + Send + Sync> StatementSerializer for F {
+    fn serialize(&self, body: &mut Map<String, Value>) {
+        self(body)
+    }
+}
+
+/// Extra fields and serializers of a statement, applied in order.
+#[derive(Clone, Default)]
+pub(crate) struct Payload {
+    extra_fields: Map<String, Value>,
+    serializers: Vec<Arc<dyn StatementSerializer>>,
+}
+
+impl fmt::Debug for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Payload")
+            .field("extra_fields", &self.extra_fields)
+            .field("serializers", &self.serializers.len())
+            .finish()
+    }
+}
+
+impl Payload {
+    fn is_empty(&self) -> bool {
+        self.extra_fields.is_empty() && self.serializers.is_empty()
+    }
+}
+
+/// Body of a statement, the fields the crate sets followed by the [`Payload`].
+pub(crate) struct StatementBody<'b, 'a>(pub(crate) &'b SnowflakeExecutorSQLJSON<'a>);
+
+impl Serialize for StatementBody<'_, '_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Some(roles) = &self.0.secondary_roles {
+            return StatementBody(&secondary_roles::prefixed(self.0, roles)).serialize(serializer);
+        }
+        let payload = &self.0.payload;
+        if payload.is_empty() {
+            return self.0.serialize(serializer);
+        }
+        let mut body = match serde_json::to_value(self.0).map_err(S::Error::custom)? {
+            Value::Object(body) => body,
+            _ => return Err(S::Error::custom("statement did not serialize to a JSON object")),
+        };
+        body.extend(payload.extra_fields.clone());
+        for statement_serializer in &payload.serializers {
+            statement_serializer.serialize(&mut body);
+        }
+        body.serialize(serializer)
+    }
+}
+
+impl<'a> SnowflakeSQL<'a> {
+    /// Add `key` to the body of the statement, replacing the field the crate sets if it has the same name.
+    pub fn with_extra_field<V: Into<Value>>(mut self, key: impl Into<String>, value: V) -> SnowflakeSQL<'a> {
+        self.statement.payload.extra_fields.insert(key.into(), value.into());
+        self
+    }
+    /// Change the body of the statement with `serializer`, after the extra fields were added.
+    pub fn with_serializer<S: StatementSerializer + 'static>(mut self, serializer: S) -> SnowflakeSQL<'a> {
+        self.statement.payload.serializers.push(Arc::new(serializer));
+        self
+    }
+    /// Body the statement will be posted with, ex. to show reviewers what will hit Snowflake before it runs.
+    ///
+    /// With `redact_bindings` the values of bindings are replaced by `***`, their types are kept.
+    pub fn to_request_json(&self, redact_bindings: bool) -> Result<String, SnowflakeError> {
+        let to_string = |statement: &SnowflakeExecutorSQLJSON<'_>| serde_json::to_string(&StatementBody(statement))
+            .map_err(|e| SnowflakeError::InvalidStatement(e.into()));
+        if !redact_bindings {
+            return to_string(&self.statement);
+        }
+        let mut statement = self.statement.clone();
+        for binding in statement.bindings.iter_mut().flat_map(|bindings| bindings.values_mut()) {
+            if binding.value.is_some() {
+                binding.value = Some(REDACTED.into());
+            }
+        }
+        to_string(&statement)
+    }
+}
+
+impl<'a> MultipleSnowflakeSQL<'a> {
+    /// See [`SnowflakeSQL::with_extra_field`], added to every request.
+    pub fn with_extra_field<V: Into<Value>>(self, key: impl Into<String>, value: V) -> MultipleSnowflakeSQL<'a> {
+        self.map_sql(|sql| sql.with_extra_field(key, value))
+    }
+    /// See [`SnowflakeSQL::with_serializer`], applied to every request.
+    pub fn with_serializer<S: StatementSerializer + 'static>(self, serializer: S) -> MultipleSnowflakeSQL<'a> {
+        self.map_sql(|sql| sql.with_serializer(serializer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::test_util::MockSnowflake;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn extra_fields() -> Result<(), anyhow::Error> {
+        let connector = MockSnowflake::start().await.connector();
+        let sql = connector.execute("DB", "WH")
+            .sql("SELECT 1")?
+            .with_extra_field("describeOnly", true)
+            .with_extra_field("warehouse", "BIG_WH")
+            .with_serializer(|body: &mut Map<String, Value>| {
+                body.remove("timeout");
+            });
+        let body = serde_json::to_value(StatementBody(&sql.statement))?;
+        assert_eq!(body["describeOnly"], json!(true));
+        assert_eq!(body["warehouse"], json!("BIG_WH"));
+        assert_eq!(body["statement"], json!("SELECT 1"));
+        assert!(body.get("timeout").is_none());
+        assert!(body.get("payload").is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn request_json() -> Result<(), anyhow::Error> {
+        let connector = MockSnowflake::start().await.connector();
+        let sql = connector.execute("DB", "WH")
+            .sql("SELECT * FROM USERS WHERE EMAIL = ?")?
+            .add_binding("jo@example.com");
+        let body = sql.to_request_json(false)?;
+        assert_eq!(body, serde_json::to_string(&StatementBody(&sql.statement))?);
+        assert!(body.contains("jo@example.com"));
+        let redacted: Value = serde_json::from_str(&sql.to_request_json(true)?)?;
+        assert_eq!(redacted["bindings"]["1"], json!({ "type": "TEXT", "value": "***" }));
+        assert_eq!(redacted["statement"], json!("SELECT * FROM USERS WHERE EMAIL = ?"));
+        Ok(())
+    }
+}
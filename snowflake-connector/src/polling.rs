@@ -0,0 +1,78 @@
+use std::time::{Duration, Instant};
+
+/// How long statements without a timeout are polled before giving up, see [`SnowflakeSQL::with_poll_timeout`](crate::SnowflakeSQL::with_poll_timeout).
+pub const DEFAULT_POLL_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
+/// Point in time after which polling gives up.
+#[derive(Clone, Copy, Debug)]
+pub struct Deadline(Instant);
+
+impl Deadline {
+    pub fn at(instant: Instant) -> Self {
+        Deadline(instant)
+    }
+    pub fn after(duration: Duration) -> Self {
+        Deadline(Instant::now() + duration)
+    }
+    pub fn remaining(&self) -> Duration {
+        self.0.saturating_duration_since(Instant::now())
+    }
+    pub fn has_passed(&self) -> bool {
+        self.remaining().is_zero()
+    }
+}
+
+/// Exponential backoff with full jitter between polls.
+#[derive(Clone, Debug)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub multiplier: f64,
+    /// Randomize each delay between zero and the computed value,
+    /// so many waiting tasks do not poll in lockstep.
+    pub jitter: bool,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            initial: Duration::from_millis(250),
+            max: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: true,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before poll number `attempt`, starting at zero.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.min(i32::MAX as u32) as i32);
+        let delay = Duration::try_from_secs_f64(self.initial.as_secs_f64() * factor)
+            .unwrap_or(self.max)
+            .min(self.max);
+        if self.jitter {
+            delay.mul_f64(fastrand::f64())
+        } else {
+            delay
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_delay() {
+        let backoff = Backoff {
+            jitter: false,
+            ..Default::default()
+        };
+        assert_eq!(backoff.delay(0), Duration::from_millis(250));
+        assert_eq!(backoff.delay(2), Duration::from_secs(1));
+        assert_eq!(backoff.delay(100), Duration::from_secs(10));
+        let backoff = Backoff::default();
+        assert!(backoff.delay(3) <= Duration::from_secs(2));
+    }
+}
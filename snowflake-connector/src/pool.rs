@@ -0,0 +1,108 @@
+//! Connection pool and HTTP/2 settings of the HTTP client, shared by every statement of a connector.
+
+use std::time::Duration;
+
+use reqwest::ClientBuilder;
+
+use crate::SnowflakeConnector;
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PoolConfig {
+    max_idle_per_host: Option<usize>,
+    idle_timeout: Option<Option<Duration>>,
+    tcp_keepalive: Option<Duration>,
+    http2_keep_alive_interval: Option<Duration>,
+    http2_keep_alive_timeout: Option<Duration>,
+    http2_keep_alive_while_idle: bool,
+    http2_prior_knowledge: bool,
+}
+
+impl PoolConfig {
+    pub(crate) fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(max) = self.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        if let Some(interval) = self.http2_keep_alive_interval {
+            builder = builder.http2_keep_alive_interval(interval);
+        }
+        if let Some(timeout) = self.http2_keep_alive_timeout {
+            builder = builder.http2_keep_alive_timeout(timeout);
+        }
+        if self.http2_keep_alive_while_idle {
+            builder = builder.http2_keep_alive_while_idle(true);
+        }
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        builder
+    }
+}
+
+impl SnowflakeConnector {
+    /// Keep at most `max` idle connections to the account open, reqwest keeps any number by default.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool.max_idle_per_host = Some(max);
+        self.reset_client();
+        self
+    }
+    /// Close connections idle for longer than `timeout`, `None` keeps them open, reqwest closes them after 90 seconds by default.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool.idle_timeout = Some(timeout);
+        self.reset_client();
+        self
+    }
+    /// Send TCP keepalive probes every `interval`, ex. so load balancers do not drop idle connections.
+    pub fn tcp_keepalive(mut self, interval: Duration) -> Self {
+        self.pool.tcp_keepalive = Some(interval);
+        self.reset_client();
+        self
+    }
+    /// Ping HTTP/2 connections every `interval`, and close them if a ping is not answered within `timeout`.
+    pub fn http2_keep_alive(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.pool.http2_keep_alive_interval = Some(interval);
+        self.pool.http2_keep_alive_timeout = Some(timeout);
+        self.reset_client();
+        self
+    }
+    /// Also ping HTTP/2 connections without requests in flight, see [`http2_keep_alive`](Self::http2_keep_alive).
+    pub fn http2_keep_alive_while_idle(mut self, enabled: bool) -> Self {
+        self.pool.http2_keep_alive_while_idle = enabled;
+        self.reset_client();
+        self
+    }
+    /// Only speak HTTP/2, without negotiating it first.
+    pub fn http2_prior_knowledge(mut self) -> Self {
+        self.pool.http2_prior_knowledge = true;
+        self.reset_client();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::MockSnowflake;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn shared_client() -> Result<(), anyhow::Error> {
+        let connector = MockSnowflake::start().await.connector();
+        connector.execute("DB", "WH").sql("SELECT 1")?;
+        assert!(connector.client.get().is_some());
+        let connector = connector
+            .pool_max_idle_per_host(4)
+            .pool_idle_timeout(Some(Duration::from_secs(30)))
+            .http2_keep_alive(Duration::from_secs(10), Duration::from_secs(5))
+            .http2_keep_alive_while_idle(true);
+        assert!(connector.client.get().is_none());
+        connector.execute("DB", "WH").sql("SELECT 1")?;
+        assert!(connector.client.get().is_some());
+        Ok(())
+    }
+}
@@ -0,0 +1,127 @@
+//! Named settings of statements defined once on the connector, ex. a `batch` profile with a long timeout
+//! on a bigger warehouse, instead of repeating them at every call site.
+//!
+//! ```no_run
+//! # use snowflake_connector::{profiles::StatementProfile, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let connector = connector.with_profile("batch", StatementProfile::new()
+//!     .with_warehouse("EXTRACT_WH")
+//!     .with_timeout(3600)
+//!     .with_query_tag("nightly-extract"));
+//! let response = connector.execute("DB", "WH")
+//!     .sql("SELECT * FROM EVENTS")?
+//!     .with_profile("batch")?
+//!     .text().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{errors::SnowflakeError, multiple::MultipleSnowflakeSQL, SnowflakeConnector, SnowflakeSQL};
+
+/// Settings a statement takes from a profile, those that are `None` keep the statement's.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StatementProfile {
+    pub timeout: Option<u32>,
+    pub warehouse: Option<String>,
+    pub role: Option<String>,
+    pub query_tag: Option<String>,
+    /// Session parameters, ex. `TIMEZONE`, added to those of the statement.
+    pub parameters: HashMap<String, String>,
+}
+
+impl StatementProfile {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_timeout(mut self, timeout: u32) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+    pub fn with_warehouse(mut self, warehouse: impl Into<String>) -> Self {
+        self.warehouse = Some(warehouse.into());
+        self
+    }
+    pub fn with_role(mut self, role: impl Into<String>) -> Self {
+        self.role = Some(role.into());
+        self
+    }
+    pub fn with_query_tag(mut self, query_tag: impl Into<String>) -> Self {
+        self.query_tag = Some(query_tag.into());
+        self
+    }
+    pub fn with_parameter(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.parameters.insert(name.into(), value.into());
+        self
+    }
+}
+
+impl SnowflakeConnector {
+    /// Add `profile` under `name`, replacing a profile of the same name, see [`SnowflakeSQL::with_profile`].
+    pub fn with_profile(mut self, name: impl Into<String>, profile: StatementProfile) -> Self {
+        Arc::make_mut(&mut self.profiles).insert(name.into(), profile);
+        self
+    }
+}
+
+impl<'a> SnowflakeSQL<'a> {
+    /// Apply the connector's profile `name`, settings given afterwards override those of the profile.
+    ///
+    /// Errors if the connector has no such profile.
+    pub fn with_profile(mut self, name: &str) -> Result<SnowflakeSQL<'a>, SnowflakeError> {
+        let profiles = self.profiles.clone();
+        let profile = profiles.get(name)
+            .ok_or_else(|| SnowflakeError::InvalidStatement(anyhow::anyhow!("no statement profile named {name:?}")))?;
+        if let Some(timeout) = profile.timeout {
+            self = self.with_timeout(timeout);
+        }
+        if let Some(warehouse) = &profile.warehouse {
+            self.statement.warehouse = warehouse.clone();
+        }
+        if let Some(role) = &profile.role {
+            self = self.with_role(role);
+        }
+        if let Some(query_tag) = &profile.query_tag {
+            self = self.with_query_tag(query_tag);
+        }
+        Ok(profile.parameters.iter().fold(self, |sql, (name, value)| sql.with_parameter(name, value)))
+    }
+}
+
+impl<'a> MultipleSnowflakeSQL<'a> {
+    /// See [`SnowflakeSQL::with_profile`], statements given their own warehouse or role keep it.
+    pub fn with_profile(self, name: &str) -> Result<MultipleSnowflakeSQL<'a>, SnowflakeError> {
+        self.try_map_sql(|sql| sql.with_profile(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::MockSnowflake;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn profiles() -> Result<(), anyhow::Error> {
+        let connector = MockSnowflake::start().await.connector().with_profile("batch", StatementProfile::new()
+            .with_warehouse("EXTRACT_WH")
+            .with_role("EXTRACT_ROLE")
+            .with_timeout(3600)
+            .with_query_tag("nightly")
+            .with_parameter("TIMEZONE", "UTC"));
+        let sql = connector.execute("DB", "WH").sql("SELECT 1")?
+            .with_profile("batch")?
+            .with_role("ANALYST");
+        assert_eq!(sql.statement.warehouse, "EXTRACT_WH");
+        assert_eq!(sql.statement.role.as_deref(), Some("ANALYST"));
+        assert_eq!(sql.statement.timeout, Some(3600));
+        let parameters = sql.statement.parameters.as_ref().expect("parameters");
+        assert_eq!((parameters["QUERY_TAG"].as_str(), parameters["TIMEZONE"].as_str()), ("nightly", "UTC"));
+
+        let sql = connector.execute("DB", "WH").sql("SELECT 1")?;
+        assert!(sql.with_profile("interactive").is_err());
+        assert!(connector.execute("DB", "WH").multiple_sql(&["SELECT 1", "SELECT 2"])?.with_profile("batch").is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+//! Samples of tables and statistics of their columns, ex. for data quality checks.
+//!
+//! Table names are inserted as is, do not pass user input.
+//!
+//! ```no_run
+//! # use snowflake_connector::{profiling::Sample, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let orders: Vec<(u64, String)> = connector.execute("DB", "WH")
+//!     .sample("PUBLIC.ORDERS", Sample::Rows(100)).await?;
+//! for column in connector.execute("DB", "WH").profile("PUBLIC.ORDERS").await? {
+//!     println!("{}: {:.1}% null, {:?} to {:?}", column.name, column.null_rate() * 100.0, column.min, column.max);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::borrow::Cow;
+
+use snowflake_deserializer::SnowflakeDeserialize;
+
+use crate::{compat::Row, errors::SnowflakeError, escape::escape_identifier, SnowflakeExecutor};
+
+/// Rows a [`sample`](SnowflakeExecutor::sample) returns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Sample {
+    /// This many rows, or every row of smaller tables.
+    Rows(u64),
+    /// Each row with this probability in percent, from `0` to `100`.
+    Percent(f64),
+}
+
+impl Sample {
+    /// `SAMPLE (...)` clause.
+    pub fn to_sql(&self) -> Result<String, SnowflakeError> {
+        match *self {
+            Sample::Rows(rows) => Ok(format!("SAMPLE ({rows} ROWS)")),
+            Sample::Percent(percent) if (0.0..=100.0).contains(&percent) => Ok(format!("SAMPLE BERNOULLI ({percent})")),
+            Sample::Percent(percent) => Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                "sample of {percent}% is not between 0% and 100%",
+            ))),
+        }
+    }
+}
+
+/// Statistics of a column computed by [`profile`](SnowflakeExecutor::profile).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnProfile {
+    pub name: String,
+    pub data_type: String,
+    /// Smallest value as text, `None` if every value is `NULL` or the type has no order, ex. `VARIANT`.
+    pub min: Option<String>,
+    /// Largest value as text, see [`min`](Self::min).
+    pub max: Option<String>,
+    pub nulls: u64,
+    pub rows: u64,
+}
+
+impl ColumnProfile {
+    /// Share of `NULL` values, `0` for empty tables.
+    pub fn null_rate(&self) -> f64 {
+        match self.rows {
+            0 => 0.0,
+            rows => self.nulls as f64 / rows as f64,
+        }
+    }
+}
+
+impl<'a> SnowflakeExecutor<'a> {
+    /// `SELECT * FROM <table> SAMPLE (...)`.
+    pub async fn sample<T: SnowflakeDeserialize>(self, table: &str, sample: Sample) -> Result<Vec<T>, SnowflakeError> {
+        let statement = format!("SELECT * FROM {table} {}", sample.to_sql()?);
+        Ok(self.statement(Cow::Owned(statement))?.select::<T>().await?.data)
+    }
+    /// Minimum, maximum and number of `NULL` values of every column of `table`, in one scan of it.
+    ///
+    /// `table` must be qualified with its schema, see [`schema_drift`](Self::schema_drift).
+    pub async fn profile(self, table: &str) -> Result<Vec<ColumnProfile>, SnowflakeError> {
+        let columns = self.live_columns(table).await?;
+        if columns.is_empty() {
+            return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                "table {table} does not exist or the role can not see it",
+            )));
+        }
+        let mut select = vec!["COUNT(*)".to_owned()];
+        for column in &columns {
+            let name = escape_identifier(&column.name);
+            match is_ordered(&column.data_type) {
+                true => select.push(format!("MIN({name})::VARCHAR, MAX({name})::VARCHAR")),
+                false => select.push("NULL, NULL".to_owned()),
+            }
+            select.push(format!("COUNT_IF({name} IS NULL)"));
+        }
+        let statement = format!("SELECT {} FROM {table}", select.join(", "));
+        let row = self.statement(Cow::Owned(statement))?.select_one::<Row>().await?;
+        let rows = row.try_get::<u64, _>(0).map_err(SnowflakeError::SqlResultParse)?;
+        columns.into_iter()
+            .enumerate()
+            .map(|(i, column)| Ok(ColumnProfile {
+                min: row.try_get(1 + 3 * i).map_err(SnowflakeError::SqlResultParse)?,
+                max: row.try_get(2 + 3 * i).map_err(SnowflakeError::SqlResultParse)?,
+                nulls: row.try_get(3 + 3 * i).map_err(SnowflakeError::SqlResultParse)?,
+                rows,
+                name: column.name,
+                data_type: column.data_type,
+            }))
+            .collect()
+    }
+}
+
+/// Whether `MIN` and `MAX` accept values of `data_type`.
+fn is_ordered(data_type: &str) -> bool {
+    let base = data_type.split('(').next().unwrap_or_default().trim();
+    !["VARIANT", "OBJECT", "ARRAY", "GEOGRAPHY", "GEOMETRY", "VECTOR", "MAP"]
+        .iter()
+        .any(|unordered| base.eq_ignore_ascii_case(unordered))
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{matchers::{body_string_contains, method}, Mock};
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn profile() -> Result<(), anyhow::Error> {
+        assert_eq!(Sample::Rows(10).to_sql()?, "SAMPLE (10 ROWS)");
+        assert_eq!(Sample::Percent(2.5).to_sql()?, "SAMPLE BERNOULLI (2.5)");
+        assert!(Sample::Percent(120.0).to_sql().is_err());
+
+        let snowflake = MockSnowflake::start().await;
+        let columns = StatementFixture::new()
+            .column("COLUMN_NAME", "text")
+            .column("DATA_TYPE", "text")
+            .column("IS_NULLABLE", "text")
+            .column("NUMERIC_PRECISION", "fixed")
+            .column("NUMERIC_SCALE", "fixed")
+            .column("CHARACTER_MAXIMUM_LENGTH", "fixed")
+            .column("COMMENT", "text")
+            .row([Some("ID"), Some("NUMBER"), Some("NO"), Some("38"), Some("0"), None, None])
+            .row([Some("payload"), Some("VARIANT"), Some("YES"), None, None, None, None]);
+        Mock::given(method("POST"))
+            .and(body_string_contains("INFORMATION_SCHEMA.COLUMNS"))
+            .respond_with(columns.success())
+            .with_priority(1)
+            .mount(&snowflake.server).await;
+        snowflake.mount(&StatementFixture::new()
+            .column("COUNT(*)", "fixed")
+            .column("MIN", "text")
+            .column("MAX", "text")
+            .column("NULLS", "fixed")
+            .column("MIN", "text")
+            .column("MAX", "text")
+            .column("NULLS", "fixed")
+            .row([Some("4"), Some("1"), Some("9"), Some("0"), None, None, Some("1")])).await;
+        let connector = snowflake.connector();
+        let profile = connector.execute("DB", "WH").profile("PUBLIC.ORDERS").await?;
+        assert_eq!(profile[0], ColumnProfile {
+            name: "ID".into(),
+            data_type: "NUMBER(38,0)".into(),
+            min: Some("1".into()),
+            max: Some("9".into()),
+            nulls: 0,
+            rows: 4,
+        });
+        assert_eq!((profile[1].min.as_deref(), profile[1].null_rate()), (None, 0.25));
+
+        let requests = snowflake.server.received_requests().await.unwrap_or_default();
+        let statement = String::from_utf8_lossy(&requests.last().expect("profile statement").body).into_owned();
+        assert!(statement.contains(r#"MIN(ID)::VARCHAR, MAX(ID)::VARCHAR, COUNT_IF(ID IS NULL), NULL, NULL, COUNT_IF(\"payload\" IS NULL) FROM PUBLIC.ORDERS"#), "{statement}");
+        Ok(())
+    }
+}
@@ -0,0 +1,182 @@
+//! Progress reporting for long fetches, see [`SnowflakeSQL::with_progress`](crate::SnowflakeSQL::with_progress),
+//! and the sizes of results, see [`SnowflakeSQL::select_with_stats`](crate::SnowflakeSQL::select_with_stats).
+
+use std::{fmt::Debug, sync::Arc, time::Duration};
+
+use snowflake_deserializer::{PartitionInfo, SnowflakeSQLResponse};
+
+/// Snapshot of a fetch, reported after the first response, after every partition and after deserialization.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub partitions_fetched: usize,
+    pub partitions_total: usize,
+    /// Rows downloaded so far.
+    pub rows_fetched: usize,
+    pub rows_total: usize,
+    pub rows_deserialized: usize,
+    /// Size of the decompressed response bodies downloaded so far.
+    pub bytes_downloaded: u64,
+}
+
+/// Size of a result and what downloading it took, ex. to charge teams for their results or alert on bloated ones.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResultStats {
+    /// One per partition of the result, in order.
+    pub partitions: Vec<PartitionStats>,
+}
+
+impl ResultStats {
+    pub fn rows(&self) -> usize {
+        self.partitions.iter().map(|partition| partition.info.row_count).sum()
+    }
+    /// Size of the rows as Snowflake reports it, uncompressed.
+    pub fn uncompressed_size(&self) -> usize {
+        self.partitions.iter().map(|partition| partition.info.uncompressed_size).sum()
+    }
+    /// Size of the response bodies downloaded so far.
+    pub fn bytes_downloaded(&self) -> u64 {
+        self.partitions.iter().filter_map(|partition| partition.fetch).map(|fetch| fetch.bytes).sum()
+    }
+    /// Time spent downloading the partitions fetched so far, including executing the statement.
+    pub fn elapsed(&self) -> Duration {
+        self.partitions.iter().filter_map(|partition| partition.fetch).map(|fetch| fetch.elapsed).sum()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartitionStats {
+    /// Rows and sizes of the partition, from the `partitionInfo` of the response.
+    pub info: PartitionInfo,
+    /// `None` until the partition was downloaded, ex. partitions a [`Cursor`](crate::cursor::Cursor) did not reach.
+    pub fetch: Option<PartitionFetch>,
+}
+
+/// Download of a partition.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PartitionFetch {
+    /// Size of the decompressed response body.
+    pub bytes: u64,
+    /// Including retries, and for the first partition executing the statement and polling until it finished.
+    pub elapsed: Duration,
+}
+
+/// Accumulates [`Progress`] and hands it to the callback, if any, along with the [`ResultStats`].
+#[derive(Clone, Default)]
+pub(crate) struct ProgressTracker {
+    callback: Option<Arc<dyn Fn(Progress) + Send + Sync>>,
+    progress: Progress,
+    stats: ResultStats,
+}
+
+impl Debug for ProgressTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProgressTracker")
+            .field("callback", &self.callback.is_some())
+            .field("progress", &self.progress)
+            .finish()
+    }
+}
+
+impl ProgressTracker {
+    pub(crate) fn set_callback<F: Fn(Progress) + Send + Sync + 'static>(&mut self, callback: F) {
+        self.callback = Some(Arc::new(callback));
+    }
+    /// First partition, part of the statement response.
+    pub(crate) fn response(&mut self, response: &SnowflakeSQLResponse, bytes: usize, elapsed: Duration) {
+        let meta_data = &response.result_set_meta_data;
+        let mut partitions = meta_data.partition_info.clone();
+        if partitions.is_empty() {
+            partitions.push(PartitionInfo { row_count: response.data.len(), uncompressed_size: 0, compressed_size: None });
+        }
+        self.stats.partitions = partitions.into_iter()
+            .map(|info| PartitionStats { info, fetch: None })
+            .collect();
+        self.stats.partitions[0].fetch = Some(PartitionFetch { bytes: bytes as u64, elapsed });
+        self.progress = Progress {
+            partitions_fetched: 1,
+            partitions_total: meta_data.partition_info.len().max(1),
+            rows_fetched: response.data.len(),
+            rows_total: meta_data.num_rows,
+            rows_deserialized: 0,
+            bytes_downloaded: bytes as u64,
+        };
+        self.report();
+    }
+    pub(crate) fn partition(&mut self, partition: usize, rows: usize, bytes: usize, elapsed: Duration) {
+        if let Some(stats) = self.stats.partitions.get_mut(partition) {
+            stats.fetch = Some(PartitionFetch { bytes: bytes as u64, elapsed });
+        }
+        self.progress.partitions_fetched += 1;
+        self.progress.rows_fetched += rows;
+        self.progress.bytes_downloaded += bytes as u64;
+        self.report();
+    }
+    pub(crate) fn deserialized(&mut self, rows: usize) {
+        self.progress.rows_deserialized += rows;
+        self.report();
+    }
+    pub(crate) fn stats(&self) -> &ResultStats {
+        &self.stats
+    }
+    fn report(&self) {
+        if let Some(callback) = &self.callback {
+            callback(self.progress);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn tracker_reports_progress() {
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let mut tracker = ProgressTracker::default();
+        tracker.set_callback({
+            let reports = reports.clone();
+            move |progress| reports.lock().unwrap().push(progress)
+        });
+        tracker.partition(1, 10, 100, Duration::from_millis(20));
+        tracker.partition(2, 5, 50, Duration::from_millis(10));
+        tracker.deserialized(15);
+        let reports = reports.lock().unwrap();
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[2], Progress {
+            partitions_fetched: 2,
+            partitions_total: 0,
+            rows_fetched: 15,
+            rows_total: 0,
+            rows_deserialized: 15,
+            bytes_downloaded: 150,
+        });
+    }
+
+    #[tokio::test]
+    async fn result_stats() -> Result<(), anyhow::Error> {
+        use crate::test_util::{MockSnowflake, StatementFixture};
+
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&StatementFixture::new()
+            .column("ID", "fixed")
+            .row([Some("1")])
+            .row([Some("2")])
+            .partition()
+            .row([Some("3")])).await;
+        let connector = snowflake.connector();
+        let (result, stats) = connector.execute("DB", "WH")
+            .sql("SELECT ID FROM T")?
+            .select_with_stats::<(u32,)>().await?;
+        assert_eq!(result.data.len(), 3);
+        assert_eq!(stats.partitions.len(), 2);
+        assert_eq!((stats.rows(), stats.partitions[1].info.row_count), (3, 1));
+        assert!(stats.partitions.iter().all(|partition| partition.fetch.is_some_and(|fetch| fetch.bytes > 0)));
+        assert_eq!(stats.bytes_downloaded(), stats.partitions.iter().map(|partition| partition.fetch.unwrap().bytes).sum::<u64>());
+
+        let cursor = connector.execute("DB", "WH").sql("SELECT ID FROM T")?.cursor().await?;
+        assert!(cursor.stats().partitions[1].fetch.is_none());
+        Ok(())
+    }
+}
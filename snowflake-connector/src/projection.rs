@@ -0,0 +1,63 @@
+//! Request only the columns a type needs from `SELECT *` statements, ex. to transfer less of wide tables.
+
+use std::borrow::Cow;
+
+use snowflake_deserializer::{table::SnowflakeTable, SnowflakeDeserialize, SnowflakeSQLResult};
+
+use crate::{errors::SnowflakeError, SnowflakeSQL};
+
+impl SnowflakeSQL<'_> {
+    /// Replace the `*` of a statement starting with `SELECT *` or `SELECT DISTINCT *` by `columns`, then [`select`](Self::select).
+    pub async fn select_columns<T: SnowflakeDeserialize>(mut self, columns: &[&str]) -> Result<SnowflakeSQLResult<T>, SnowflakeError> {
+        self.statement.statement = Cow::Owned(project(&self.statement.statement, columns)?);
+        self.select().await
+    }
+    /// [`select_columns`](Self::select_columns) with the [`SnowflakeTable::COLUMNS`] of `T`.
+    pub async fn select_projected<T: SnowflakeDeserialize + SnowflakeTable>(self) -> Result<SnowflakeSQLResult<T>, SnowflakeError> {
+        let columns: Vec<&str> = T::COLUMNS.iter().map(|column| column.name).collect();
+        self.select_columns(&columns).await
+    }
+}
+
+/// `statement` with its leading `SELECT *` replaced by `SELECT columns`.
+fn project(statement: &str, columns: &[&str]) -> Result<String, SnowflakeError> {
+    let invalid = |message: &str| SnowflakeError::InvalidStatement(anyhow::anyhow!("{message}"));
+    if columns.is_empty() {
+        return Err(invalid("no columns to select"));
+    }
+    let trimmed = statement.trim_start();
+    let rest = strip_keyword(trimmed, "SELECT")
+        .ok_or_else(|| invalid("only statements starting with SELECT * can be projected"))?;
+    let (distinct, rest) = match strip_keyword(rest, "DISTINCT") {
+        Some(rest) => ("DISTINCT ", rest),
+        None => ("", rest),
+    };
+    let rest = rest.strip_prefix('*')
+        .filter(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+        .ok_or_else(|| invalid("only statements starting with SELECT * can be projected"))?;
+    Ok(format!("SELECT {distinct}{}{rest}", columns.join(", ")))
+}
+
+/// Rest after `keyword` and the whitespace following it, `None` unless the text starts with it as a whole word.
+fn strip_keyword<'s>(text: &'s str, keyword: &str) -> Option<&'s str> {
+    let head = text.get(..keyword.len())?;
+    let rest = &text[keyword.len()..];
+    (head.eq_ignore_ascii_case(keyword) && rest.starts_with(char::is_whitespace))
+        .then(|| rest.trim_start())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projections() -> Result<(), anyhow::Error> {
+        assert_eq!(project("select * from USERS WHERE ID = ?", &["ID", "NAME"])?, "SELECT ID, NAME from USERS WHERE ID = ?");
+        assert_eq!(project("\n SELECT  DISTINCT *\nFROM T", &["\"a\""])?, "SELECT DISTINCT \"a\"\nFROM T");
+        assert!(project("SELECT ID FROM T", &["ID"]).is_err());
+        assert!(project("SELECT *, 1 FROM T", &["ID"]).is_err());
+        assert!(project("SELECTED * FROM T", &["ID"]).is_err());
+        assert!(project("SELECT * FROM T", &[]).is_err());
+        Ok(())
+    }
+}
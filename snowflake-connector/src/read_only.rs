@@ -0,0 +1,102 @@
+//! Reject statements that could change data or objects, see [`SnowflakeConnector::read_only`].
+
+use crate::{errors::SnowflakeError, SnowflakeConnector, SnowflakeExecutor};
+
+/// Keywords of statements that write, checked anywhere in the statement to also catch ones after a `;`.
+const WRITE_KEYWORDS: &[&str] = &[
+    "INSERT", "UPDATE", "DELETE", "MERGE", "TRUNCATE", "COPY", "PUT", "REMOVE", "RM",
+    "CREATE", "ALTER", "DROP", "UNDROP", "RENAME", "COMMENT", "GRANT", "REVOKE",
+    "CALL", "EXECUTE",
+];
+
+impl SnowflakeConnector {
+    /// Reject statements with a keyword that writes, ex. `INSERT`, `CREATE` or `GRANT`,
+    /// outside of string literals, quoted identifiers and comments, along with Snowpipe loads.
+    ///
+    /// Keywords are matched as whole words, so a query only trips it by using a write keyword as an unquoted name.
+    /// Use [`SnowflakeExecutor::allow_writes`] for the statements that are meant to write.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+}
+
+impl SnowflakeExecutor<'_> {
+    /// Run statements of this executor even if the connector is [read-only](SnowflakeConnector::read_only).
+    pub fn allow_writes(mut self) -> Self {
+        self.read_only = false;
+        self
+    }
+}
+
+/// Errors if `statement` has a write keyword.
+pub(crate) fn check(statement: &str) -> Result<(), SnowflakeError> {
+    match write_keyword(statement) {
+        Some(keyword) => Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+            "connector is read-only but statement contains {keyword}, use `allow_writes` if it is meant to write",
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn write_keyword(statement: &str) -> Option<&'static str> {
+    let bytes = statement.as_bytes();
+    let mut index = 0;
+    while index < bytes.len() {
+        let rest = &bytes[index..];
+        if rest.starts_with(b"--") || rest.starts_with(b"//") {
+            index += rest.iter().position(|&byte| byte == b'\n').unwrap_or(rest.len());
+        } else if rest.starts_with(b"/*") {
+            index += rest.windows(2).position(|pair| pair == b"*/").map_or(rest.len(), |end| end + 2);
+        } else if rest.starts_with(b"$$") {
+            index += 2 + rest[2..].windows(2).position(|pair| pair == b"$$").map_or(rest.len(), |end| end + 2);
+        } else if rest[0] == b'\'' || rest[0] == b'"' {
+            index += 1 + quoted_len(&rest[1..], rest[0]);
+        } else if rest[0].is_ascii_alphabetic() || rest[0] == b'_' {
+            let len = rest.iter()
+                .position(|&byte| !(byte.is_ascii_alphanumeric() || byte == b'_' || byte == b'$'))
+                .unwrap_or(rest.len());
+            let word = &statement[index..index + len];
+            if let Some(keyword) = WRITE_KEYWORDS.iter().find(|keyword| keyword.eq_ignore_ascii_case(word)) {
+                return Some(keyword);
+            }
+            index += len;
+        } else {
+            index += 1;
+        }
+    }
+    None
+}
+
+/// Length up to and including the closing `quote`, a doubled quote does not close it,
+/// nor does a backslash escaped one in string literals, identifiers have no escapes.
+fn quoted_len(rest: &[u8], quote: u8) -> usize {
+    let mut index = 0;
+    while index < rest.len() {
+        match rest[index] {
+            b'\\' if quote == b'\'' => index += 2,
+            byte if byte == quote && rest.get(index + 1) == Some(&quote) => index += 2,
+            byte if byte == quote => return index + 1,
+            _ => index += 1,
+        }
+    }
+    rest.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords() {
+        assert_eq!(write_keyword("SELECT * FROM USERS WHERE ID = ?"), None);
+        assert_eq!(write_keyword("select * from users; drop table users"), Some("DROP"));
+        assert_eq!(write_keyword("INSERT INTO USERS VALUES (1)"), Some("INSERT"));
+        assert_eq!(write_keyword("SELECT 'DELETE FROM USERS', \"UPDATE\" FROM T -- TRUNCATE"), None);
+        assert_eq!(write_keyword("SELECT 'it''s', 'a\\'b' /* DROP */ FROM T"), None);
+        assert_eq!(write_keyword("SELECT UPDATED_AT, DELETED FROM T"), None);
+        assert_eq!(write_keyword("SELECT 'unterminated; DROP TABLE T"), None);
+        assert_eq!(write_keyword("SHOW TABLES; CALL CLEANUP()"), Some("CALL"));
+        assert_eq!(write_keyword(r#"SELECT 1 AS "\"; DROP TABLE USERS; --""#), Some("DROP"));
+    }
+}
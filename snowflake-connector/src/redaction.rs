@@ -0,0 +1,80 @@
+//! Choose how fields marked `#[snowflake(redact)]` are deserialized, they are masked unless unlocked.
+//!
+//! ```no_run
+//! # use snowflake_connector::{redaction::RedactionPolicy, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! // Redacted fields are `[redacted]` for every statement of the connector...
+//! let connector = connector.with_redaction_policy(RedactionPolicy::Placeholder("[redacted]".into()));
+//! // ...except for this one.
+//! let sql = connector.execute("DB", "WH")
+//!     .sql("SELECT ID, EMAIL FROM USERS")?
+//!     .with_redaction_policy(RedactionPolicy::Unlocked);
+//! # Ok(())
+//! # }
+//! ```
+
+pub use snowflake_deserializer::redact::RedactionPolicy;
+
+use crate::{SnowflakeConnector, SnowflakeSQL};
+
+impl SnowflakeConnector {
+    /// Policy of redacted fields for every statement, [`RedactionPolicy::default`] masks them.
+    pub fn with_redaction_policy(mut self, policy: RedactionPolicy) -> Self {
+        self.redaction = policy;
+        self
+    }
+}
+
+impl<'a> SnowflakeSQL<'a> {
+    /// Override the connector's [`RedactionPolicy`] for this statement, ex. to unlock a column for a job allowed to see it.
+    pub fn with_redaction_policy(mut self, policy: RedactionPolicy) -> SnowflakeSQL<'a> {
+        self.redaction = policy;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use snowflake_connector_derive::SnowflakeDeserialize;
+    use snowflake_deserializer::*;
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[derive(SnowflakeDeserialize, Debug, PartialEq)]
+    struct User {
+        id: i64,
+        #[snowflake(redact)]
+        email: Option<String>,
+    }
+
+    #[tokio::test]
+    async fn redacted() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&StatementFixture::new()
+            .column("ID", "fixed")
+            .column("EMAIL", "text")
+            .row([Some("1"), Some("jo@example.com")])
+            .row([Some("2"), None])).await;
+        let connector = snowflake.connector();
+        let select = |policy: Option<RedactionPolicy>| {
+            let sql = connector.execute("DB", "WH").sql("SELECT ID, EMAIL FROM USERS").unwrap();
+            let sql = match policy {
+                Some(policy) => sql.with_redaction_policy(policy),
+                None => sql,
+            };
+            sql.select::<User>()
+        };
+        let users = select(None).await?;
+        assert_eq!(users.data, [
+            User { id: 1, email: Some(redact::PLACEHOLDER.into()) },
+            User { id: 2, email: None },
+        ]);
+        let users = select(Some(RedactionPolicy::Unlocked)).await?;
+        assert_eq!(users.data[0].email.as_deref(), Some("jo@example.com"));
+        let users = select(Some(RedactionPolicy::custom(|_, value| value.len().to_string()))).await?;
+        assert_eq!(users.data[0].email.as_deref(), Some("14"));
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+//! Route statements to warehouses and roles by priority, so interactive queries
+//! do not queue behind batch extracts on the same warehouse.
+//!
+//! ```no_run
+//! # use snowflake_connector::{routing::{Priority, RoutingPolicy}, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let connector = connector.with_routing(
+//!     RoutingPolicy::default()
+//!         .route(Priority::Interactive, "DASHBOARD_WH")
+//!         .route_with_role(Priority::Batch, "EXTRACT_WH", "EXTRACT_ROLE"),
+//! );
+//! let response = connector.execute("DB", "WH")
+//!     .sql("SELECT * FROM EVENTS")?
+//!     .with_priority(Priority::Batch)
+//!     .text().await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{collections::HashMap, sync::Arc};
+
+use crate::{multiple::MultipleSnowflakeSQL, SnowflakeConnector, SnowflakeSQL};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Priority {
+    /// Latency sensitive, ex. queries behind a user interface.
+    Interactive,
+    /// Throughput over latency, ex. extracts and backfills.
+    Batch,
+}
+
+/// Warehouse, and optionally role, statements of a [`Priority`] run with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    pub warehouse: String,
+    pub role: Option<String>,
+}
+
+/// Routes per priority, a priority without a route keeps the warehouse and role of the statement.
+#[derive(Clone, Debug, Default)]
+pub struct RoutingPolicy {
+    routes: HashMap<Priority, Route>,
+}
+
+impl RoutingPolicy {
+    pub fn route(mut self, priority: Priority, warehouse: impl Into<String>) -> Self {
+        self.routes.insert(priority, Route { warehouse: warehouse.into(), role: None });
+        self
+    }
+    pub fn route_with_role(mut self, priority: Priority, warehouse: impl Into<String>, role: impl Into<String>) -> Self {
+        self.routes.insert(priority, Route { warehouse: warehouse.into(), role: Some(role.into()) });
+        self
+    }
+    pub fn get(&self, priority: Priority) -> Option<&Route> {
+        self.routes.get(&priority)
+    }
+}
+
+impl SnowflakeConnector {
+    /// Warehouses and roles of statements labeled with [`SnowflakeSQL::with_priority`].
+    pub fn with_routing(mut self, policy: RoutingPolicy) -> Self {
+        self.routing = Arc::new(policy);
+        self
+    }
+}
+
+impl<'a> SnowflakeSQL<'a> {
+    /// Run on the warehouse, and role if any, the connector's [`RoutingPolicy`] maps `priority` to.
+    ///
+    /// A later [`with_role`](Self::with_role) still overrides the role of the route.
+    pub fn with_priority(mut self, priority: Priority) -> SnowflakeSQL<'a> {
+        if let Some(route) = self.routing.get(priority) {
+            self.statement.warehouse = route.warehouse.clone();
+            if let Some(role) = &route.role {
+                self.statement.role = Some(role.clone());
+            }
+        }
+        self
+    }
+}
+
+impl<'a> MultipleSnowflakeSQL<'a> {
+    /// See [`SnowflakeSQL::with_priority`], statements given their own warehouse or role keep it.
+    pub fn with_priority(self, priority: Priority) -> MultipleSnowflakeSQL<'a> {
+        self.map_sql(|sql| sql.with_priority(priority))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::MockSnowflake;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn priorities() -> Result<(), anyhow::Error> {
+        let connector = MockSnowflake::start().await.connector().with_routing(
+            RoutingPolicy::default()
+                .route(Priority::Interactive, "DASHBOARD_WH")
+                .route_with_role(Priority::Batch, "EXTRACT_WH", "EXTRACT_ROLE"),
+        );
+        let sql = connector.execute("DB", "WH").sql("SELECT 1")?;
+        assert_eq!(sql.statement.warehouse, "WH");
+        let sql = sql.with_role("ANALYST").with_priority(Priority::Interactive);
+        assert_eq!(sql.statement.warehouse, "DASHBOARD_WH");
+        assert_eq!(sql.statement.role.as_deref(), Some("ANALYST"));
+        let sql = connector.execute("DB", "WH").sql("SELECT 1")?.with_priority(Priority::Batch);
+        assert_eq!(sql.statement.warehouse, "EXTRACT_WH");
+        assert_eq!(sql.statement.role.as_deref(), Some("EXTRACT_ROLE"));
+
+        let connector = connector.with_routing(RoutingPolicy::default());
+        let sql = connector.execute("DB", "WH").sql("SELECT 1")?.with_priority(Priority::Batch);
+        assert_eq!(sql.statement.warehouse, "WH");
+        Ok(())
+    }
+}
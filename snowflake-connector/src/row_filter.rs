@@ -0,0 +1,71 @@
+//! Rewrite raw cells before rows are deserialized, ex. to trim padded text or read `N/A` as `NULL`.
+//!
+//! ```no_run
+//! # use snowflake_connector::{row_filter::{CellTransform, RowFilter}, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! // Text of every statement of the connector is trimmed...
+//! let connector = connector.with_row_filter(RowFilter::new().with(CellTransform::Trim));
+//! // ...and this statement's legacy scores of `N/A` are `NULL`.
+//! let scores: Vec<(String, Option<u32>)> = connector.execute("DB", "WH")
+//!     .sql("SELECT NAME, SCORE FROM LEGACY_SCORES")?
+//!     .with_row_filter(RowFilter::new()
+//!         .with(CellTransform::Trim)
+//!         .with_column("SCORE", CellTransform::null_if(["N/A"])))
+//!     .select().await?
+//!     .data;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::Arc;
+
+pub use snowflake_deserializer::transform::{CellMap, CellTransform, RowFilter};
+
+use crate::{SnowflakeConnector, SnowflakeSQL};
+
+impl SnowflakeConnector {
+    /// Filter of the rows of every statement, none by default.
+    pub fn with_row_filter(mut self, filter: RowFilter) -> Self {
+        self.row_filter = Arc::new(filter);
+        self
+    }
+}
+
+impl<'a> SnowflakeSQL<'a> {
+    /// Replace the connector's [`RowFilter`] for this statement.
+    pub fn with_row_filter(mut self, filter: RowFilter) -> SnowflakeSQL<'a> {
+        self.row_filter = Arc::new(filter);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn filtered() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&StatementFixture::new()
+            .column("NAME", "text")
+            .column("SCORE", "fixed")
+            .row([Some(" ada "), Some("N/A")])
+            .row([Some("bob"), Some("7")])).await;
+        let connector = snowflake.connector().with_row_filter(RowFilter::new().with(CellTransform::Trim));
+        let select = || connector.execute("DB", "WH").sql("SELECT NAME, SCORE FROM LEGACY_SCORES");
+        assert!(select()?.select::<(String, Option<u32>)>().await.is_err());
+        let scores = select()?
+            .with_row_filter(RowFilter::new()
+                .with(CellTransform::Trim)
+                .with_column("SCORE", CellTransform::null_if(["N/A"])))
+            .select::<(String, Option<u32>)>().await?;
+        assert_eq!(scores.data, [("ada".to_owned(), None), ("bob".to_owned(), Some(7))]);
+        let mut cursor = select()?
+            .with_row_filter(RowFilter::new().with(CellTransform::null_if(["N/A"])))
+            .cursor().await?;
+        assert_eq!(cursor.fetchall::<(String, Option<u32>)>().await?[0], (" ada ".to_owned(), None));
+        Ok(())
+    }
+}
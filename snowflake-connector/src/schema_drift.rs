@@ -0,0 +1,279 @@
+//! Compare [`SnowflakeTable`] metadata against the live `INFORMATION_SCHEMA`,
+//! so CI can fail before deserialization breaks at runtime,
+//! and fetch the [`Schema`] `snowflake_query!` checks statements against.
+
+use std::{borrow::Cow, fmt::Display};
+
+use snowflake_deserializer::{
+    schema::{normalize_identifier, ColumnSchema, Schema, TableSchema},
+    table::{normalize_data_type, ColumnMeta, SnowflakeTable},
+};
+
+use crate::{errors::SnowflakeError, SnowflakeExecutor};
+
+#[derive(Clone, Debug)]
+pub struct LiveColumn {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+    pub comment: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct RetypedColumn {
+    pub expected: ColumnMeta,
+    pub actual: LiveColumn,
+}
+
+/// Differences between a [`SnowflakeTable`] and the live table.
+#[derive(Clone, Debug)]
+pub struct SchemaDrift {
+    pub table: &'static str,
+    /// Columns of the live table missing from the type.
+    pub added: Vec<LiveColumn>,
+    /// Columns of the type missing from the live table.
+    pub removed: Vec<ColumnMeta>,
+    /// Columns whose type or nullability changed.
+    pub retyped: Vec<RetypedColumn>,
+}
+
+impl SchemaDrift {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.retyped.is_empty()
+    }
+    fn compare(table: &'static str, expected: &[ColumnMeta], live: Vec<LiveColumn>) -> Self {
+        let mut drift = SchemaDrift {
+            table,
+            added: Vec::new(),
+            removed: Vec::new(),
+            retyped: Vec::new(),
+        };
+        for column in expected {
+            match live.iter().find(|live| live.name.eq_ignore_ascii_case(column.name)) {
+                Some(live) => {
+                    if !same_type(column.data_type, &live.data_type) || column.nullable != live.nullable
+                    {
+                        drift.retyped.push(RetypedColumn {
+                            expected: *column,
+                            actual: live.clone(),
+                        });
+                    }
+                },
+                None => drift.removed.push(*column),
+            }
+        }
+        drift.added = live.into_iter()
+            .filter(|live| !expected.iter().any(|column| column.name.eq_ignore_ascii_case(&live.name)))
+            .collect();
+        drift
+    }
+}
+
+/// Whether `expected` and `live` are the same type, precision and scale of numbers included.
+///
+/// Lengths of text are compared if `expected` has one, text without is `VARCHAR` of the default length.
+fn same_type(expected: &str, live: &str) -> bool {
+    let base = normalize_data_type(expected);
+    if base != normalize_data_type(live) {
+        return false;
+    }
+    match base.as_str() {
+        // Integer types and `NUMBER` without arguments are `NUMBER(38,0)`, a missing scale is 0.
+        "NUMBER" => {
+            let arguments = |data_type| match type_arguments(data_type).as_slice() {
+                [precision] => (*precision, 0),
+                [precision, scale] => (*precision, *scale),
+                _ => (38, 0),
+            };
+            arguments(expected) == arguments(live)
+        },
+        "TEXT" => {
+            let length = type_arguments(expected);
+            length.is_empty() || length == type_arguments(live)
+        },
+        _ => true,
+    }
+}
+
+/// Arguments of a data type, ex. `[12, 2]` for `NUMBER(12,2)`.
+fn type_arguments(data_type: &str) -> Vec<u32> {
+    data_type.split_once('(')
+        .map(|(_, arguments)| arguments.trim_end().trim_end_matches(')')
+            .split(',')
+            .filter_map(|argument| argument.trim().parse().ok())
+            .collect())
+        .unwrap_or_default()
+}
+
+impl Display for SchemaDrift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "{} has no schema drift", self.table);
+        }
+        write!(f, "{} drifted from its definition:", self.table)?;
+        for column in &self.added {
+            write!(f, "\n  added {} {}", column.name, column.data_type)?;
+        }
+        for column in &self.removed {
+            write!(f, "\n  removed {} {}", column.name, column.data_type)?;
+        }
+        for column in &self.retyped {
+            write!(
+                f,
+                "\n  retyped {} from {}{} to {}{}",
+                column.expected.name,
+                column.expected.data_type,
+                if column.expected.nullable { " NULL" } else { " NOT NULL" },
+                column.actual.data_type,
+                if column.actual.nullable { " NULL" } else { " NOT NULL" },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> SnowflakeExecutor<'a> {
+    /// Compare `T` against the columns of its table in `INFORMATION_SCHEMA.COLUMNS`.
+    ///
+    /// [`SnowflakeTable::TABLE`] may omit the database, the executor's database is used then.
+    pub async fn schema_drift<T: SnowflakeTable>(self) -> Result<SchemaDrift, SnowflakeError> {
+        let live = self.live_columns(T::TABLE).await?;
+        Ok(SchemaDrift::compare(T::TABLE, T::COLUMNS, live))
+    }
+    /// Columns of every table in `tables`, save it with [`Schema::to_json`] as [`Schema::FILE_NAME`]
+    /// next to `Cargo.toml` for `snowflake_query!` to check statements against.
+    ///
+    /// Comments of tables, views and columns are included, generated structs carry them as doc comments.
+    ///
+    /// Tables may omit the database, the executor's database is used then.
+    pub async fn fetch_schema(self, tables: &[&str]) -> Result<Schema, SnowflakeError> {
+        let mut schema = Schema::default();
+        for table in tables {
+            let columns = self.live_columns(table).await?;
+            if columns.is_empty() {
+                return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                    "table {table} does not exist or the role can not see it",
+                )));
+            }
+            schema.tables.push(TableSchema {
+                name: table.to_string(),
+                columns: columns.into_iter()
+                    .map(|column| ColumnSchema {
+                        name: column.name,
+                        data_type: column.data_type,
+                        nullable: column.nullable,
+                        comment: column.comment,
+                    })
+                    .collect(),
+                comment: self.table_comment(table).await?,
+            });
+        }
+        Ok(schema)
+    }
+    /// Columns of `table` in `INFORMATION_SCHEMA.COLUMNS`, numbers carry their precision and scale and text its length.
+    pub(crate) async fn live_columns(&self, table: &str) -> Result<Vec<LiveColumn>, SnowflakeError> {
+        let (database, schema, table) = self.qualify(table)?;
+        let statement = format!(
+            "SELECT COLUMN_NAME, DATA_TYPE, IS_NULLABLE, NUMERIC_PRECISION, NUMERIC_SCALE, CHARACTER_MAXIMUM_LENGTH, COMMENT \
+            FROM \"{}\".INFORMATION_SCHEMA.COLUMNS \
+            WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ? ORDER BY ORDINAL_POSITION",
+            database.replace('"', "\"\""),
+        );
+        Ok(self.statement(Cow::Owned(statement))?
+            .add_binding(schema)
+            .add_binding(table)
+            .select::<(String, String, String, Option<u32>, Option<i32>, Option<u32>, Option<String>)>().await?
+            .data
+            .into_iter()
+            .map(|(name, data_type, nullable, precision, scale, length, comment)| LiveColumn {
+                name,
+                data_type: match (precision, scale, length) {
+                    (Some(precision), Some(scale), _) if data_type.eq_ignore_ascii_case("NUMBER")
+                        => format!("{data_type}({precision},{scale})"),
+                    (_, _, Some(length)) if data_type.eq_ignore_ascii_case("TEXT") => format!("{data_type}({length})"),
+                    _ => data_type,
+                },
+                nullable: nullable.eq_ignore_ascii_case("YES"),
+                comment,
+            })
+            .collect())
+    }
+    /// Comment of the table or view `table` in `INFORMATION_SCHEMA.TABLES`.
+    async fn table_comment(&self, table: &str) -> Result<Option<String>, SnowflakeError> {
+        let (database, schema, table) = self.qualify(table)?;
+        let statement = format!(
+            "SELECT COMMENT FROM \"{}\".INFORMATION_SCHEMA.TABLES WHERE TABLE_SCHEMA = ? AND TABLE_NAME = ?",
+            database.replace('"', "\"\""),
+        );
+        Ok(self.statement(Cow::Owned(statement))?
+            .add_binding(schema)
+            .add_binding(table)
+            .select::<(Option<String>,)>().await?
+            .data
+            .pop()
+            .and_then(|(comment,)| comment))
+    }
+    /// Database, schema and name of `table`, normalized as stored in `INFORMATION_SCHEMA`.
+    fn qualify(&self, table: &str) -> Result<(String, String, String), SnowflakeError> {
+        let parts: Vec<String> = table.split('.').map(normalize_identifier).collect();
+        Ok(match parts.as_slice() {
+            [database, schema, table] => (database.clone(), schema.clone(), table.clone()),
+            [schema, table] => match self.database() {
+                Some(database) => (normalize_identifier(&database), schema.clone(), table.clone()),
+                None => return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                    "table {table} must be qualified with its database when the executor has none, ex. DATABASE.SCHEMA.TABLE",
+                ))),
+            },
+            _ => return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                "table {table} must be qualified with its schema, ex. SCHEMA.TABLE",
+            ))),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_columns() {
+        let expected = [
+            ColumnMeta { name: "ID", data_type: "NUMBER(38,0)", nullable: false },
+            ColumnMeta { name: "NAME", data_type: "VARCHAR", nullable: true },
+            ColumnMeta { name: "LEGACY", data_type: "BOOLEAN", nullable: true },
+        ];
+        let live = vec![
+            LiveColumn { name: "ID".into(), data_type: "TEXT".into(), nullable: false, comment: None },
+            LiveColumn { name: "NAME".into(), data_type: "TEXT".into(), nullable: true, comment: None },
+            LiveColumn { name: "CREATED_ON".into(), data_type: "TIMESTAMP_NTZ".into(), nullable: true, comment: None },
+        ];
+        let drift = SchemaDrift::compare("DB.PUBLIC.TEST_TABLE", &expected, live);
+        assert_eq!(drift.added.len(), 1);
+        assert_eq!(drift.added[0].name, "CREATED_ON");
+        assert_eq!(drift.removed, vec![expected[2]]);
+        assert_eq!(drift.retyped.len(), 1);
+        assert_eq!(drift.retyped[0].expected.name, "ID");
+        assert!(!drift.is_empty());
+    }
+
+    #[test]
+    fn compare_precision() {
+        let expected = [
+            ColumnMeta { name: "ID", data_type: "INT", nullable: false },
+            ColumnMeta { name: "TOTAL", data_type: "NUMBER(12,2)", nullable: true },
+            ColumnMeta { name: "CODE", data_type: "VARCHAR(3)", nullable: true },
+            ColumnMeta { name: "NOTE", data_type: "VARCHAR", nullable: true },
+        ];
+        let live = |total: &str, code: &str| vec![
+            LiveColumn { name: "ID".into(), data_type: "NUMBER(38,0)".into(), nullable: false, comment: None },
+            LiveColumn { name: "TOTAL".into(), data_type: total.into(), nullable: true, comment: None },
+            LiveColumn { name: "CODE".into(), data_type: code.into(), nullable: true, comment: None },
+            LiveColumn { name: "NOTE".into(), data_type: "TEXT(16777216)".into(), nullable: true, comment: None },
+        ];
+        assert!(SchemaDrift::compare("ORDERS", &expected, live("NUMBER(12,2)", "TEXT(3)")).is_empty());
+        let drift = SchemaDrift::compare("ORDERS", &expected, live("NUMBER(12,4)", "TEXT(16)"));
+        assert_eq!(drift.retyped.iter().map(|column| column.expected.name).collect::<Vec<_>>(), ["TOTAL", "CODE"]);
+        assert!(!same_type("NUMBER(10)", "NUMBER(10,2)"));
+        assert!(same_type("DECIMAL(10)", "NUMBER(10,0)"));
+    }
+}
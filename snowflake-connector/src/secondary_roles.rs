@@ -0,0 +1,136 @@
+//! Secondary roles statements run with, ex. for row access policies checking `IS_ROLE_IN_SESSION`.
+//!
+//! The SQL API has no session parameter for secondary roles, so statements given some run as a request of two
+//! statements, `USE SECONDARY ROLES ...` and the statement, and their result is the one of the statement.
+//! [`raw`](crate::SnowflakeSQL::raw), [`text`](crate::SnowflakeSQL::text) and [`bytes`](crate::SnowflakeSQL::bytes)
+//! return the response of the whole request, the last of its child handles is the statement's.
+//!
+//! ```no_run
+//! # use snowflake_connector::{secondary_roles::SecondaryRoles, SnowflakeConnector};
+//! # async fn example(connector: SnowflakeConnector) -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! let connector = connector.with_default_secondary_roles(SecondaryRoles::All);
+//! let regions: Vec<(String,)> = connector.execute("DB", "WH")
+//!     .sql("SELECT REGION FROM SALES")?
+//!     .with_secondary_roles(SecondaryRoles::list(["EMEA_ANALYST"]))
+//!     .select().await?
+//!     .data;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::borrow::Cow;
+
+use crate::{escape::escape_identifier, multiple::MultipleSnowflakeSQL, SnowflakeConnector, SnowflakeExecutorSQLJSON, SnowflakeSQL};
+
+/// Roles whose privileges a statement has besides those of its primary role.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SecondaryRoles {
+    /// Every role granted to the user.
+    All,
+    /// Only the primary role, ex. to override a user's `DEFAULT_SECONDARY_ROLES`.
+    None,
+    /// These roles, which must be granted to the user.
+    List(Vec<String>),
+}
+
+impl SecondaryRoles {
+    pub fn list<R: Into<String>>(roles: impl IntoIterator<Item = R>) -> Self {
+        SecondaryRoles::List(roles.into_iter().map(Into::into).collect())
+    }
+    /// `USE SECONDARY ROLES ...` statement, an empty list uses none.
+    pub fn to_sql(&self) -> String {
+        match self {
+            SecondaryRoles::All => "USE SECONDARY ROLES ALL".to_owned(),
+            SecondaryRoles::List(roles) if !roles.is_empty() => format!(
+                "USE SECONDARY ROLES {}",
+                roles.iter().map(|role| escape_identifier(role)).collect::<Vec<_>>().join(", "),
+            ),
+            SecondaryRoles::None | SecondaryRoles::List(_) => "USE SECONDARY ROLES NONE".to_owned(),
+        }
+    }
+}
+
+impl SnowflakeConnector {
+    /// Secondary roles of every statement of the connector, unless one is given its own.
+    pub fn with_default_secondary_roles(mut self, roles: SecondaryRoles) -> Self {
+        self.default_secondary_roles = Some(roles);
+        self
+    }
+}
+
+impl<'a> SnowflakeSQL<'a> {
+    /// Run the statement with `roles` as its secondary roles, see the [module](self).
+    pub fn with_secondary_roles(mut self, roles: SecondaryRoles) -> SnowflakeSQL<'a> {
+        self.statement.secondary_roles = Some(roles);
+        self
+    }
+}
+
+impl<'a> MultipleSnowflakeSQL<'a> {
+    /// See [`SnowflakeSQL::with_secondary_roles`], used by every request,
+    /// [`child_handles`](crate::multiple::MultipleSnowflakeSQLResponse::child_handles) skip the `USE SECONDARY ROLES` statements.
+    pub fn with_secondary_roles(self, roles: SecondaryRoles) -> MultipleSnowflakeSQL<'a> {
+        self.map_sql(|sql| sql.with_secondary_roles(roles))
+    }
+}
+
+/// `statement` preceded by `USE SECONDARY ROLES ...` in the same request.
+pub(crate) fn prefixed<'a>(statement: &SnowflakeExecutorSQLJSON<'a>, roles: &SecondaryRoles) -> SnowflakeExecutorSQLJSON<'a> {
+    let mut prefixed = statement.clone();
+    prefixed.secondary_roles = None;
+    prefixed.statement = Cow::Owned(format!("{};\n{}", roles.to_sql(), statement.statement));
+    let parameters = prefixed.parameters.get_or_insert_with(Default::default);
+    let count = match parameters.get(MULTI_STATEMENT_COUNT).map(|count| count.parse::<usize>()) {
+        // Scripts of any number of statements stay so.
+        Some(Ok(0)) => 0,
+        Some(Ok(count)) => count + 1,
+        _ => 2,
+    };
+    parameters.insert(MULTI_STATEMENT_COUNT.into(), count.to_string());
+    prefixed
+}
+
+const MULTI_STATEMENT_COUNT: &str = "MULTI_STATEMENT_COUNT";
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{matchers::{method, path_regex}, Mock};
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[test]
+    fn statements() {
+        assert_eq!(SecondaryRoles::All.to_sql(), "USE SECONDARY ROLES ALL");
+        assert_eq!(SecondaryRoles::list(["ANALYST", "eu reader"]).to_sql(), r#"USE SECONDARY ROLES ANALYST, "eu reader""#);
+        assert_eq!(SecondaryRoles::list(Vec::<String>::new()).to_sql(), "USE SECONDARY ROLES NONE");
+    }
+
+    #[tokio::test]
+    async fn select() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        let parent = StatementFixture::new()
+            .column("multiple statement execution", "text")
+            .row([Some("Multiple statements executed successfully.")])
+            .with_child_handles(["use-handle", "select-handle"]);
+        Mock::given(method("POST"))
+            .respond_with(parent.success())
+            .mount(&snowflake.server).await;
+        Mock::given(method("GET"))
+            .and(path_regex("statements/select-handle$"))
+            .respond_with(StatementFixture::new().column("REGION", "text").row([Some("EMEA")]).success())
+            .mount(&snowflake.server).await;
+        let connector = snowflake.connector().with_default_secondary_roles(SecondaryRoles::All);
+        let (region,): (String,) = connector.execute("DB", "WH")
+            .sql("SELECT REGION FROM SALES")?
+            .select_one().await?;
+        assert_eq!(region, "EMEA");
+
+        let requests = snowflake.server.received_requests().await.unwrap_or_default();
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body)?;
+        assert_eq!(body["statement"], "USE SECONDARY ROLES ALL;\nSELECT REGION FROM SALES");
+        assert_eq!(body["parameters"]["MULTI_STATEMENT_COUNT"], "2");
+        Ok(())
+    }
+}
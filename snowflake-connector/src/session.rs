@@ -0,0 +1,117 @@
+//! What a connector's session runs as, see [`SnowflakeConnector::verify`] and [`SnowflakeConnector::session_info`].
+//!
+//! Every statement of the SQL API starts a session with the user's defaults and the connector's
+//! [default database](SnowflakeConnector::with_default_database) and [warehouse](SnowflakeConnector::with_default_warehouse),
+//! which is what is reported, statements changing them with `USE` do not outlive their request.
+
+use std::sync::PoisonError;
+
+use crate::{errors::SnowflakeError, sql_static, SnowflakeConnector};
+
+/// Context statements run in, `None` where the session has none.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub user: String,
+    pub role: Option<String>,
+    pub warehouse: Option<String>,
+    pub database: Option<String>,
+    pub schema: Option<String>,
+    /// Ex. `AWS_US_WEST_2`.
+    pub region: String,
+    /// Release of Snowflake, ex. `8.40.1`.
+    pub version: String,
+}
+
+impl SnowflakeConnector {
+    /// Sign in and run `SELECT CURRENT_USER(), ...` on the [default warehouse](Self::with_default_warehouse),
+    /// ex. on startup to fail before the first statement does. Its result is cached for [`session_info`](Self::session_info).
+    ///
+    /// Errors of a rejected token or an unreachable host explain what to check.
+    pub async fn verify(&self) -> Result<SessionInfo, SnowflakeError> {
+        self.refresh_session_info().await.map_err(hint)
+    }
+    /// [`SessionInfo`] fetched by the first call, or the last [`verify`](Self::verify) or [`refresh_session_info`](Self::refresh_session_info).
+    pub async fn session_info(&self) -> Result<SessionInfo, SnowflakeError> {
+        if let Some(session) = self.session.read().unwrap_or_else(PoisonError::into_inner).as_ref() {
+            return Ok(session.clone());
+        }
+        self.refresh_session_info().await
+    }
+    /// Fetch the [`SessionInfo`] again, ex. after the user's default role or warehouse changed.
+    pub async fn refresh_session_info(&self) -> Result<SessionInfo, SnowflakeError> {
+        let warehouse = self.default_warehouse.clone().unwrap_or_default();
+        let (user, role, warehouse, database, schema, region, version) = self.execute_on(warehouse)
+            .sql_static(sql_static!(
+                "SELECT CURRENT_USER(), CURRENT_ROLE(), CURRENT_WAREHOUSE(), CURRENT_DATABASE(), CURRENT_SCHEMA(), CURRENT_REGION(), CURRENT_VERSION()"
+            ))?
+            .select_one().await?;
+        let session = SessionInfo { user, role, warehouse, database, schema, region, version };
+        *self.session.write().unwrap_or_else(PoisonError::into_inner) = Some(session.clone());
+        Ok(session)
+    }
+}
+
+/// `error` with what to check added, if it is a common misconfiguration.
+fn hint(error: SnowflakeError) -> SnowflakeError {
+    match error {
+        SnowflakeError::SqlExecution(e) => {
+            let message = format!("{e:#}");
+            let hint = if message.contains("401") {
+                "Snowflake rejected the token, check the account identifier, the user and the public key set for the user"
+            } else if message.contains("403") {
+                "the user may not use Snowflake's SQL API, check its network policy and default role"
+            } else if e.downcast_ref::<reqwest::Error>().is_some_and(reqwest::Error::is_connect) {
+                "could not reach Snowflake, check the host of the account"
+            } else {
+                return SnowflakeError::SqlExecution(e);
+            };
+            SnowflakeError::SqlExecution(e.context(hint))
+        },
+        error => error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{matchers::method, Mock, ResponseTemplate};
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn verify() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&StatementFixture::new()
+            .column("CURRENT_USER()", "text")
+            .column("CURRENT_ROLE()", "text")
+            .column("CURRENT_WAREHOUSE()", "text")
+            .column("CURRENT_DATABASE()", "text")
+            .column("CURRENT_SCHEMA()", "text")
+            .column("CURRENT_REGION()", "text")
+            .column("CURRENT_VERSION()", "text")
+            .row([Some("USER"), Some("ANALYST"), Some("WH"), None, None, Some("AWS_US_WEST_2"), Some("8.40.1")])).await;
+        let connector = snowflake.connector();
+        let session = connector.verify().await?;
+        assert_eq!(session, SessionInfo {
+            user: "USER".into(),
+            role: Some("ANALYST".into()),
+            warehouse: Some("WH".into()),
+            database: None,
+            schema: None,
+            region: "AWS_US_WEST_2".into(),
+            version: "8.40.1".into(),
+        });
+        snowflake.server.reset().await;
+        assert_eq!(connector.session_info().await?, session);
+        assert!(connector.refresh_session_info().await.is_err());
+
+        let snowflake = MockSnowflake::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("JWT token is invalid"))
+            .mount(&snowflake.server).await;
+        let error = anyhow::Error::from(snowflake.connector().verify().await.unwrap_err());
+        assert!(format!("{error:#}").contains("check the account identifier"), "{error:#}");
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+//! Typed results of `SHOW` commands.
+//!
+//! Columns are looked up by name, so columns added by newer Snowflake releases are ignored.
+
+use std::borrow::Cow;
+
+use snowflake_deserializer::{null, DeserializeFromStr, SnowflakeDeserialize, SnowflakeSQLResponse, SnowflakeSQLResult};
+
+use crate::{compat::Row, errors::SnowflakeError, SnowflakeExecutor};
+
+#[derive(Clone, Debug)]
+pub struct ShowTable {
+    pub created_on: String,
+    pub name: String,
+    pub database_name: String,
+    pub schema_name: String,
+    pub kind: String,
+    pub comment: String,
+    pub cluster_by: String,
+    pub rows: Option<u64>,
+    pub bytes: Option<u64>,
+    pub owner: String,
+    pub retention_time: Option<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ShowSchema {
+    pub created_on: String,
+    pub name: String,
+    pub database_name: String,
+    pub owner: String,
+    pub comment: String,
+    pub retention_time: Option<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ShowWarehouse {
+    pub name: String,
+    pub state: String,
+    pub warehouse_type: String,
+    pub size: String,
+    pub running: Option<u32>,
+    pub queued: Option<u32>,
+    pub auto_suspend: Option<u32>,
+    pub auto_resume: bool,
+    pub owner: String,
+    pub comment: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct ShowGrant {
+    pub created_on: String,
+    pub privilege: String,
+    pub granted_on: String,
+    pub name: String,
+    pub granted_to: String,
+    pub grantee_name: String,
+    pub grant_option: bool,
+    pub granted_by: String,
+}
+
+impl<'a> SnowflakeExecutor<'a> {
+    /// `SHOW TABLES [LIKE '<like>']` in the current database.
+    pub async fn show_tables(self, like: Option<&str>) -> Result<Vec<ShowTable>, SnowflakeError> {
+        self.show(format!("SHOW TABLES{}", like_clause(like))).await
+    }
+    /// `SHOW SCHEMAS [LIKE '<like>']` in the current database.
+    pub async fn show_schemas(self, like: Option<&str>) -> Result<Vec<ShowSchema>, SnowflakeError> {
+        self.show(format!("SHOW SCHEMAS{}", like_clause(like))).await
+    }
+    /// `SHOW WAREHOUSES [LIKE '<like>']`.
+    pub async fn show_warehouses(self, like: Option<&str>) -> Result<Vec<ShowWarehouse>, SnowflakeError> {
+        self.show(format!("SHOW WAREHOUSES{}", like_clause(like))).await
+    }
+    /// `SHOW GRANTS ON <object_type> <name>`, ex. `show_grants_on("TABLE", "DB.SCHEMA.T")`.
+    ///
+    /// `object_type` and `name` are inserted as is, do not pass user input.
+    pub async fn show_grants_on(self, object_type: &str, name: &str) -> Result<Vec<ShowGrant>, SnowflakeError> {
+        self.show(format!("SHOW GRANTS ON {object_type} {name}")).await
+    }
+    /// `SHOW GRANTS TO ROLE <role>`.
+    ///
+    /// `role` is inserted as is, do not pass user input.
+    pub async fn show_grants_to_role(self, role: &str) -> Result<Vec<ShowGrant>, SnowflakeError> {
+        self.show(format!("SHOW GRANTS TO ROLE {role}")).await
+    }
+    async fn show<T: SnowflakeDeserialize>(self, statement: String) -> Result<Vec<T>, SnowflakeError> {
+        Ok(self.statement(Cow::Owned(statement))?.select::<T>().await?.data)
+    }
+}
+
+fn like_clause(like: Option<&str>) -> String {
+    match like {
+        Some(like) => format!(" LIKE '{}'", like.replace('\\', "\\\\").replace('\'', "''")),
+        None => String::new(),
+    }
+}
+
+fn get<T>(row: &Row, column: &str) -> Result<T, anyhow::Error>
+    where T: DeserializeFromStr, T::Err: Into<anyhow::Error>
+{
+    row.try_get(column)
+}
+
+/// `None` if the column is missing, empty or `NULL`.
+fn get_optional<T>(row: &Row, column: &str) -> Result<Option<T>, anyhow::Error>
+    where T: DeserializeFromStr, T::Err: Into<anyhow::Error>
+{
+    match row.try_get_raw(column) {
+        Ok(value) if !value.is_empty() && !null::is_null(value) => row.try_get(column).map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// Text column, empty if missing.
+fn get_text(row: &Row, column: &str) -> String {
+    row.try_get_raw(column)
+        .map(str::to_owned)
+        .unwrap_or_default()
+}
+
+fn get_flag(row: &Row, column: &str) -> Result<bool, anyhow::Error> {
+    Ok(get_optional::<bool>(row, column)?.unwrap_or_default())
+}
+
+macro_rules! impl_show_deserialize {
+    ($ty: ty, |$row: ident| $ex: expr) => {
+        impl SnowflakeDeserialize for $ty {
+            fn snowflake_deserialize(response: SnowflakeSQLResponse) -> Result<SnowflakeSQLResult<Self>, anyhow::Error> {
+                let data = Row::snowflake_deserialize(response)?.data
+                    .iter()
+                    .map(|$row| $ex)
+                    .collect::<Result<_, anyhow::Error>>()?;
+                Ok(SnowflakeSQLResult::new(data))
+            }
+        }
+    };
+}
+
+impl_show_deserialize!(ShowTable, |row| Ok(ShowTable {
+    created_on: get_text(row, "created_on"),
+    name: get(row, "name")?,
+    database_name: get_text(row, "database_name"),
+    schema_name: get_text(row, "schema_name"),
+    kind: get_text(row, "kind"),
+    comment: get_text(row, "comment"),
+    cluster_by: get_text(row, "cluster_by"),
+    rows: get_optional(row, "rows")?,
+    bytes: get_optional(row, "bytes")?,
+    owner: get_text(row, "owner"),
+    retention_time: get_optional(row, "retention_time")?,
+}));
+
+impl_show_deserialize!(ShowSchema, |row| Ok(ShowSchema {
+    created_on: get_text(row, "created_on"),
+    name: get(row, "name")?,
+    database_name: get_text(row, "database_name"),
+    owner: get_text(row, "owner"),
+    comment: get_text(row, "comment"),
+    retention_time: get_optional(row, "retention_time")?,
+}));
+
+impl_show_deserialize!(ShowWarehouse, |row| Ok(ShowWarehouse {
+    name: get(row, "name")?,
+    state: get_text(row, "state"),
+    warehouse_type: get_text(row, "type"),
+    size: get_text(row, "size"),
+    running: get_optional(row, "running")?,
+    queued: get_optional(row, "queued")?,
+    auto_suspend: get_optional(row, "auto_suspend")?,
+    auto_resume: get_flag(row, "auto_resume")?,
+    owner: get_text(row, "owner"),
+    comment: get_text(row, "comment"),
+}));
+
+impl_show_deserialize!(ShowGrant, |row| Ok(ShowGrant {
+    created_on: get_text(row, "created_on"),
+    privilege: get(row, "privilege")?,
+    granted_on: get_text(row, "granted_on"),
+    name: get_text(row, "name"),
+    granted_to: get_text(row, "granted_to"),
+    grantee_name: get_text(row, "grantee_name"),
+    grant_option: get_flag(row, "grant_option")?,
+    granted_by: get_text(row, "granted_by"),
+}));
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{matchers::{body_string_contains, method}, Mock};
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[test]
+    fn like_clause_escapes_quotes() {
+        assert_eq!(like_clause(None), "");
+        assert_eq!(like_clause(Some("TEST_%")), " LIKE 'TEST_%'");
+        assert_eq!(like_clause(Some("it's")), " LIKE 'it''s'");
+    }
+
+    #[tokio::test]
+    async fn rows() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        let tables = StatementFixture::new()
+            .column("created_on", "timestamp_ltz")
+            .column("name", "text")
+            .column("database_name", "text")
+            .column("schema_name", "text")
+            .column("kind", "text")
+            .column("rows", "fixed")
+            .column("bytes", "fixed")
+            .column("owner", "text")
+            .column("budget", "text")
+            .row([Some("1700000000.000"), Some("ORDERS"), Some("DB"), Some("PUBLIC"), Some("TABLE"), Some("42"), Some(""), Some("SYSADMIN"), None]);
+        let warehouses = StatementFixture::new()
+            .column("name", "text")
+            .column("state", "text")
+            .column("type", "text")
+            .column("size", "text")
+            .column("running", "fixed")
+            .column("queued", "fixed")
+            .column("auto_suspend", "fixed")
+            .column("auto_resume", "text")
+            .row([Some("WH"), Some("SUSPENDED"), Some("STANDARD"), Some("X-Small"), Some("0"), Some("0"), None, Some("true")]);
+        let grants = StatementFixture::new()
+            .column("privilege", "text")
+            .column("granted_on", "text")
+            .column("name", "text")
+            .column("granted_to", "text")
+            .column("grantee_name", "text")
+            .column("grant_option", "text")
+            .row([Some("SELECT"), Some("TABLE"), Some("DB.PUBLIC.ORDERS"), Some("ROLE"), Some("ANALYST"), Some("false")])
+            .row([Some("OWNERSHIP"), Some("TABLE"), Some("DB.PUBLIC.ORDERS"), Some("ROLE"), Some("SYSADMIN"), Some("true")]);
+        for (statement, fixture) in [("SHOW TABLES", &tables), ("SHOW WAREHOUSES", &warehouses), ("SHOW GRANTS", &grants)] {
+            Mock::given(method("POST"))
+                .and(body_string_contains(statement))
+                .respond_with(fixture.success())
+                .mount(&snowflake.server).await;
+        }
+        let connector = snowflake.connector();
+
+        let tables = connector.execute("DB", "WH").show_tables(Some("ORD%")).await?;
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!((table.name.as_str(), table.schema_name.as_str(), table.owner.as_str()), ("ORDERS", "PUBLIC", "SYSADMIN"));
+        assert_eq!((table.rows, table.bytes, table.retention_time), (Some(42), None, None), "empty and missing columns are None");
+        assert_eq!(table.comment, "");
+
+        let warehouses = connector.execute("DB", "WH").show_warehouses(None).await?;
+        let warehouse = &warehouses[0];
+        assert_eq!((warehouse.name.as_str(), warehouse.state.as_str(), warehouse.warehouse_type.as_str()), ("WH", "SUSPENDED", "STANDARD"));
+        assert_eq!((warehouse.running, warehouse.auto_suspend, warehouse.auto_resume), (Some(0), None, true));
+
+        let grants = connector.execute("DB", "WH").show_grants_on("TABLE", "DB.PUBLIC.ORDERS").await?;
+        assert_eq!(
+            grants.iter().map(|grant| (grant.privilege.as_str(), grant.grantee_name.as_str(), grant.grant_option)).collect::<Vec<_>>(),
+            [("SELECT", "ANALYST", false), ("OWNERSHIP", "SYSADMIN", true)],
+        );
+        assert_eq!(grants[0].created_on, "");
+
+        let requests = snowflake.server.received_requests().await.unwrap_or_default();
+        assert!(String::from_utf8_lossy(&requests[0].body).contains("SHOW TABLES LIKE 'ORD%'"));
+        Ok(())
+    }
+}
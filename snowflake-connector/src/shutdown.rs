@@ -0,0 +1,188 @@
+//! Graceful shutdown, see [`SnowflakeConnector::shutdown`].
+
+use std::{
+    collections::HashSet,
+    sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc, Mutex},
+    time::Duration,
+};
+
+use crate::{
+    errors::SnowflakeError,
+    polling::{Backoff, Deadline},
+    status::{self, StatementOutcome},
+    SnowflakeConnector,
+};
+
+/// Statements sent through a connector that have not finished yet.
+#[derive(Debug, Default)]
+pub(crate) struct InFlight {
+    closed: AtomicBool,
+    /// Requests waiting on a response.
+    requests: AtomicUsize,
+    /// Handles of submitted statements still running.
+    handles: Mutex<HashSet<String>>,
+}
+
+/// Counts a request as in flight until dropped.
+#[derive(Debug)]
+pub(crate) struct InFlightGuard(Arc<InFlight>);
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        self.0.requests.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl InFlight {
+    pub(crate) fn check_open(&self) -> Result<(), SnowflakeError> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(SnowflakeError::ShuttingDown);
+        }
+        Ok(())
+    }
+    pub(crate) fn begin(self: &Arc<Self>) -> Result<InFlightGuard, SnowflakeError> {
+        self.check_open()?;
+        self.requests.fetch_add(1, Ordering::SeqCst);
+        Ok(InFlightGuard(self.clone()))
+    }
+    /// Remember pending statements until a later outcome shows they finished.
+    pub(crate) fn track(&self, outcome: &StatementOutcome) {
+        let mut handles = self.handles.lock().unwrap();
+        if outcome.is_pending() {
+            handles.insert(outcome.statement_handle().to_owned());
+        } else {
+            handles.remove(outcome.statement_handle());
+        }
+    }
+    fn pending_handles(&self) -> Vec<String> {
+        self.handles.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// What [`SnowflakeConnector::shutdown`] left behind.
+#[derive(Debug, Default)]
+pub struct ShutdownReport {
+    /// Handles of submitted statements cancelled once the grace period ran out.
+    pub cancelled: Vec<String>,
+    /// Requests still waiting on a response, they can not be cancelled without a statement handle.
+    pub abandoned: usize,
+    /// Handles of statements that could not be cancelled and may still be running, with why.
+    pub failed: Vec<(String, SnowflakeError)>,
+}
+
+impl ShutdownReport {
+    /// Whether every in-flight statement finished within the grace period.
+    pub fn is_drained(&self) -> bool {
+        self.cancelled.is_empty() && self.abandoned == 0 && self.failed.is_empty()
+    }
+}
+
+impl SnowflakeConnector {
+    /// Stop accepting new statements and wait up to `grace` for in-flight ones to finish,
+    /// submitted statements still running afterwards are cancelled.
+    ///
+    /// New statements fail with [`SnowflakeError::ShuttingDown`] once called.
+    /// Failing to cancel a statement does not stop the others from being cancelled, see [`ShutdownReport::failed`].
+    pub async fn shutdown(&self, grace: Duration) -> Result<ShutdownReport, SnowflakeError> {
+        self.in_flight.closed.store(true, Ordering::SeqCst);
+        let client = self.client()?;
+        let deadline = Deadline::after(grace);
+        let backoff = Backoff {
+            initial: Duration::from_millis(50),
+            max: Duration::from_secs(1),
+            ..Default::default()
+        };
+        let mut attempt = 0;
+        loop {
+            for handle in self.in_flight.pending_handles() {
+                // Statements whose status could not be fetched are cancelled once the grace period ran out.
+                if let Ok(outcome) = status::fetch_status(&client, &self.host, &handle, &self.null_sentinel).await {
+                    self.in_flight.track(&outcome);
+                }
+            }
+            let idle = self.in_flight.requests.load(Ordering::SeqCst) == 0
+                && self.in_flight.handles.lock().unwrap().is_empty();
+            if idle || deadline.has_passed() {
+                break;
+            }
+            tokio::time::sleep(backoff.delay(attempt).min(deadline.remaining())).await;
+            attempt += 1;
+        }
+        let mut report = ShutdownReport {
+            abandoned: self.in_flight.requests.load(Ordering::SeqCst),
+            ..Default::default()
+        };
+        for handle in self.in_flight.pending_handles() {
+            match status::cancel(&client, &self.host, &handle).await {
+                Ok(()) => {
+                    self.in_flight.handles.lock().unwrap().remove(&handle);
+                    report.cancelled.push(handle);
+                },
+                Err(e) => report.failed.push((handle, e)),
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wiremock::{matchers::{body_string_contains, method, path}, Mock, ResponseTemplate};
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[test]
+    fn closed_rejects_requests() {
+        let in_flight = Arc::new(InFlight::default());
+        let guard = in_flight.begin().unwrap();
+        assert_eq!(in_flight.requests.load(Ordering::SeqCst), 1);
+        drop(guard);
+        assert_eq!(in_flight.requests.load(Ordering::SeqCst), 0);
+        in_flight.closed.store(true, Ordering::SeqCst);
+        assert!(matches!(in_flight.begin(), Err(SnowflakeError::ShuttingDown)));
+    }
+
+    #[tokio::test]
+    async fn drain_then_cancel() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        for (statement, handle) in [("SELECT 1", "finishing"), ("SELECT 2", "running"), ("SELECT 3", "broken")] {
+            let fixture = StatementFixture::new().with_statement_handle(handle).column("ID", "fixed").row([Some("1")]);
+            Mock::given(method("POST"))
+                .and(path("/api/v2/statements"))
+                .and(body_string_contains(statement))
+                .respond_with(fixture.pending())
+                .mount(&snowflake.server).await;
+            let status = match handle {
+                "finishing" => fixture.success(),
+                "running" => fixture.pending(),
+                _ => ResponseTemplate::new(500),
+            };
+            Mock::given(method("GET"))
+                .and(path(format!("/api/v2/statements/{handle}")))
+                .respond_with(status)
+                .mount(&snowflake.server).await;
+            Mock::given(method("POST"))
+                .and(path(format!("/api/v2/statements/{handle}/cancel")))
+                .respond_with(ResponseTemplate::new(if handle == "broken" { 500 } else { 200 }))
+                .mount(&snowflake.server).await;
+        }
+        let connector = Arc::new(snowflake.connector());
+        let sql = |statement| connector.execute("DB", "WH").sql(statement).map(|sql| sql.into_owned());
+        sql("SELECT 1")?.select::<(u32,)>().await?;
+        assert!(sql("SELECT 3")?.select::<(u32,)>().await.is_err());
+        let running = tokio::spawn(sql("SELECT 2")?.select::<(u32,)>());
+        while !connector.in_flight.pending_handles().contains(&"running".to_owned()) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        let report = connector.shutdown(Duration::from_millis(100)).await?;
+        running.abort();
+        assert_eq!(report.cancelled, ["running"]);
+        assert_eq!(report.failed.iter().map(|(handle, _)| handle.as_str()).collect::<Vec<_>>(), ["broken"]);
+        assert_eq!(report.abandoned, 1);
+        assert!(!report.is_drained());
+        Ok(())
+    }
+}
@@ -0,0 +1,215 @@
+//! Snowpipe REST API, submit staged files to a pipe and follow their ingestion.
+//!
+//! Requests are authenticated with the connector's key pair token.
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{errors::SnowflakeError, SnowflakeConnector};
+
+/// File in the pipe's stage, relative to the stage location.
+#[derive(Serialize, Clone, Debug)]
+pub struct StagedFile {
+    pub path: String,
+    /// Size in bytes, lets Snowflake plan the load.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+impl StagedFile {
+    pub fn new(path: impl Into<String>) -> Self {
+        StagedFile { path: path.into(), size: None }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertFilesResponse {
+    pub request_id: String,
+    /// `SUCCESS` once the files are queued for ingestion.
+    pub response_code: String,
+}
+
+/// Load status of a file submitted to a pipe.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FileLoadStatus {
+    pub path: String,
+    pub stage_location: Option<String>,
+    pub file_size: Option<u64>,
+    pub time_received: Option<String>,
+    pub last_insert_time: Option<String>,
+    #[serde(default)]
+    pub rows_inserted: u64,
+    #[serde(default)]
+    pub rows_parsed: u64,
+    #[serde(default)]
+    pub errors_seen: u64,
+    pub error_limit: Option<u64>,
+    pub first_error: Option<String>,
+    pub first_error_line_num: Option<u64>,
+    pub first_error_character_pos: Option<u64>,
+    pub first_error_column_name: Option<String>,
+    pub system_error: Option<String>,
+    #[serde(default)]
+    pub complete: bool,
+    /// `LOADED`, `LOAD_IN_PROGRESS`, `PARTIALLY_LOADED` or `LOAD_FAILED`.
+    pub status: String,
+}
+
+impl FileLoadStatus {
+    pub fn is_loaded(&self) -> bool {
+        self.status == "LOADED"
+    }
+}
+
+/// Files loaded or still loading within the last 10 minutes, see [`Snowpipe::insert_report`].
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct InsertReport {
+    pub pipe: String,
+    /// Whether every event was returned, if false some were dropped and [`Snowpipe::load_history_scan`] should be used.
+    pub complete_result: bool,
+    /// Pass to the next [`Snowpipe::insert_report`] to only receive newer events.
+    pub next_begin_mark: Option<String>,
+    #[serde(default)]
+    pub files: Vec<FileLoadStatus>,
+}
+
+/// Files loaded within a time range, see [`Snowpipe::load_history_scan`].
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LoadHistory {
+    pub pipe: String,
+    pub complete_result: bool,
+    pub start_time_inclusive: Option<String>,
+    pub end_time_exclusive: Option<String>,
+    pub range_start_time: Option<String>,
+    pub range_end_time: Option<String>,
+    #[serde(default)]
+    pub files: Vec<FileLoadStatus>,
+}
+
+#[derive(Serialize)]
+struct InsertFilesRequest<'a> {
+    files: &'a [StagedFile],
+}
+
+/// Pipe driven through the Snowpipe REST API, see [`SnowflakeConnector::pipe`].
+#[derive(Clone, Debug)]
+pub struct Snowpipe {
+    client: reqwest::Client,
+    /// `https://{host}.snowflakecomputing.com/v1/data/pipes/{pipe}/`
+    url: String,
+}
+
+impl SnowflakeConnector {
+    /// Pipe named `pipe`, fully qualified, ex. `DB.SCHEMA.PIPE`.
+    pub fn pipe<P: AsRef<str>>(&self, pipe: P) -> Result<Snowpipe, SnowflakeError> {
+        self.in_flight.check_open()?;
+        if self.read_only {
+            return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                "connector is read-only, Snowpipe loads are rejected",
+            )));
+        }
+        let base = self.host.strip_suffix("api/v2/").unwrap_or(&self.host);
+        Ok(Snowpipe {
+            client: self.client()?,
+            url: format!("{base}v1/data/pipes/{}/", pipe.as_ref()),
+        })
+    }
+}
+
+impl Snowpipe {
+    /// Queue `files` for ingestion, at most 5000 per request.
+    pub async fn insert_files(&self, files: &[StagedFile]) -> Result<InsertFilesResponse, SnowflakeError> {
+        let request = self.client
+            .post(format!("{}insertFiles", self.url))
+            .query(&[("requestId", uuid::Uuid::new_v4().to_string())])
+            .json(&InsertFilesRequest { files });
+        send(request).await
+    }
+    /// Load events of the last 10 minutes, newer than `begin_mark` if given.
+    pub async fn insert_report(&self, begin_mark: Option<&str>) -> Result<InsertReport, SnowflakeError> {
+        let mut request = self.client
+            .get(format!("{}insertReport", self.url))
+            .query(&[("requestId", uuid::Uuid::new_v4().to_string())]);
+        if let Some(begin_mark) = begin_mark {
+            request = request.query(&[("beginMark", begin_mark)]);
+        }
+        send(request).await
+    }
+    /// Files loaded from `start_inclusive` up to `end_exclusive`, or now, as ISO-8601 timestamps, ex. `2023-01-02T00:00:00Z`.
+    pub async fn load_history_scan(
+        &self,
+        start_inclusive: &str,
+        end_exclusive: Option<&str>,
+    ) -> Result<LoadHistory, SnowflakeError> {
+        let mut request = self.client
+            .get(format!("{}loadHistoryScan", self.url))
+            .query(&[("requestId", uuid::Uuid::new_v4().to_string())])
+            .query(&[("startTimeInclusive", start_inclusive)]);
+        if let Some(end_exclusive) = end_exclusive {
+            request = request.query(&[("endTimeExclusive", end_exclusive)]);
+        }
+        send(request).await
+    }
+}
+
+async fn send<T: DeserializeOwned>(request: reqwest::RequestBuilder) -> Result<T, SnowflakeError> {
+    let response = request.send().await
+        .map_err(|e| SnowflakeError::SqlExecution(e.into()))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(SnowflakeError::SqlExecution(anyhow::anyhow!("snowpipe returned {status}: {body}")));
+    }
+    response.json().await
+        .map_err(|e| SnowflakeError::SqlResultParse(e.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::MockSnowflake;
+    use serde_json::json;
+    use wiremock::{matchers::{method, path, query_param}, Mock, ResponseTemplate};
+
+    #[tokio::test]
+    async fn insert_and_report() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/data/pipes/DB.PUBLIC.EVENTS/insertFiles"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "requestId": "a1",
+                "responseCode": "SUCCESS",
+            })))
+            .mount(&snowflake.server).await;
+        Mock::given(method("GET"))
+            .and(path("/v1/data/pipes/DB.PUBLIC.EVENTS/insertReport"))
+            .and(query_param("beginMark", "1_0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "pipe": "DB.PUBLIC.EVENTS",
+                "completeResult": true,
+                "nextBeginMark": "1_1",
+                "files": [{
+                    "path": "events/1.json",
+                    "rowsInserted": 2,
+                    "rowsParsed": 2,
+                    "errorsSeen": 0,
+                    "complete": true,
+                    "status": "LOADED",
+                }],
+            })))
+            .mount(&snowflake.server).await;
+
+        let pipe = snowflake.connector().pipe("DB.PUBLIC.EVENTS")?;
+        let response = pipe.insert_files(&[StagedFile::new("events/1.json")]).await?;
+        assert_eq!(response.response_code, "SUCCESS");
+        let report = pipe.insert_report(Some("1_0")).await?;
+        assert_eq!(report.next_begin_mark.as_deref(), Some("1_1"));
+        assert!(report.files[0].is_loaded());
+        assert_eq!(report.files[0].rows_inserted, 2);
+        assert!(pipe.load_history_scan("2023-01-02T00:00:00Z", None).await.is_err());
+        Ok(())
+    }
+}
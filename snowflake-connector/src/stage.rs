@@ -0,0 +1,547 @@
+//! Stages and file formats: unloading query results with `COPY INTO @stage FROM (query)`,
+//! and typed `CREATE`/`ALTER` of external stages and file formats.
+
+use std::{borrow::Cow, fmt::{Debug, Display}};
+
+use crate::{ddl::create_prefix, errors::SnowflakeError, SnowflakeExecutor};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compression {
+    Auto,
+    Gzip,
+    Bz2,
+    Brotli,
+    Zstd,
+    Deflate,
+    RawDeflate,
+    Snappy,
+    Lzo,
+    None,
+}
+
+impl Display for Compression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Compression::Auto => "AUTO",
+            Compression::Gzip => "GZIP",
+            Compression::Bz2 => "BZ2",
+            Compression::Brotli => "BROTLI",
+            Compression::Zstd => "ZSTD",
+            Compression::Deflate => "DEFLATE",
+            Compression::RawDeflate => "RAW_DEFLATE",
+            Compression::Snappy => "SNAPPY",
+            Compression::Lzo => "LZO",
+            Compression::None => "NONE",
+        })
+    }
+}
+
+/// `CSV` options, unset options keep Snowflake's defaults.
+#[derive(Clone, Debug, Default)]
+pub struct CsvOptions {
+    pub compression: Option<Compression>,
+    pub field_delimiter: Option<String>,
+    pub record_delimiter: Option<String>,
+    pub field_optionally_enclosed_by: Option<char>,
+    pub null_if: Vec<String>,
+    pub empty_field_as_null: Option<bool>,
+    /// Lines to skip at the start of each file when loading.
+    pub skip_header: Option<u32>,
+    pub trim_space: Option<bool>,
+    pub error_on_column_count_mismatch: Option<bool>,
+    pub date_format: Option<String>,
+    pub timestamp_format: Option<String>,
+    pub encoding: Option<String>,
+}
+
+/// `JSON` options, unset options keep Snowflake's defaults.
+#[derive(Clone, Debug, Default)]
+pub struct JsonOptions {
+    pub compression: Option<Compression>,
+    /// Load the elements of a top level array as separate rows.
+    pub strip_outer_array: Option<bool>,
+    pub strip_null_values: Option<bool>,
+    pub date_format: Option<String>,
+    pub timestamp_format: Option<String>,
+}
+
+/// `PARQUET` options, unset options keep Snowflake's defaults.
+#[derive(Clone, Debug, Default)]
+pub struct ParquetOptions {
+    pub compression: Option<Compression>,
+    pub binary_as_text: Option<bool>,
+    pub null_if: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub enum FileFormat {
+    /// File format created beforehand, ex. `DB.SCHEMA.MY_CSV`, inserted as is.
+    Named(String),
+    Csv(CsvOptions),
+    Json(JsonOptions),
+    Parquet(ParquetOptions),
+}
+
+impl FileFormat {
+    /// Options inside `FILE_FORMAT = (...)`.
+    pub fn to_sql(&self) -> String {
+        let mut options = Vec::new();
+        match self {
+            FileFormat::Named(name) => options.push(format!("FORMAT_NAME = {name}")),
+            FileFormat::Csv(csv) => {
+                options.push("TYPE = CSV".into());
+                push_option(&mut options, "COMPRESSION", csv.compression);
+                push_option(&mut options, "FIELD_DELIMITER", csv.field_delimiter.as_deref().map(literal));
+                push_option(&mut options, "RECORD_DELIMITER", csv.record_delimiter.as_deref().map(literal));
+                push_option(
+                    &mut options,
+                    "FIELD_OPTIONALLY_ENCLOSED_BY",
+                    csv.field_optionally_enclosed_by.map(|c| literal(&c.to_string())),
+                );
+                if !csv.null_if.is_empty() {
+                    let null_if: Vec<String> = csv.null_if.iter().map(|value| literal(value)).collect();
+                    options.push(format!("NULL_IF = ({})", null_if.join(", ")));
+                }
+                push_option(&mut options, "EMPTY_FIELD_AS_NULL", csv.empty_field_as_null.map(sql_bool));
+                push_option(&mut options, "SKIP_HEADER", csv.skip_header);
+                push_option(&mut options, "TRIM_SPACE", csv.trim_space.map(sql_bool));
+                push_option(
+                    &mut options,
+                    "ERROR_ON_COLUMN_COUNT_MISMATCH",
+                    csv.error_on_column_count_mismatch.map(sql_bool),
+                );
+                push_option(&mut options, "DATE_FORMAT", csv.date_format.as_deref().map(literal));
+                push_option(&mut options, "TIMESTAMP_FORMAT", csv.timestamp_format.as_deref().map(literal));
+                push_option(&mut options, "ENCODING", csv.encoding.as_deref().map(literal));
+            },
+            FileFormat::Json(json) => {
+                options.push("TYPE = JSON".into());
+                push_option(&mut options, "COMPRESSION", json.compression);
+                push_option(&mut options, "STRIP_OUTER_ARRAY", json.strip_outer_array.map(sql_bool));
+                push_option(&mut options, "STRIP_NULL_VALUES", json.strip_null_values.map(sql_bool));
+                push_option(&mut options, "DATE_FORMAT", json.date_format.as_deref().map(literal));
+                push_option(&mut options, "TIMESTAMP_FORMAT", json.timestamp_format.as_deref().map(literal));
+            },
+            FileFormat::Parquet(parquet) => {
+                options.push("TYPE = PARQUET".into());
+                push_option(&mut options, "COMPRESSION", parquet.compression);
+                push_option(&mut options, "BINARY_AS_TEXT", parquet.binary_as_text.map(sql_bool));
+                if !parquet.null_if.is_empty() {
+                    let null_if: Vec<String> = parquet.null_if.iter().map(|value| literal(value)).collect();
+                    options.push(format!("NULL_IF = ({})", null_if.join(", ")));
+                }
+            },
+        }
+        options.join(" ")
+    }
+}
+
+/// `CREATE FILE FORMAT`, named file formats can be shared by stages and `COPY` statements.
+#[derive(Clone, Debug)]
+pub struct CreateFileFormat {
+    /// Name, ex. `DB.SCHEMA.MY_CSV`, inserted as is.
+    pub name: String,
+    pub format: FileFormat,
+    pub or_replace: bool,
+    pub if_not_exists: bool,
+    pub comment: Option<String>,
+}
+
+impl CreateFileFormat {
+    pub fn new(name: impl Into<String>, format: FileFormat) -> Self {
+        CreateFileFormat {
+            name: name.into(),
+            format,
+            or_replace: false,
+            if_not_exists: false,
+            comment: None,
+        }
+    }
+    pub fn or_replace(mut self) -> Self {
+        self.or_replace = true;
+        self
+    }
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+    pub fn to_sql(&self) -> Result<String, SnowflakeError> {
+        if let FileFormat::Named(name) = &self.format {
+            return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                "file format {} can not be created from the named file format {name}, pass its options instead",
+                self.name,
+            )));
+        }
+        let mut statement = create_prefix("FILE FORMAT", &self.name, self.or_replace, self.if_not_exists);
+        statement.push(' ');
+        statement.push_str(&self.format.to_sql());
+        if let Some(comment) = &self.comment {
+            statement.push_str(&format!(" COMMENT = {}", literal(comment)));
+        }
+        Ok(statement)
+    }
+}
+
+/// Credentials Snowflake uses to access an external location,
+/// prefer a storage integration so no secret ends up in the statement.
+#[derive(Clone)]
+pub enum StageCredentials {
+    Aws {
+        key_id: String,
+        secret_key: String,
+        /// Temporary session token.
+        token: Option<String>,
+    },
+    Azure {
+        sas_token: String,
+    },
+}
+
+impl StageCredentials {
+    fn to_sql(&self) -> String {
+        match self {
+            StageCredentials::Aws { key_id, secret_key, token } => {
+                let mut credentials = format!("AWS_KEY_ID = {} AWS_SECRET_KEY = {}", literal(key_id), literal(secret_key));
+                if let Some(token) = token {
+                    credentials.push_str(&format!(" AWS_TOKEN = {}", literal(token)));
+                }
+                credentials
+            },
+            StageCredentials::Azure { sas_token } => format!("AZURE_SAS_TOKEN = {}", literal(sas_token)),
+        }
+    }
+}
+
+/// Secrets are redacted.
+impl Debug for StageCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StageCredentials::Aws { key_id, token, .. } => f.debug_struct("Aws")
+                .field("key_id", key_id)
+                .field("secret_key", &"<redacted>")
+                .field("token", &token.as_ref().map(|_| "<redacted>"))
+                .finish(),
+            StageCredentials::Azure { .. } => f.debug_struct("Azure")
+                .field("sas_token", &"<redacted>")
+                .finish(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum StageEncryption {
+    AwsSseS3,
+    AwsSseKms { kms_key_id: Option<String> },
+    GcsSseKms { kms_key_id: Option<String> },
+    AzureCse { master_key: String },
+    None,
+}
+
+impl StageEncryption {
+    fn to_sql(&self) -> String {
+        match self {
+            StageEncryption::AwsSseS3 => "TYPE = 'AWS_SSE_S3'".into(),
+            StageEncryption::AwsSseKms { kms_key_id } => kms("AWS_SSE_KMS", kms_key_id.as_deref()),
+            StageEncryption::GcsSseKms { kms_key_id } => kms("GCS_SSE_KMS", kms_key_id.as_deref()),
+            StageEncryption::AzureCse { master_key } => format!("TYPE = 'AZURE_CSE' MASTER_KEY = {}", literal(master_key)),
+            StageEncryption::None => "TYPE = 'NONE'".into(),
+        }
+    }
+}
+
+fn kms(encryption: &str, kms_key_id: Option<&str>) -> String {
+    match kms_key_id {
+        Some(kms_key_id) => format!("TYPE = '{encryption}' KMS_KEY_ID = {}", literal(kms_key_id)),
+        None => format!("TYPE = '{encryption}'"),
+    }
+}
+
+/// External stage on S3, GCS or Azure, see [`SnowflakeExecutor::create_stage`] and [`SnowflakeExecutor::alter_stage`].
+#[derive(Clone, Debug)]
+pub struct ExternalStage {
+    /// Name, ex. `DB.SCHEMA.EXPORTS`, inserted as is.
+    pub name: String,
+    /// Location, ex. `s3://bucket/path/`, `gcs://bucket/path/` or `azure://account.blob.core.windows.net/container/path/`.
+    pub url: String,
+    pub storage_integration: Option<String>,
+    pub credentials: Option<StageCredentials>,
+    pub encryption: Option<StageEncryption>,
+    pub file_format: Option<FileFormat>,
+    pub comment: Option<String>,
+    pub or_replace: bool,
+    pub if_not_exists: bool,
+}
+
+impl ExternalStage {
+    pub fn new(name: impl Into<String>, url: impl Into<String>) -> Self {
+        ExternalStage {
+            name: name.into(),
+            url: url.into(),
+            storage_integration: None,
+            credentials: None,
+            encryption: None,
+            file_format: None,
+            comment: None,
+            or_replace: false,
+            if_not_exists: false,
+        }
+    }
+    /// Stage on `s3://{bucket}/{path}`.
+    pub fn s3(name: impl Into<String>, bucket: &str, path: &str) -> Self {
+        Self::new(name, location("s3", bucket, path))
+    }
+    /// Stage on `gcs://{bucket}/{path}`, GCS only supports access through a storage integration.
+    pub fn gcs(name: impl Into<String>, bucket: &str, path: &str, storage_integration: impl Into<String>) -> Self {
+        Self::new(name, location("gcs", bucket, path)).with_storage_integration(storage_integration)
+    }
+    pub fn with_storage_integration(mut self, storage_integration: impl Into<String>) -> Self {
+        self.storage_integration = Some(storage_integration.into());
+        self
+    }
+    pub fn with_credentials(mut self, credentials: StageCredentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+    pub fn with_encryption(mut self, encryption: StageEncryption) -> Self {
+        self.encryption = Some(encryption);
+        self
+    }
+    pub fn with_file_format(mut self, file_format: FileFormat) -> Self {
+        self.file_format = Some(file_format);
+        self
+    }
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+    pub fn or_replace(mut self) -> Self {
+        self.or_replace = true;
+        self
+    }
+    pub fn if_not_exists(mut self) -> Self {
+        self.if_not_exists = true;
+        self
+    }
+    /// `CREATE STAGE`, may contain credentials, do not log it.
+    pub fn to_create_sql(&self) -> Result<String, SnowflakeError> {
+        self.check()?;
+        let mut statement = create_prefix("STAGE", &self.name, self.or_replace, self.if_not_exists);
+        statement.push(' ');
+        statement.push_str(&self.properties().join(" "));
+        Ok(statement)
+    }
+    /// `ALTER STAGE ... SET` every configured property, may contain credentials, do not log it.
+    pub fn to_alter_sql(&self) -> Result<String, SnowflakeError> {
+        self.check()?;
+        Ok(format!("ALTER STAGE {} SET {}", self.name, self.properties().join(" ")))
+    }
+    fn check(&self) -> Result<(), SnowflakeError> {
+        if self.storage_integration.is_some() && self.credentials.is_some() {
+            return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                "stage {} can not use both a storage integration and credentials", self.name,
+            )));
+        }
+        if self.url.starts_with("gcs://") && self.credentials.is_some() {
+            return Err(SnowflakeError::InvalidStatement(anyhow::anyhow!(
+                "stage {} is on GCS, which only supports a storage integration", self.name,
+            )));
+        }
+        Ok(())
+    }
+    fn properties(&self) -> Vec<String> {
+        let mut properties = vec![format!("URL = {}", literal(&self.url))];
+        push_option(&mut properties, "STORAGE_INTEGRATION", self.storage_integration.as_deref());
+        push_option(&mut properties, "CREDENTIALS", self.credentials.as_ref().map(|credentials| format!("({})", credentials.to_sql())));
+        push_option(&mut properties, "ENCRYPTION", self.encryption.as_ref().map(|encryption| format!("({})", encryption.to_sql())));
+        push_option(&mut properties, "FILE_FORMAT", self.file_format.as_ref().map(|file_format| format!("({})", file_format.to_sql())));
+        push_option(&mut properties, "COMMENT", self.comment.as_deref().map(literal));
+        properties
+    }
+}
+
+fn location(scheme: &str, bucket: &str, path: &str) -> String {
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        format!("{scheme}://{bucket}/")
+    } else {
+        format!("{scheme}://{bucket}/{path}/")
+    }
+}
+
+/// Copy options of an unload, unset options keep Snowflake's defaults.
+#[derive(Clone, Debug, Default)]
+pub struct UnloadOptions {
+    /// Write a single file instead of one per thread.
+    pub single: Option<bool>,
+    /// Upper size of each file in bytes.
+    pub max_file_size: Option<u64>,
+    pub overwrite: Option<bool>,
+    /// Write the column names as the first line, `CSV` only.
+    pub header: Option<bool>,
+    /// Include the query id in file names, so concurrent unloads do not overwrite each other.
+    pub include_query_id: Option<bool>,
+}
+
+impl UnloadOptions {
+    fn to_sql(&self) -> String {
+        let mut options = Vec::new();
+        push_option(&mut options, "SINGLE", self.single.map(sql_bool));
+        push_option(&mut options, "MAX_FILE_SIZE", self.max_file_size);
+        push_option(&mut options, "OVERWRITE", self.overwrite.map(sql_bool));
+        push_option(&mut options, "HEADER", self.header.map(sql_bool));
+        push_option(&mut options, "INCLUDE_QUERY_ID", self.include_query_id.map(sql_bool));
+        options.join(" ")
+    }
+}
+
+/// File written by an unload.
+#[derive(Clone, Debug)]
+pub struct UnloadedFile {
+    /// Path relative to the stage location.
+    pub name: String,
+    pub size: u64,
+    pub rows: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct UnloadResult {
+    pub files: Vec<UnloadedFile>,
+}
+
+impl UnloadResult {
+    pub fn rows_unloaded(&self) -> u64 {
+        self.files.iter().map(|file| file.rows).sum()
+    }
+    pub fn bytes_written(&self) -> u64 {
+        self.files.iter().map(|file| file.size).sum()
+    }
+}
+
+impl<'a> SnowflakeExecutor<'a> {
+    /// `COPY INTO @<stage> FROM (<query>)`, `stage` may include a path, ex. `MY_STAGE/exports/`.
+    ///
+    /// `query` and `stage` are inserted as is, do not pass user input.
+    pub async fn unload_to_stage(
+        self,
+        query: &str,
+        stage: &str,
+        file_format: &FileFormat,
+        options: &UnloadOptions,
+    ) -> Result<UnloadResult, SnowflakeError> {
+        let statement = unload_statement(query, stage, file_format, options);
+        let files = self.statement(Cow::Owned(statement))?
+            .select::<(String, u64, u64)>().await?
+            .data
+            .into_iter()
+            .map(|(name, size, rows)| UnloadedFile { name, size, rows })
+            .collect();
+        Ok(UnloadResult { files })
+    }
+    pub async fn create_stage(self, stage: &ExternalStage) -> Result<(), SnowflakeError> {
+        self.ddl(stage.to_create_sql()?).await
+    }
+    pub async fn alter_stage(self, stage: &ExternalStage) -> Result<(), SnowflakeError> {
+        self.ddl(stage.to_alter_sql()?).await
+    }
+    pub async fn create_file_format(self, file_format: &CreateFileFormat) -> Result<(), SnowflakeError> {
+        self.ddl(file_format.to_sql()?).await
+    }
+}
+
+fn unload_statement(query: &str, stage: &str, file_format: &FileFormat, options: &UnloadOptions) -> String {
+    let query = query.trim().trim_end_matches(';');
+    let stage = stage.trim_start_matches('@');
+    let mut statement = format!("COPY INTO @{stage} FROM ({query}) FILE_FORMAT = ({})", file_format.to_sql());
+    let options = options.to_sql();
+    if !options.is_empty() {
+        statement.push(' ');
+        statement.push_str(&options);
+    }
+    // One row per file instead of totals.
+    statement.push_str(" DETAILED_OUTPUT = TRUE");
+    statement
+}
+
+fn push_option<T: Display>(options: &mut Vec<String>, name: &str, value: Option<T>) {
+    if let Some(value) = value {
+        options.push(format!("{name} = {value}"));
+    }
+}
+
+fn sql_bool(value: bool) -> &'static str {
+    if value { "TRUE" } else { "FALSE" }
+}
+
+/// Single quoted string literal.
+fn literal(value: &str) -> String {
+    crate::escape::escape_string_literal(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn statement() {
+        let file_format = FileFormat::Csv(CsvOptions {
+            compression: Some(Compression::Gzip),
+            field_optionally_enclosed_by: Some('"'),
+            null_if: vec!["".into(), "NULL".into()],
+            ..Default::default()
+        });
+        let options = UnloadOptions {
+            header: Some(true),
+            max_file_size: Some(1 << 20),
+            ..Default::default()
+        };
+        assert_eq!(
+            unload_statement("SELECT * FROM T;", "@EXPORTS/t/", &file_format, &options),
+            "COPY INTO @EXPORTS/t/ FROM (SELECT * FROM T) \
+            FILE_FORMAT = (TYPE = CSV COMPRESSION = GZIP FIELD_OPTIONALLY_ENCLOSED_BY = '\"' NULL_IF = ('', 'NULL')) \
+            MAX_FILE_SIZE = 1048576 HEADER = TRUE DETAILED_OUTPUT = TRUE",
+        );
+        assert_eq!(FileFormat::Named("DB.PUBLIC.MY_CSV".into()).to_sql(), "FORMAT_NAME = DB.PUBLIC.MY_CSV");
+        assert_eq!(literal("it's"), "'it\\'s'");
+    }
+
+    #[test]
+    fn external_stage() {
+        let stage = ExternalStage::s3("DB.PUBLIC.EXPORTS", "bucket", "/exports/")
+            .with_credentials(StageCredentials::Aws {
+                key_id: "AKIA".into(),
+                secret_key: "s3cr3t".into(),
+                token: None,
+            })
+            .with_encryption(StageEncryption::AwsSseKms { kms_key_id: None })
+            .with_file_format(FileFormat::Json(JsonOptions {
+                strip_outer_array: Some(true),
+                ..Default::default()
+            }))
+            .or_replace();
+        assert_eq!(
+            stage.to_create_sql().unwrap(),
+            "CREATE OR REPLACE STAGE DB.PUBLIC.EXPORTS URL = 's3://bucket/exports/' \
+            CREDENTIALS = (AWS_KEY_ID = 'AKIA' AWS_SECRET_KEY = 's3cr3t') \
+            ENCRYPTION = (TYPE = 'AWS_SSE_KMS') FILE_FORMAT = (TYPE = JSON STRIP_OUTER_ARRAY = TRUE)",
+        );
+        assert!(!format!("{stage:?}").contains("s3cr3t"));
+
+        let stage = ExternalStage::gcs("EXPORTS", "bucket", "", "GCS_INT").with_comment("nightly");
+        assert_eq!(
+            stage.to_alter_sql().unwrap(),
+            "ALTER STAGE EXPORTS SET URL = 'gcs://bucket/' STORAGE_INTEGRATION = GCS_INT COMMENT = 'nightly'",
+        );
+        assert!(stage.with_credentials(StageCredentials::Azure { sas_token: "t".into() }).to_create_sql().is_err());
+
+        let file_format = CreateFileFormat::new("MY_CSV", FileFormat::Csv(CsvOptions {
+            skip_header: Some(1),
+            ..Default::default()
+        })).if_not_exists();
+        assert_eq!(file_format.to_sql().unwrap(), "CREATE FILE FORMAT IF NOT EXISTS MY_CSV TYPE = CSV SKIP_HEADER = 1");
+        assert!(CreateFileFormat::new("COPY", FileFormat::Named("MY_CSV".into())).to_sql().is_err());
+    }
+}
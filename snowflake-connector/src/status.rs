@@ -0,0 +1,337 @@
+use std::sync::Arc;
+
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use snowflake_deserializer::{SnowflakeDeserialize, SnowflakeSQLResponse, SnowflakeSQLResult};
+
+use crate::{
+    error_code::ErrorCode,
+    errors::SnowflakeError,
+    partitions::read_json,
+    polling::{Backoff, Deadline},
+    SnowflakeConnector,
+};
+
+/// Body returned while a statement is still executing (HTTP 202).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryStatus {
+    pub code: String,
+    #[serde(default)]
+    pub sql_state: String,
+    pub message: String,
+    pub statement_handle: String,
+    pub created_on: Option<u64>,
+    pub statement_status_url: String,
+}
+
+/// Body returned when a statement failed (HTTP 422).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryFailureStatus {
+    pub code: String,
+    #[serde(default)]
+    pub sql_state: String,
+    pub message: String,
+    pub statement_handle: String,
+    pub created_on: Option<u64>,
+    pub statement_status_url: Option<String>,
+}
+
+impl QueryStatus {
+    /// [`StatementState::Running`], the SQL API reports statements waiting for warehouse capacity
+    /// with the same code, [`ErrorCode::InProgress`], as running ones.
+    pub fn state(&self) -> StatementState {
+        StatementState::Running
+    }
+}
+
+impl QueryFailureStatus {
+    /// [`StatementState::Aborted`] for cancelled statements, else [`StatementState::FailedWithError`].
+    pub fn state(&self) -> StatementState {
+        match ErrorCode::from_code(&self.code) {
+            Some(ErrorCode::Cancelled) => StatementState::Aborted,
+            _ => StatementState::FailedWithError,
+        }
+    }
+}
+
+/// State of a statement, derived from the code of its status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StatementState {
+    /// Queued or running.
+    Running,
+    Success,
+    FailedWithError,
+    /// Cancelled, by the caller or Snowflake.
+    Aborted,
+}
+
+impl StatementState {
+    /// Whether the statement will not change state anymore.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, StatementState::Success | StatementState::FailedWithError | StatementState::Aborted)
+    }
+}
+
+/// Where a submitted statement currently stands.
+#[derive(Debug)]
+pub enum StatementOutcome {
+    Success(Box<SnowflakeSQLResponse>),
+    Failed(QueryFailureStatus),
+    /// Still executing (HTTP 202), keep polling.
+    Pending(QueryStatus),
+    /// Exceeded the timeout it was sent with (HTTP 408) and may still be running,
+    /// wait longer or cancel it, see [`SnowflakeConnector::cancel_or_wait`].
+    TimedOut(QueryStatus),
+}
+
+impl StatementOutcome {
+    pub fn state(&self) -> StatementState {
+        match self {
+            StatementOutcome::Success(_) => StatementState::Success,
+            StatementOutcome::Failed(status) => status.state(),
+            StatementOutcome::Pending(status) |
+            StatementOutcome::TimedOut(status)
+                => status.state(),
+        }
+    }
+    /// Whether the statement may still be running.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, StatementOutcome::Pending(_) | StatementOutcome::TimedOut(_))
+    }
+    pub fn is_timed_out(&self) -> bool {
+        matches!(self, StatementOutcome::TimedOut(_))
+    }
+    /// Handles of each child statement once a multi-statement request succeeded.
+    pub fn child_handles(&self) -> &[String] {
+        match self {
+            StatementOutcome::Success(response) => &response.statement_handles,
+            _ => &[],
+        }
+    }
+    pub fn statement_handle(&self) -> &str {
+        match self {
+            StatementOutcome::Success(response) => &response.statement_handle,
+            StatementOutcome::Failed(status) => &status.statement_handle,
+            StatementOutcome::Pending(status) |
+            StatementOutcome::TimedOut(status)
+                => &status.statement_handle,
+        }
+    }
+    /// Error of a statement that failed or did not finish.
+    pub(crate) fn succeeded(self) -> Result<(), SnowflakeError> {
+        match self {
+            StatementOutcome::Success(_) => Ok(()),
+            StatementOutcome::Failed(status) => Err(SnowflakeError::StatementFailed(Box::new(status))),
+            StatementOutcome::Pending(status) |
+            StatementOutcome::TimedOut(status)
+                => Err(SnowflakeError::StatementTimedOut(Box::new(status))),
+        }
+    }
+}
+
+impl QueryStatus {
+    /// [`statement_status_url`](Self::statement_status_url) resolved against the connector's host,
+    /// Snowflake usually returns it relative, ex. `/api/v2/statements/{handle}`.
+    pub fn status_url(&self, connector: &SnowflakeConnector) -> String {
+        resolve_url(&connector.host, &self.statement_status_url)
+    }
+    /// Current status of the statement, following [`status_url`](Self::status_url).
+    pub async fn fetch_status(&self, connector: &SnowflakeConnector) -> Result<StatementOutcome, SnowflakeError> {
+        let client = connector.client()?;
+        Ok(fetch_url_sized(&client, &self.status_url(connector), &connector.null_sentinel).await?.0)
+    }
+    /// Rows of the statement once it succeeded, with every partition,
+    /// errors if it failed or is still running.
+    pub async fn fetch_result<T: SnowflakeDeserialize>(&self, connector: &SnowflakeConnector) -> Result<SnowflakeSQLResult<T>, SnowflakeError> {
+        connector.in_flight.check_open()?;
+        let client = connector.client()?;
+        let (outcome, _) = fetch_url_sized(&client, &self.status_url(connector), &connector.null_sentinel).await?;
+        let data = connector.result_cursor(client, outcome)?
+            .fetchall().await?;
+        Ok(SnowflakeSQLResult::new(data))
+    }
+}
+
+/// `url` as is if absolute, else its path on the host of `host`, ex. `https://account.snowflakecomputing.com/api/v2/`.
+fn resolve_url(host: &str, url: &str) -> String {
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return url.to_owned();
+    }
+    let origin_length = host.find("://")
+        .and_then(|scheme| host[scheme + 3..].find('/').map(|path| scheme + 3 + path))
+        .unwrap_or(host.len());
+    format!("{}/{}", &host[..origin_length], url.trim_start_matches('/'))
+}
+
+/// Current status of an already submitted statement.
+pub(crate) async fn fetch_status(
+    client: &reqwest::Client,
+    host: &str,
+    statement_handle: &str,
+    null_sentinel: &Arc<str>,
+) -> Result<StatementOutcome, SnowflakeError> {
+    Ok(fetch_status_sized(client, host, statement_handle, null_sentinel).await?.0)
+}
+
+/// [`fetch_status`] along with the size of the body in bytes.
+pub(crate) async fn fetch_status_sized(
+    client: &reqwest::Client,
+    host: &str,
+    statement_handle: &str,
+    null_sentinel: &Arc<str>,
+) -> Result<(StatementOutcome, usize), SnowflakeError> {
+    fetch_url_sized(client, &format!("{host}statements/{statement_handle}"), null_sentinel).await
+}
+
+/// [`fetch_status_sized`] of the statement whose status is at `url`.
+async fn fetch_url_sized(client: &reqwest::Client, url: &str, null_sentinel: &Arc<str>) -> Result<(StatementOutcome, usize), SnowflakeError> {
+    let response = client
+        .get(url)
+        .send().await
+        .map_err(|e| SnowflakeError::SqlExecution(e.into()))?;
+    parse_outcome_sized(response, null_sentinel).await
+}
+
+/// Cancel an already submitted statement.
+pub(crate) async fn cancel(
+    client: &reqwest::Client,
+    host: &str,
+    statement_handle: &str,
+) -> Result<(), SnowflakeError> {
+    client
+        .post(format!("{host}statements/{statement_handle}/cancel"))
+        .send().await
+        .map_err(|e| SnowflakeError::SqlExecution(e.into()))?
+        .error_for_status()
+        .map_err(|e| SnowflakeError::SqlExecution(e.into()))?;
+    Ok(())
+}
+
+/// Outcome of a statement given the response to it, `NULL` cells of its rows are `null_sentinel`.
+pub(crate) async fn parse_outcome(response: reqwest::Response, null_sentinel: &Arc<str>) -> Result<StatementOutcome, SnowflakeError> {
+    Ok(parse_outcome_sized(response, null_sentinel).await?.0)
+}
+
+/// [`parse_outcome`] along with the size of the body in bytes.
+pub(crate) async fn parse_outcome_sized(response: reqwest::Response, null_sentinel: &Arc<str>) -> Result<(StatementOutcome, usize), SnowflakeError> {
+    match response.status() {
+        StatusCode::OK => read_json(response, null_sentinel).await
+            .map(|(response, bytes)| (StatementOutcome::Success(Box::new(response)), bytes)),
+        StatusCode::ACCEPTED => read_json(response, null_sentinel).await
+            .map(|(status, bytes)| (StatementOutcome::Pending(status), bytes)),
+        StatusCode::REQUEST_TIMEOUT => read_json(response, null_sentinel).await
+            .map(|(status, bytes)| (StatementOutcome::TimedOut(status), bytes)),
+        StatusCode::UNPROCESSABLE_ENTITY => read_json(response, null_sentinel).await
+            .map(|(status, bytes)| (StatementOutcome::Failed(status), bytes)),
+        status => {
+            let body = response.text().await.unwrap_or_default();
+            Err(SnowflakeError::SqlExecution(anyhow::anyhow!("unexpected status {status}: {body}")))
+        },
+    }
+}
+
+impl SnowflakeConnector {
+    /// Poll a statement until it finished or `deadline` passed, cancelling it if it is still running then.
+    ///
+    /// Meant for statements that returned [`StatementOutcome::TimedOut`] or [`SnowflakeError::StatementTimedOut`],
+    /// returns the last outcome seen.
+    pub async fn cancel_or_wait(
+        &self,
+        statement_handle: &str,
+        deadline: Deadline,
+        backoff: Backoff,
+    ) -> Result<StatementOutcome, SnowflakeError> {
+        let client = self.client()?;
+        let mut attempt = 0;
+        loop {
+            let outcome = fetch_status(&client, &self.host, statement_handle, &self.null_sentinel).await?;
+            if !outcome.is_pending() {
+                return Ok(outcome);
+            }
+            if deadline.has_passed() {
+                cancel(&client, &self.host, statement_handle).await?;
+                return Ok(outcome);
+            }
+            tokio::time::sleep(backoff.delay(attempt).min(deadline.remaining())).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    fn failure(code: &str) -> QueryFailureStatus {
+        QueryFailureStatus {
+            code: code.into(),
+            sql_state: "57014".into(),
+            message: "SQL execution canceled".into(),
+            statement_handle: "handle".into(),
+            created_on: None,
+            statement_status_url: None,
+        }
+    }
+
+    #[test]
+    fn states() {
+        assert_eq!(StatementOutcome::Failed(failure("000604")).state(), StatementState::Aborted);
+        assert_eq!(StatementOutcome::Failed(failure("002003")).state(), StatementState::FailedWithError);
+        let status = QueryStatus {
+            code: "333334".into(),
+            sql_state: String::new(),
+            message: "Asynchronous execution in progress.".into(),
+            statement_handle: "handle".into(),
+            created_on: None,
+            statement_status_url: String::new(),
+        };
+        assert_eq!(status.state(), StatementState::Running);
+        let queued = QueryStatus { message: "Statement is queued.".into(), ..status };
+        assert_eq!(StatementOutcome::Pending(queued).state(), StatementState::Running, "messages are not parsed");
+        assert!(!StatementState::Running.is_finished());
+        assert!(StatementState::Aborted.is_finished());
+    }
+
+    #[tokio::test]
+    async fn follow_status_url() -> Result<(), anyhow::Error> {
+        assert_eq!(
+            resolve_url("https://account.snowflakecomputing.com/api/v2/", "/api/v2/statements/h"),
+            "https://account.snowflakecomputing.com/api/v2/statements/h",
+        );
+        assert_eq!(resolve_url("https://a/api/v2/", "https://b/api/v2/statements/h"), "https://b/api/v2/statements/h");
+
+        let snowflake = MockSnowflake::start().await;
+        let fixture = StatementFixture::new()
+            .column("ID", "fixed")
+            .row([Some("1")])
+            .partition()
+            .row([Some("2")]);
+        snowflake.mount(&fixture).await;
+        let connector = snowflake.connector();
+        let status = QueryStatus {
+            code: "333334".into(),
+            sql_state: String::new(),
+            message: "Asynchronous execution in progress.".into(),
+            statement_handle: fixture.statement_handle().into(),
+            created_on: None,
+            statement_status_url: format!("/api/v2/statements/{}", fixture.statement_handle()),
+        };
+        assert_eq!(status.fetch_status(&connector).await?.state(), StatementState::Success);
+        assert_eq!(status.fetch_result::<i64>(&connector).await?.data, [1, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn serialized_as_received() -> Result<(), anyhow::Error> {
+        let json = serde_json::to_value(failure("000604"))?;
+        assert_eq!(json["sqlState"], "57014");
+        assert_eq!(json["statementHandle"], "handle");
+        let parsed: QueryFailureStatus = serde_json::from_value(json)?;
+        assert_eq!(parsed.code, "000604");
+        Ok(())
+    }
+}
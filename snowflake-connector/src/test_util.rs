@@ -0,0 +1,389 @@
+//! Canned SQL API responses served by a [wiremock] server, for end-to-end tests without an account.
+//!
+//! ```no_run
+//! # async fn example() -> Result<(), snowflake_connector::errors::SnowflakeError> {
+//! use snowflake_connector::test_util::{MockSnowflake, StatementFixture};
+//!
+//! let snowflake = MockSnowflake::start().await;
+//! snowflake.mount(&StatementFixture::new()
+//!     .column("ID", "fixed")
+//!     .row(["1"])
+//!     .row(["2"])).await;
+//! let result = snowflake.connector()
+//!     .execute("DB", "WH")
+//!     .sql("SELECT ID FROM T")?
+//!     .select::<(u32,)>().await?;
+//! assert_eq!(result.data.len(), 2);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::{Arc, OnceLock};
+
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{method, path, query_param, query_param_is_missing},
+    Mock, MockServer, ResponseTemplate,
+};
+
+use snowflake_deserializer::null;
+
+use crate::{pool::PoolConfig, tls::TlsConfig, SnowflakeConnector};
+
+pub use wiremock;
+
+pub const STATEMENT_HANDLE: &str = "01b0f2a1-0000-0000-0000-000000000001";
+pub const REQUEST_ID: &str = "00000000-0000-0000-0000-000000000000";
+
+/// Result of a statement, split into any number of partitions.
+#[derive(Clone, Debug)]
+pub struct StatementFixture {
+    statement_handle: String,
+    child_handles: Vec<String>,
+    row_type: Vec<Value>,
+    partitions: Vec<Vec<Value>>,
+}
+
+impl Default for StatementFixture {
+    fn default() -> Self {
+        StatementFixture {
+            statement_handle: STATEMENT_HANDLE.into(),
+            child_handles: Vec::new(),
+            row_type: Vec::new(),
+            partitions: vec![Vec::new()],
+        }
+    }
+}
+
+impl StatementFixture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn with_statement_handle(mut self, statement_handle: impl Into<String>) -> Self {
+        self.statement_handle = statement_handle.into();
+        self
+    }
+    pub fn statement_handle(&self) -> &str {
+        &self.statement_handle
+    }
+    /// Handles of the child statements of a multi-statement request.
+    pub fn with_child_handles<H: Into<String>>(mut self, child_handles: impl IntoIterator<Item = H>) -> Self {
+        self.child_handles = child_handles.into_iter().map(Into::into).collect();
+        self
+    }
+    /// Add a nullable column, `data_type` as Snowflake reports it, ex. `fixed`, `text` or `timestamp_ntz`.
+    pub fn column(mut self, name: &str, data_type: &str) -> Self {
+        self.row_type.push(json!({
+            "name": name,
+            "database": "DB",
+            "schema": "PUBLIC",
+            "table": "T",
+            "precision": null,
+            "byteLength": null,
+            "type": data_type,
+            "scale": null,
+            "nullable": true,
+        }));
+        self
+    }
+    /// Add a row to the last partition, `None` cells are `NULL`.
+    pub fn row<I: IntoIterator<Item = C>, C: Into<Value>>(mut self, cells: I) -> Self {
+        let row = cells.into_iter().map(Into::into).collect();
+        self.partitions.last_mut().unwrap().push(Value::Array(row));
+        self
+    }
+    /// Start a new partition, rows added afterwards are only returned when it is fetched.
+    pub fn partition(mut self) -> Self {
+        self.partitions.push(Vec::new());
+        self
+    }
+    /// HTTP 200 with the first partition.
+    pub fn success(&self) -> ResponseTemplate {
+        let partition_info: Vec<Value> = self.partitions.iter()
+            .map(|rows| json!({ "rowCount": rows.len(), "uncompressedSize": 0 }))
+            .collect();
+        let mut body = json!({
+            "resultSetMetaData": {
+                "numRows": self.partitions.iter().map(Vec::len).sum::<usize>(),
+                "format": "jsonv2",
+                "rowType": self.row_type,
+                "partitionInfo": partition_info,
+            },
+            "data": self.partitions[0],
+            "code": "090001",
+            "statementHandle": self.statement_handle,
+            "statementStatusUrl": self.status_url(),
+            "requestId": REQUEST_ID,
+            "sqlState": "00000",
+            "message": "Statement executed successfully.",
+        });
+        if !self.child_handles.is_empty() {
+            body["statementHandles"] = json!(self.child_handles);
+        }
+        ResponseTemplate::new(200).set_body_json(body)
+    }
+    /// HTTP 200 with partition `partition`, as returned by `GET statements/{handle}?partition=`.
+    pub fn partition_response(&self, partition: usize) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({ "data": self.partitions[partition] }))
+    }
+    /// HTTP 202, the statement is still executing.
+    pub fn pending(&self) -> ResponseTemplate {
+        ResponseTemplate::new(202).set_body_json(json!({
+            "code": "333334",
+            "message": "Asynchronous execution in progress. Use provided query id to perform query monitoring and management.",
+            "statementHandle": self.statement_handle,
+            "statementStatusUrl": self.status_url(),
+        }))
+    }
+    /// HTTP 422, the statement failed with `code`, ex. `002003` for a missing object.
+    pub fn failure(&self, code: &str, message: &str) -> ResponseTemplate {
+        ResponseTemplate::new(422).set_body_json(json!({
+            "code": code,
+            "sqlState": "42S02",
+            "message": message,
+            "statementHandle": self.statement_handle,
+            "statementStatusUrl": self.status_url(),
+        }))
+    }
+    fn status_url(&self) -> String {
+        format!("/api/v2/statements/{}", self.statement_handle)
+    }
+}
+
+/// A wiremock server standing in for the SQL API.
+pub struct MockSnowflake {
+    pub server: MockServer,
+}
+
+impl MockSnowflake {
+    pub async fn start() -> Self {
+        MockSnowflake { server: MockServer::start().await }
+    }
+    /// Connector sending its requests to the server, no key pair needed.
+    pub fn connector(&self) -> SnowflakeConnector {
+        SnowflakeConnector {
+            token: "test".into(),
+            host: format!("{}/api/v2/", self.server.uri()).into(),
+            in_flight: Arc::default(),
+            tls: TlsConfig::default(),
+            pool: PoolConfig::default(),
+            client: OnceLock::new(),
+            deny_interpolation: false,
+            read_only: false,
+            default_database: None,
+            default_warehouse: None,
+            routing: Arc::default(),
+            profiles: Arc::default(),
+            partition_policy: Default::default(),
+            redaction: Default::default(),
+            row_filter: Default::default(),
+            cost_limits: None,
+            null_sentinel: null::DEFAULT_SENTINEL.into(),
+            default_secondary_roles: None,
+            session: Default::default(),
+        }
+    }
+    /// Statements succeed with `fixture`, every partition can be fetched.
+    pub async fn mount(&self, fixture: &StatementFixture) {
+        self.mount_statement(fixture.success()).await;
+        self.mount_status(fixture, fixture.success()).await;
+        self.mount_partitions(fixture).await;
+    }
+    /// Statements are still executing for the first `polls` status requests, then succeed with `fixture`.
+    pub async fn mount_pending_then_success(&self, fixture: &StatementFixture, polls: u64) {
+        self.mount_statement(fixture.pending()).await;
+        Mock::given(method("GET"))
+            .and(path(format!("/api/v2/statements/{}", fixture.statement_handle)))
+            .and(query_param_is_missing("partition"))
+            .respond_with(fixture.pending())
+            .up_to_n_times(polls)
+            .with_priority(1)
+            .mount(&self.server).await;
+        self.mount_status(fixture, fixture.success()).await;
+        self.mount_partitions(fixture).await;
+    }
+    /// Statements fail with `code` and `message`.
+    pub async fn mount_failure(&self, fixture: &StatementFixture, code: &str, message: &str) {
+        self.mount_statement(fixture.failure(code, message)).await;
+        self.mount_status(fixture, fixture.failure(code, message)).await;
+    }
+    async fn mount_statement(&self, response: ResponseTemplate) {
+        Mock::given(method("POST"))
+            .and(path("/api/v2/statements"))
+            .respond_with(response)
+            .mount(&self.server).await;
+    }
+    async fn mount_status(&self, fixture: &StatementFixture, response: ResponseTemplate) {
+        Mock::given(method("GET"))
+            .and(path(format!("/api/v2/statements/{}", fixture.statement_handle)))
+            .and(query_param_is_missing("partition"))
+            .respond_with(response)
+            .mount(&self.server).await;
+    }
+    async fn mount_partitions(&self, fixture: &StatementFixture) {
+        for partition in 1..fixture.partitions.len() {
+            Mock::given(method("GET"))
+                .and(path(format!("/api/v2/statements/{}", fixture.statement_handle)))
+                .and(query_param("partition", partition.to_string()))
+                .respond_with(fixture.partition_response(partition))
+                .mount(&self.server).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::SnowflakeError;
+
+    fn fixture() -> StatementFixture {
+        StatementFixture::new()
+            .column("ID", "fixed")
+            .column("NAME", "text")
+            .row([Some("1"), Some("a")])
+            .row([Some("2"), None])
+    }
+
+    #[tokio::test]
+    async fn success() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&fixture()).await;
+        let result = snowflake.connector()
+            .execute("DB", "WH")
+            .sql("SELECT ID, NAME FROM T")?
+            .select::<(u32, Option<String>)>().await?;
+        assert_eq!(result.data, vec![(1, Some("a".into())), (2, None)]);
+        assert_eq!(result.statement_handle, STATEMENT_HANDLE);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn partitions() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&fixture().partition().row([Some("3"), Some("c")])).await;
+        let result = snowflake.connector()
+            .execute("DB", "WH")
+            .sql("SELECT ID, NAME FROM T")?
+            .select::<(u32, Option<String>)>().await?;
+        assert_eq!(result.data.len(), 3);
+        assert_eq!(result.data[2], (3, Some("c".into())));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn partition_retry() -> Result<(), anyhow::Error> {
+        use std::time::Duration;
+
+        use crate::{partitions::PartitionFetchPolicy, polling::Backoff};
+
+        let snowflake = MockSnowflake::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/api/v2/statements/{STATEMENT_HANDLE}")))
+            .and(query_param("partition", "1"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(3)
+            .with_priority(1)
+            .mount(&snowflake.server).await;
+        snowflake.mount(&fixture().partition().row([Some("3"), Some("c")])).await;
+        let policy = |retries| PartitionFetchPolicy {
+            retries,
+            backoff: Backoff { initial: Duration::ZERO, jitter: false, ..Default::default() },
+            timeout: Some(Duration::from_secs(5)),
+        };
+        let connector = snowflake.connector().with_partition_fetch_policy(policy(1));
+        let sql = || connector.execute("DB", "WH").sql("SELECT ID, NAME FROM T");
+        assert!(sql()?.select::<(u32, Option<String>)>().await.is_err());
+        assert!(sql()?.with_partition_fetch_policy(policy(0)).select::<(u32, Option<String>)>().await.is_err());
+        let result = sql()?
+            .select::<(u32, Option<String>)>().await?;
+        assert_eq!(result.data.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn interned() -> Result<(), anyhow::Error> {
+        use snowflake_deserializer::intern::{InternStats, Interned};
+
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&fixture().row([Some("3"), Some("a")]).partition().row([Some("4"), Some("a")])).await;
+        let (result, stats) = snowflake.connector()
+            .execute("DB", "WH")
+            .sql("SELECT ID, NAME FROM T")?
+            .select_interned::<(u32, Option<Interned>)>().await?;
+        assert_eq!(result.data.len(), 4);
+        assert_eq!(result.data[3].1.as_deref(), Some("a"));
+        assert_eq!(stats, InternStats { values: 3, unique: 1, bytes_saved: 2 });
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pending_then_success() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount_pending_then_success(&fixture(), 1).await;
+        let count = snowflake.connector()
+            .execute("DB", "WH")
+            .sql("SELECT ID, NAME FROM T")?
+            .select::<(u32, Option<String>)>().await?
+            .data.len();
+        assert_eq!(count, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn null_sentinel() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&fixture().row([Some("3"), Some("null")]).partition().row([Some("4"), Some("null")])).await;
+        let select = |connector: SnowflakeConnector| async move {
+            connector.execute("DB", "WH")
+                .sql("SELECT ID, NAME FROM T")?
+                .select::<(u32, Option<String>)>().await
+                .map(|result| result.data.into_iter().map(|(_, name)| name).collect::<Vec<_>>())
+        };
+        assert_eq!(select(snowflake.connector()).await?, [Some("a".into()), None, None, None]);
+        let literal = Some("null".to_owned());
+        assert_eq!(select(snowflake.connector().with_null_sentinel("\u{1}NULL")).await?, [Some("a".into()), None, literal.clone(), literal]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn pending_past_poll_timeout() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount_pending_then_success(&fixture(), u64::MAX).await;
+        let error = snowflake.connector()
+            .execute("DB", "WH")
+            .sql("SELECT ID, NAME FROM T")?
+            .with_poll_timeout(std::time::Duration::from_millis(100))
+            .select::<(u32, Option<String>)>().await
+            .unwrap_err();
+        assert!(matches!(error, SnowflakeError::StatementTimedOut(status) if status.statement_handle == STATEMENT_HANDLE));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn raw() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount_failure(&fixture(), "002003", "Object 'T' does not exist or not authorized.").await;
+        let response = snowflake.connector()
+            .execute("DB", "WH")
+            .sql("SELECT ID, NAME FROM T")?
+            .raw().await?;
+        assert_eq!(response.status(), 422);
+        let body: Value = response.json().await?;
+        assert_eq!(body["code"], "002003");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn failure() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount_failure(&fixture(), "002003", "Object 'T' does not exist or not authorized.").await;
+        let error = snowflake.connector()
+            .execute("DB", "WH")
+            .sql("SELECT ID, NAME FROM T")?
+            .select::<(u32, Option<String>)>().await
+            .unwrap_err();
+        assert!(matches!(error, SnowflakeError::StatementFailed(_)));
+        assert_eq!(error.code(), Some("002003"));
+        Ok(())
+    }
+}
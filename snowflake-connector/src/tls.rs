@@ -0,0 +1,65 @@
+//! TLS settings of the HTTP client, for ex. testing against an emulator with a self-signed certificate.
+
+use std::{fs, path::Path};
+
+use anyhow::Context;
+use reqwest::{Certificate, ClientBuilder};
+
+use crate::{errors::SnowflakeError, SnowflakeConnector};
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TlsConfig {
+    root_certificates: Vec<Certificate>,
+    #[cfg(feature = "danger-accept-invalid-certs")]
+    accept_invalid_certs: bool,
+}
+
+impl TlsConfig {
+    pub(crate) fn apply(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        #[cfg(feature = "danger-accept-invalid-certs")]
+        {
+            builder = builder.danger_accept_invalid_certs(self.accept_invalid_certs);
+        }
+        builder
+    }
+}
+
+impl SnowflakeConnector {
+    /// Trust the PEM encoded certificate at `path` in addition to the system roots.
+    pub fn add_root_certificate<P: AsRef<Path>>(mut self, path: P) -> Result<Self, SnowflakeError> {
+        let path = path.as_ref();
+        let certificate = fs::read(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|pem| Certificate::from_pem(&pem).map_err(anyhow::Error::from))
+            .with_context(|| format!("could not load certificate {}", path.display()))
+            .map_err(SnowflakeError::SqlClient)?;
+        self.tls.root_certificates.push(certificate);
+        self.reset_client();
+        Ok(self)
+    }
+    /// Accept any certificate the server presents, including expired, self-signed and ones for another host.
+    ///
+    /// Only meant for testing against local emulators, anyone on the network path can read and change the traffic.
+    #[cfg(feature = "danger-accept-invalid-certs")]
+    pub fn danger_accept_invalid_certs(mut self, accept: bool) -> Self {
+        self.tls.accept_invalid_certs = accept;
+        self.reset_client();
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::MockSnowflake;
+
+    #[tokio::test]
+    async fn missing_certificate() -> Result<(), anyhow::Error> {
+        let connector = MockSnowflake::start().await.connector();
+        let error = connector.add_root_certificate("./environment_variables/local/missing.pem").unwrap_err();
+        assert!(format!("{:#}", anyhow::Error::from(error)).contains("missing.pem"));
+        Ok(())
+    }
+}
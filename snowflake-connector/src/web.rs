@@ -0,0 +1,124 @@
+//! Glue for axum services, an extractor for the shared connector and a health check handler.
+//!
+//! ```no_run
+//! # use std::{sync::Arc, time::Duration};
+//! # use axum::{routing::get, Router};
+//! # use snowflake_connector::{web::{HealthCheck, Snowflake}, SnowflakeConnector};
+//! async fn count(snowflake: Snowflake) -> String {
+//!     let count = snowflake.execute("DB", "WH").sql("SELECT COUNT(*) FROM USERS").unwrap()
+//!         .select_scalar::<u64>().await;
+//!     format!("{count:?}")
+//! }
+//!
+//! # async fn example(connector: SnowflakeConnector, shutdown: impl std::future::Future<Output = ()> + Send + 'static) -> Result<(), Box<dyn std::error::Error>> {
+//! let connector = Arc::new(connector);
+//! let app: Router = Router::new()
+//!     .route("/count", get(count))
+//!     .route("/healthz", HealthCheck::new(connector.clone(), "WH").route())
+//!     .with_state(connector.clone());
+//! // Serve `app` with the graceful shutdown of axum, then wait for statements still in flight.
+//! # shutdown.await;
+//! connector.shutdown(Duration::from_secs(30)).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{convert::Infallible, ops::Deref, sync::Arc, time::Duration};
+
+use axum::{
+    extract::{FromRef, FromRequestParts},
+    http::{request::Parts, StatusCode},
+    routing::{get, MethodRouter},
+};
+
+use crate::{errors::SnowflakeError, SnowflakeConnector};
+
+/// Time the health check has to answer by default.
+pub const DEFAULT_HEALTH_CHECK_DEADLINE: Duration = Duration::from_secs(5);
+
+/// Connector of the state, which must provide an `Arc<SnowflakeConnector>`.
+#[derive(Clone, Debug)]
+pub struct Snowflake(pub Arc<SnowflakeConnector>);
+
+impl<S: Send + Sync> FromRequestParts<S> for Snowflake where Arc<SnowflakeConnector>: FromRef<S> {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(_: &mut Parts, state: &S) -> Result<Self, Infallible> {
+        Ok(Snowflake(Arc::from_ref(state)))
+    }
+}
+
+impl Deref for Snowflake {
+    type Target = SnowflakeConnector;
+    fn deref(&self) -> &SnowflakeConnector {
+        &self.0
+    }
+}
+
+/// Runs `SELECT 1` on a warehouse, answering `200 OK` if it succeeded within the deadline
+/// and `503 Service Unavailable` with the error otherwise.
+#[derive(Clone, Debug)]
+pub struct HealthCheck {
+    connector: Arc<SnowflakeConnector>,
+    warehouse: String,
+    deadline: Duration,
+}
+
+impl HealthCheck {
+    pub fn new(connector: Arc<SnowflakeConnector>, warehouse: impl Into<String>) -> Self {
+        HealthCheck {
+            connector,
+            warehouse: warehouse.into(),
+            deadline: DEFAULT_HEALTH_CHECK_DEADLINE,
+        }
+    }
+    /// Fail the check if Snowflake did not answer within `deadline`, [`DEFAULT_HEALTH_CHECK_DEADLINE`] by default.
+    pub fn with_deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = deadline;
+        self
+    }
+    pub async fn check(&self) -> Result<(), SnowflakeError> {
+        let select = self.connector.execute_on(&self.warehouse)
+            .sql("SELECT 1")?
+            .select_scalar::<i64>();
+        match tokio::time::timeout(self.deadline, select).await {
+            Ok(result) => result.map(drop),
+            Err(_) => Err(SnowflakeError::SqlExecution(anyhow::anyhow!(
+                "health check did not finish within {:?}", self.deadline,
+            ))),
+        }
+    }
+    /// `GET` handler answering with the outcome of [`check`](Self::check), ex. for `/healthz`.
+    pub fn route<S: Clone + Send + Sync + 'static>(self) -> MethodRouter<S> {
+        get(move || async move {
+            match self.check().await {
+                Ok(()) => (StatusCode::OK, "ok".to_owned()),
+                Err(e) => (StatusCode::SERVICE_UNAVAILABLE, e.to_string()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::Request;
+
+    use crate::test_util::{MockSnowflake, StatementFixture};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn health_check() -> Result<(), anyhow::Error> {
+        let snowflake = MockSnowflake::start().await;
+        snowflake.mount(&StatementFixture::new().column("1", "fixed").row([Some("1")])).await;
+        let connector = Arc::new(snowflake.connector());
+        let (mut parts, ()) = Request::new(()).into_parts();
+        let extracted = Snowflake::from_request_parts(&mut parts, &connector).await?;
+        assert!(Arc::ptr_eq(&extracted.0, &connector));
+
+        HealthCheck::new(connector.clone(), "WH").check().await?;
+        let error = HealthCheck::new(connector, "WH").with_deadline(Duration::ZERO).check().await.unwrap_err();
+        assert!(error.to_string().contains("health check did not finish"));
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
+use std::fmt::Display;
+
 use chrono::{NaiveDateTime, NaiveDate, NaiveTime};
 use rust_decimal::Decimal;
 
-#[derive(Clone, Debug)]
+use crate::encoding;
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum BindingValue {
     Bool(bool),
 
@@ -9,6 +13,7 @@ pub enum BindingValue {
     SmallInt(i16),
     Int(i32),
     BigInt(i64),
+    HugeInt(i128),
     ISize(isize),
 
     UByte(u8),
@@ -27,9 +32,18 @@ pub enum BindingValue {
     DateTime(NaiveDateTime),
     Date(NaiveDate),
     Time(NaiveTime),
+
+    /// `NULL`, ex. from `None`.
+    Null,
+}
+
+impl BindingValue {
+    pub fn is_null(&self) -> bool {
+        matches!(self, BindingValue::Null)
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BindingType {
     Bool,
     Fixed,
@@ -40,9 +54,9 @@ pub enum BindingType {
     Time,
 }
 
-impl ToString for BindingType {
-    fn to_string(&self) -> String {
-        match self {
+impl Display for BindingType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
             BindingType::Bool => "BOOLEAN",
             BindingType::Fixed => "FIXED",
             BindingType::Real => "REAL",
@@ -50,7 +64,7 @@ impl ToString for BindingType {
             BindingType::DateTime => "TIMESTAMP_NTZ",
             BindingType::Date => "DATE",
             BindingType::Time => "TIME",
-        }.into()
+        })
     }
 }
 
@@ -62,6 +76,7 @@ impl From<BindingValue> for BindingType {
             BindingValue::SmallInt(_) |
             BindingValue::Int(_) |
             BindingValue::BigInt(_) |
+            BindingValue::HugeInt(_) |
             BindingValue::ISize(_) |
             BindingValue::UByte(_) |
             BindingValue::SmallUInt(_) |
@@ -74,7 +89,8 @@ impl From<BindingValue> for BindingType {
             BindingValue::Decimal(_)
                 => BindingType::Real,
             BindingValue::Char(_) |
-            BindingValue::String(_)
+            BindingValue::String(_) |
+            BindingValue::Null
                 => BindingType::Text,
             BindingValue::DateTime(_) => BindingType::DateTime,
             BindingValue::Date(_) => BindingType::Date,
@@ -83,38 +99,84 @@ impl From<BindingValue> for BindingType {
     }
 }
 
-impl ToString for BindingValue {
-    fn to_string(&self) -> String {
+impl Display for BindingValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            BindingValue::Bool(value) => value.to_string(),
-            BindingValue::Byte(value) => value.to_string(),
-            BindingValue::SmallInt(value) => value.to_string(),
-            BindingValue::Int(value) => value.to_string(),
-            BindingValue::BigInt(value) => value.to_string(),
-            BindingValue::ISize(value) => value.to_string(),
-            BindingValue::UByte(value) => value.to_string(),
-            BindingValue::SmallUInt(value) => value.to_string(),
-            BindingValue::UInt(value) => value.to_string(),
-            BindingValue::BigUInt(value) => value.to_string(),
-            BindingValue::USize(value) => value.to_string(),
-            BindingValue::Float(value) => value.to_string(),
-            BindingValue::Double(value) => value.to_string(),
-            BindingValue::Decimal(value) => value.to_string(),
-            BindingValue::Char(value) => value.to_string(),
-            BindingValue::String(value) => value.to_string(),
-            BindingValue::DateTime(value) => value.timestamp_nanos().to_string(),
-            BindingValue::Date(value) => value.and_time(NaiveTime::default()).timestamp_millis().to_string(),
-            BindingValue::Time(value) => (Decimal::new(NaiveDate::default().and_time(*value).timestamp_nanos(), 0) / rust_decimal_macros::dec!(60)).to_string(),
+            BindingValue::Bool(value) => value.fmt(f),
+            BindingValue::Byte(value) => value.fmt(f),
+            BindingValue::SmallInt(value) => value.fmt(f),
+            BindingValue::Int(value) => value.fmt(f),
+            BindingValue::BigInt(value) => value.fmt(f),
+            BindingValue::HugeInt(value) => value.fmt(f),
+            BindingValue::ISize(value) => value.fmt(f),
+            BindingValue::UByte(value) => value.fmt(f),
+            BindingValue::SmallUInt(value) => value.fmt(f),
+            BindingValue::UInt(value) => value.fmt(f),
+            BindingValue::BigUInt(value) => value.fmt(f),
+            BindingValue::USize(value) => value.fmt(f),
+            BindingValue::Float(value) => value.fmt(f),
+            BindingValue::Double(value) => value.fmt(f),
+            BindingValue::Decimal(value) => value.fmt(f),
+            BindingValue::Char(value) => value.fmt(f),
+            BindingValue::String(value) => value.fmt(f),
+            BindingValue::DateTime(value) => encoding::encode_timestamp_ntz_nanos(*value).fmt(f),
+            BindingValue::Date(value) => encoding::encode_date(*value).fmt(f),
+            BindingValue::Time(value) => encoding::encode_time_nanos(*value).fmt(f),
+            BindingValue::Null => f.write_str("NULL"),
         }
     }
 }
 
+impl<T: Into<BindingValue>> From<Option<T>> for BindingValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(BindingValue::Null, Into::into)
+    }
+}
+
 impl From<&str> for BindingValue {
     fn from(value: &str) -> Self {
         BindingValue::String(value.to_owned())
     }
 }
 
+/// Values bound to the `?` of a statement in order, ex. `[1, 2]`, `(1, "a")` or `()` for none.
+pub trait IntoBindings {
+    fn into_bindings(self) -> Vec<BindingValue>;
+}
+
+impl IntoBindings for Vec<BindingValue> {
+    fn into_bindings(self) -> Vec<BindingValue> {
+        self
+    }
+}
+
+impl<T: Into<BindingValue>, const N: usize> IntoBindings for [T; N] {
+    fn into_bindings(self) -> Vec<BindingValue> {
+        self.into_iter().map(Into::into).collect()
+    }
+}
+
+macro_rules! impl_into_bindings_tuple {
+    ($($ty: ident),*) => {
+        impl<$($ty: Into<BindingValue>),*> IntoBindings for ($($ty,)*) {
+            #[allow(non_snake_case)]
+            fn into_bindings(self) -> Vec<BindingValue> {
+                let ($($ty,)*) = self;
+                vec![$($ty.into()),*]
+            }
+        }
+    };
+}
+impl_into_bindings_tuple!();
+impl_into_bindings_tuple!(A);
+impl_into_bindings_tuple!(A, B);
+impl_into_bindings_tuple!(A, B, C);
+impl_into_bindings_tuple!(A, B, C, D);
+impl_into_bindings_tuple!(A, B, C, D, E);
+impl_into_bindings_tuple!(A, B, C, D, E, F);
+impl_into_bindings_tuple!(A, B, C, D, E, F, G);
+impl_into_bindings_tuple!(A, B, C, D, E, F, G, H);
+
 macro_rules! impl_from_binding_value {
     ($ty: ty, $ex: expr) => {
         impl From<$ty> for BindingValue {
@@ -129,6 +191,7 @@ impl_from_binding_value!(i8, BindingValue::Byte);
 impl_from_binding_value!(i16, BindingValue::SmallInt);
 impl_from_binding_value!(i32, BindingValue::Int);
 impl_from_binding_value!(i64, BindingValue::BigInt);
+impl_from_binding_value!(i128, BindingValue::HugeInt);
 impl_from_binding_value!(isize, BindingValue::ISize);
 impl_from_binding_value!(u8, BindingValue::UByte);
 impl_from_binding_value!(u16, BindingValue::SmallUInt);
@@ -143,3 +206,63 @@ impl_from_binding_value!(String, BindingValue::String);
 impl_from_binding_value!(NaiveDateTime, BindingValue::DateTime);
 impl_from_binding_value!(NaiveDate, BindingValue::Date);
 impl_from_binding_value!(NaiveTime, BindingValue::Time);
+
+/// Values bound to `:name` placeholders, created with [`named_bindings!`](crate::named_bindings).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NamedBindings(Vec<(String, BindingValue)>);
+
+impl NamedBindings {
+    pub fn new() -> Self {
+        NamedBindings::default()
+    }
+    /// Bind `value` to `:name`, replacing the value it had.
+    pub fn insert<N: Into<String>, V: Into<BindingValue>>(&mut self, name: N, value: V) {
+        let name = name.into();
+        let value = value.into();
+        match self.0.iter_mut().find(|(bound, _)| *bound == name) {
+            Some((_, bound)) => *bound = value,
+            None => self.0.push((name, value)),
+        }
+    }
+    /// Position of `name` among the bindings, starting at 0.
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.0.iter().position(|(bound, _)| bound == name)
+    }
+    pub fn values(&self) -> impl Iterator<Item = &BindingValue> {
+        self.0.iter().map(|(_, value)| value)
+    }
+    pub fn into_values(self) -> Vec<BindingValue> {
+        self.0.into_iter().map(|(_, value)| value).collect()
+    }
+}
+
+/// Bindings in order, `None` binds `NULL` and each value's type picks the binding type.
+///
+/// ```
+/// # use snowflake_deserializer::{bindings, bindings::BindingValue};
+/// let name: Option<&str> = None;
+/// assert_eq!(bindings![1, "a", name], [BindingValue::Int(1), BindingValue::String("a".into()), BindingValue::Null]);
+/// ```
+#[macro_export]
+macro_rules! bindings {
+    ($($value:expr),* $(,)?) => {
+        ::std::vec![$($crate::bindings::BindingValue::from($value)),*]
+    };
+}
+
+/// [`NamedBindings`] for `:name` placeholders, same conversions as [`bindings!`](crate::bindings).
+///
+/// ```
+/// # use snowflake_deserializer::named_bindings;
+/// let bindings = named_bindings! { "id" => 1, "name" => Some("a") };
+/// assert_eq!(bindings.position("name"), Some(1));
+/// ```
+#[macro_export]
+macro_rules! named_bindings {
+    ($($name:expr => $value:expr),* $(,)?) => {{
+        #[allow(unused_mut)]
+        let mut bindings = $crate::bindings::NamedBindings::new();
+        $(bindings.insert($name, $value);)*
+        bindings
+    }};
+}
@@ -0,0 +1,395 @@
+//! Rust structs for the tables of a [`Schema`], ex. written by a build script or committed to `src`.
+//!
+//! Every table becomes a struct deriving `SnowflakeDeserialize` with its columns in order,
+//! comments of tables, views and columns become doc comments.
+//! [`Layout::SingleFile`] writes all of them to [`Generator::SINGLE_FILE_NAME`],
+//! [`Layout::ModulePerTable`] writes a `mod.rs` with a module per database and schema
+//! and a file per table, so changes to one table only touch its file and tables compile in parallel.
+//!
+//! From a build script, [`Generator::build`] writes the structs to `OUT_DIR`:
+//!
+//! ```no_run
+//! // build.rs
+//! fn main() -> Result<(), anyhow::Error> {
+//!     snowflake_deserializer::codegen::Generator::new().build()
+//! }
+//! ```
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/snowflake_tables.rs"));
+//! ```
+//!
+//! Tables are sorted by name, so the output only changes when the schema does.
+//! [`Generator::write`] keeps a hash of each table in [`Generator::CACHE_FILE_NAME`] and only rewrites
+//! the files of tables that changed, so the others keep their modification time and are not rebuilt.
+
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap},
+    fmt::Write,
+    fs,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use crate::{coercion::ColumnType, schema::{Schema, TableSchema}};
+
+/// How the generated code is split into files.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// Every struct in one file, ex. included from `OUT_DIR` with `include!`.
+    #[default]
+    SingleFile,
+    /// `mod.rs` declaring `<database>/<schema>/<table>.rs`, ex. written to `src/tables` and declared with `mod tables;`.
+    ModulePerTable,
+}
+
+/// File to write, `path` is relative to the output directory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub contents: String,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Generator {
+    layout: Layout,
+    serde: bool,
+}
+
+impl Generator {
+    pub const SINGLE_FILE_NAME: &'static str = "snowflake_tables.rs";
+    pub const HEADER: &'static str = "// Generated from the Snowflake schema file, do not edit.\n";
+    /// Hash of the source of each file written to the output directory, as JSON.
+    pub const CACHE_FILE_NAME: &'static str = ".snowflake_tables.json";
+
+    pub fn new() -> Self {
+        Generator::default()
+    }
+    pub fn layout(mut self, layout: Layout) -> Self {
+        self.layout = layout;
+        self
+    }
+    /// Also derive `serde::Serialize` and `serde::Deserialize`, fields are renamed to their column.
+    pub fn serde(mut self, serde: bool) -> Self {
+        self.serde = serde;
+        self
+    }
+    /// Files for the tables of `schema`, sorted by path.
+    pub fn generate(&self, schema: &Schema) -> Result<Vec<GeneratedFile>, anyhow::Error> {
+        Ok(self.files(schema)?.into_iter().map(|(file, _)| file).collect())
+    }
+    /// Write the files of [`generate`](Self::generate) to `directory`, returns the paths written.
+    ///
+    /// Files whose tables did not change since the last call are skipped,
+    /// files of tables no longer in `schema` are removed.
+    pub fn write<P: AsRef<Path>>(&self, schema: &Schema, directory: P) -> Result<Vec<PathBuf>, anyhow::Error> {
+        let directory = directory.as_ref();
+        let cache_path = directory.join(Self::CACHE_FILE_NAME);
+        // A cache that can not be read only means everything is written again.
+        let previous: BTreeMap<PathBuf, u64> = fs::read_to_string(&cache_path).ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        let mut cache = BTreeMap::new();
+        let mut written = Vec::new();
+        for (file, hash) in self.files(schema)? {
+            let path = directory.join(&file.path);
+            if previous.get(&file.path) != Some(&hash) || !path.exists() {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)
+                        .with_context(|| format!("could not create {}", parent.display()))?;
+                }
+                fs::write(&path, file.contents)
+                    .with_context(|| format!("could not write {}", path.display()))?;
+                written.push(path);
+            }
+            cache.insert(file.path, hash);
+        }
+        for stale in previous.keys().filter(|path| !cache.contains_key(*path)) {
+            let path = directory.join(stale);
+            match fs::remove_file(&path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    return Err(e).with_context(|| format!("could not remove {}", path.display()));
+                },
+                _ => {},
+            }
+        }
+        let json = serde_json::to_string_pretty(&cache).expect("cache is always valid json");
+        fs::write(&cache_path, json)
+            .with_context(|| format!("could not write {}", cache_path.display()))?;
+        Ok(written)
+    }
+    /// Generate from a build script, the schema file of [`Schema::path`] is written to `OUT_DIR`.
+    ///
+    /// Cargo is told to only run the build script again when the schema file or [`Schema::PATH_VARIABLE`] change.
+    /// Snowflake is never queried, so builds need no credentials, refresh the schema file with `fetch_schema`.
+    pub fn build(&self) -> Result<(), anyhow::Error> {
+        println!("cargo:rerun-if-env-changed={}", Schema::PATH_VARIABLE);
+        let path = Schema::path();
+        println!("cargo:rerun-if-changed={}", path.display());
+        let schema = Schema::read(&path)?;
+        let out_dir = std::env::var_os("OUT_DIR")
+            .context("OUT_DIR is not set, `build` is meant to be called from a build script")?;
+        self.write(&schema, out_dir)?;
+        Ok(())
+    }
+    /// Files sorted by path, along with a hash of what they are generated from.
+    fn files(&self, schema: &Schema) -> Result<Vec<(GeneratedFile, u64)>, anyhow::Error> {
+        let mut tables: Vec<&TableSchema> = schema.tables.iter().collect();
+        tables.sort_by(|a, b| a.name.cmp(&b.name));
+        let mut files = match self.layout {
+            Layout::SingleFile => vec![single_file(self, &tables)?],
+            Layout::ModulePerTable => module_per_table(self, &tables)?,
+        };
+        files.sort_by(|(a, _), (b, _)| a.path.cmp(&b.path));
+        Ok(files)
+    }
+}
+
+/// Hash of `source` and the version of the generator, so upgrading it regenerates every file.
+fn hash<T: Hash + ?Sized>(source: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    source.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn single_file(generator: &Generator, tables: &[&TableSchema]) -> Result<(GeneratedFile, u64), anyhow::Error> {
+    let mut contents = format!("{}\nuse snowflake_connector::*;\n", Generator::HEADER);
+    let mut names: BTreeMap<String, &str> = BTreeMap::new();
+    for table in tables {
+        let name = struct_name(&table.name);
+        if let Some(other) = names.insert(name.clone(), &table.name) {
+            anyhow::bail!("{} and {other} are both named {name}, use Layout::ModulePerTable", table.name);
+        }
+        contents.push('\n');
+        contents.push_str(&table_struct(generator, &name, table));
+    }
+    Ok((GeneratedFile { path: Generator::SINGLE_FILE_NAME.into(), contents }, hash(&(generator.serde, tables))))
+}
+
+/// `mod.rs` of a directory.
+#[derive(Default)]
+struct Module {
+    children: Vec<String>,
+    /// Module of each table along with its struct.
+    reexports: Vec<(String, String)>,
+}
+
+fn module_per_table(generator: &Generator, tables: &[&TableSchema]) -> Result<Vec<(GeneratedFile, u64)>, anyhow::Error> {
+    let mut modules: BTreeMap<PathBuf, Module> = BTreeMap::new();
+    let mut files = Vec::new();
+    for table in tables {
+        let parts: Vec<&str> = table.name.split('.').collect();
+        let [database, schema, name] = parts[..] else {
+            anyhow::bail!("{} is not named DB.SCHEMA.TABLE", table.name);
+        };
+        let (database, schema, module) = (identifier(database), identifier(schema), identifier(name));
+        let struct_name = struct_name(&table.name);
+        let directory = PathBuf::from(&database).join(&schema);
+        for (parent, child) in [(PathBuf::new(), &database), (PathBuf::from(&database), &schema)] {
+            let children = &mut modules.entry(parent).or_default().children;
+            if !children.contains(child) {
+                children.push(child.clone());
+            }
+        }
+        let Module { children, reexports } = modules.entry(directory.clone()).or_default();
+        if children.contains(&module) {
+            anyhow::bail!("more than one table of {database}.{schema} is named {module}");
+        }
+        children.push(module.clone());
+        reexports.push((module.clone(), struct_name.clone()));
+        files.push((GeneratedFile {
+            path: directory.join(format!("{module}.rs")),
+            contents: format!("{}\nuse snowflake_connector::*;\n\n{}", Generator::HEADER, table_struct(generator, &struct_name, table)),
+        }, hash(&(generator.serde, table))));
+    }
+    for (directory, Module { children, reexports }) in modules {
+        let mut contents = Generator::HEADER.to_owned();
+        contents.push('\n');
+        for child in children {
+            writeln!(contents, "pub mod {child};").unwrap();
+        }
+        if !reexports.is_empty() {
+            contents.push('\n');
+            for (module, struct_name) in reexports {
+                writeln!(contents, "pub use {module}::{struct_name};").unwrap();
+            }
+        }
+        let source = hash(&contents);
+        files.push((GeneratedFile { path: directory.join("mod.rs"), contents }, source));
+    }
+    Ok(files)
+}
+
+fn table_struct(generator: &Generator, name: &str, table: &TableSchema) -> String {
+    let mut code = String::new();
+    doc_comment(&mut code, "", table.comment.as_deref());
+    if generator.serde {
+        writeln!(code, "#[derive(SnowflakeDeserialize, serde::Serialize, serde::Deserialize, Clone, Debug)]").unwrap();
+    } else {
+        writeln!(code, "#[derive(SnowflakeDeserialize, Clone, Debug)]").unwrap();
+    }
+    writeln!(code, "#[snowflake(table = {:?})]", table.name).unwrap();
+    writeln!(code, "pub struct {name} {{").unwrap();
+    for column in &table.columns {
+        let rust_type = ColumnType::from_declared(&column.data_type).rust_type();
+        let rust_type = if column.nullable { format!("Option<{rust_type}>") } else { rust_type.to_owned() };
+        doc_comment(&mut code, "    ", column.comment.as_deref());
+        writeln!(code, "    #[snowflake(rename = {:?}, data_type = {:?})]", column.name, column.data_type).unwrap();
+        if generator.serde {
+            writeln!(code, "    #[serde(rename = {:?})]", column.name).unwrap();
+        }
+        writeln!(code, "    pub {}: {rust_type},", identifier(&column.name)).unwrap();
+    }
+    writeln!(code, "}}").unwrap();
+    code
+}
+
+/// `///` line for every line of `comment`, nothing for empty comments.
+fn doc_comment(code: &mut String, indent: &str, comment: Option<&str>) {
+    for line in comment.unwrap_or_default().trim().lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            writeln!(code, "{indent}///").unwrap();
+        } else {
+            writeln!(code, "{indent}/// {line}").unwrap();
+        }
+    }
+}
+
+/// Lowercase identifier, characters not allowed in an identifier become `_`, keywords get a trailing `_`.
+fn identifier(name: &str) -> String {
+    let mut identifier: String = name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if !identifier.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        identifier.insert(0, '_');
+    }
+    if KEYWORDS.contains(&identifier.as_str()) {
+        identifier.push('_');
+    }
+    identifier
+}
+
+/// Last part of the table name in `UpperCamelCase`, ex. `ORDER_ITEMS` is `OrderItems`.
+fn struct_name(table: &str) -> String {
+    let table = table.rsplit('.').next().unwrap_or(table).trim_matches('"');
+    let mut name: String = table.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().map(|c| c.to_ascii_uppercase()).into_iter();
+            first.chain(chars.map(|c| c.to_ascii_lowercase())).collect::<String>()
+        })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        name.insert(0, 'T');
+    }
+    name
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn",
+    "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "static",
+    "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+];
+
+#[cfg(test)]
+mod tests {
+    use crate::schema::ColumnSchema;
+
+    use super::*;
+
+    fn schema() -> Schema {
+        let table = |name: &str, columns: &[(&str, &str, bool)]| TableSchema {
+            name: name.into(),
+            columns: columns.iter()
+                .map(|(name, data_type, nullable)| ColumnSchema {
+                    name: (*name).into(),
+                    data_type: (*data_type).into(),
+                    nullable: *nullable,
+                    comment: None,
+                })
+                .collect(),
+            comment: None,
+        };
+        Schema {
+            tables: vec![
+                table("DB.SALES.ORDER_ITEMS", &[("ID", "NUMBER(38,0)", false), ("AMOUNT", "NUMBER(12,2)", true)]),
+                table("DB.PUBLIC.USERS", &[("ID", "NUMBER(38,0)", false), ("TYPE", "TEXT", true)]),
+            ],
+        }
+    }
+
+    #[test]
+    fn single_file() -> Result<(), anyhow::Error> {
+        let files = Generator::new().generate(&schema())?;
+        assert_eq!(files.len(), 1);
+        let contents = &files[0].contents;
+        assert!(contents.find("pub struct Users").unwrap() < contents.find("pub struct OrderItems").unwrap());
+        assert!(contents.contains("#[snowflake(table = \"DB.SALES.ORDER_ITEMS\")]"));
+        assert!(contents.contains("    #[snowflake(rename = \"AMOUNT\", data_type = \"NUMBER(12,2)\")]\n    pub amount: Option<rust_decimal::Decimal>,"));
+        assert!(contents.contains("    pub type_: Option<String>,"));
+        Ok(())
+    }
+
+    #[test]
+    fn module_per_table() -> Result<(), anyhow::Error> {
+        let files = Generator::new().layout(Layout::ModulePerTable).generate(&schema())?;
+        let paths: Vec<&Path> = files.iter().map(|file| file.path.as_path()).collect();
+        assert_eq!(paths, [
+            Path::new("db/mod.rs"),
+            Path::new("db/public/mod.rs"),
+            Path::new("db/public/users.rs"),
+            Path::new("db/sales/mod.rs"),
+            Path::new("db/sales/order_items.rs"),
+            Path::new("mod.rs"),
+        ]);
+        assert!(files[0].contents.ends_with("pub mod public;\npub mod sales;\n"));
+        assert!(files[3].contents.ends_with("pub mod order_items;\n\npub use order_items::OrderItems;\n"));
+        assert!(files[4].contents.contains("pub struct OrderItems {"));
+
+        let mut duplicate = schema();
+        duplicate.tables.push(TableSchema { name: "OTHER.PUBLIC.USERS".into(), columns: Vec::new(), comment: None });
+        assert!(Generator::new().generate(&duplicate).is_err());
+        assert!(Generator::new().layout(Layout::ModulePerTable).generate(&duplicate).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn incremental() -> Result<(), anyhow::Error> {
+        let directory = std::env::temp_dir().join(format!("snowflake-codegen-{}", std::process::id()));
+        let generator = Generator::new().layout(Layout::ModulePerTable);
+        let mut schema = schema();
+        assert_eq!(generator.write(&schema, &directory)?.len(), 6);
+        assert!(generator.write(&schema, &directory)?.is_empty());
+
+        schema.tables[1].columns[1].nullable = false;
+        assert_eq!(generator.write(&schema, &directory)?, [directory.join("db/public/users.rs")]);
+        assert!(fs::read_to_string(directory.join("db/public/users.rs"))?.contains("pub type_: String,"));
+
+        schema.tables.remove(0);
+        let written = generator.write(&schema, &directory)?;
+        assert_eq!(written, [directory.join("db/mod.rs")]);
+        assert!(!directory.join("db/sales/order_items.rs").exists());
+        fs::remove_dir_all(directory)?;
+        Ok(())
+    }
+
+    #[test]
+    fn comments() -> Result<(), anyhow::Error> {
+        let mut schema = schema();
+        schema.tables[1].comment = Some("Registered users.\n\nOne row per account.".into());
+        schema.tables[1].columns[0].comment = Some("Primary key.".into());
+        let contents = Generator::new().serde(true).generate(&schema)?.remove(0).contents;
+        assert!(contents.contains(
+            "/// Registered users.\n///\n/// One row per account.\n#[derive(SnowflakeDeserialize, serde::Serialize, serde::Deserialize, Clone, Debug)]\n"
+        ));
+        assert!(contents.contains(
+            "    /// Primary key.\n    #[snowflake(rename = \"ID\", data_type = \"NUMBER(38,0)\")]\n    #[serde(rename = \"ID\")]\n    pub id: i128,"
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,332 @@
+use std::fmt::Display;
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime};
+use rust_decimal::Decimal;
+
+use crate::{table::normalize_data_type, DeserializeFromStr, RowType};
+
+/// Column type as described by `resultSetMetaData.rowType`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColumnType {
+    Number { precision: u32, scale: i32 },
+    Real,
+    Text { length: Option<usize> },
+    Binary,
+    Boolean,
+    Date,
+    Time { scale: i32 },
+    TimestampNtz { scale: i32 },
+    TimestampLtz { scale: i32 },
+    TimestampTz { scale: i32 },
+    Variant,
+    Object,
+    Array,
+    Other(String),
+}
+
+impl ColumnType {
+    /// Rust type a value of this column is expected to deserialize into.
+    pub fn rust_type(&self) -> &'static str {
+        match self {
+            ColumnType::Number { scale, .. } if *scale > 0 => "rust_decimal::Decimal",
+            ColumnType::Number { precision, .. } if *precision > 18 => "i128",
+            ColumnType::Number { .. } => "i64",
+            ColumnType::Real => "f64",
+            ColumnType::Text { .. } | ColumnType::Binary | ColumnType::Other(_) => "String",
+            ColumnType::Boolean => "bool",
+            ColumnType::Date => "chrono::NaiveDate",
+            ColumnType::Time { .. } => "chrono::NaiveTime",
+            ColumnType::TimestampNtz { .. } => "chrono::NaiveDateTime",
+            ColumnType::TimestampLtz { .. } |
+            ColumnType::TimestampTz { .. }
+                => "chrono::DateTime<chrono::FixedOffset>",
+            ColumnType::Variant |
+            ColumnType::Object |
+            ColumnType::Array
+                => "variant::Variant",
+        }
+    }
+}
+
+impl ColumnType {
+    /// Column type from a declared data type, ex. `NUMBER(38,2)` or `VARCHAR(16)`,
+    /// precision defaults to 38 and scale to 0 when omitted.
+    pub fn from_declared(data_type: &str) -> Self {
+        let arguments: Vec<&str> = data_type.split_once('(')
+            .map(|(_, arguments)| arguments.trim_end().trim_end_matches(')').split(',').map(str::trim).collect())
+            .unwrap_or_default();
+        let argument = |index: usize| arguments.get(index).and_then(|argument| argument.parse::<i32>().ok());
+        let scale = argument(0).unwrap_or(9);
+        match normalize_data_type(data_type).as_str() {
+            "NUMBER" => ColumnType::Number {
+                precision: argument(0).unwrap_or(38) as u32,
+                scale: argument(1).unwrap_or_default(),
+            },
+            "FLOAT" => ColumnType::Real,
+            "TEXT" => ColumnType::Text { length: argument(0).map(|length| length as usize) },
+            "BINARY" => ColumnType::Binary,
+            "BOOLEAN" => ColumnType::Boolean,
+            "DATE" => ColumnType::Date,
+            "TIME" => ColumnType::Time { scale },
+            "TIMESTAMP_NTZ" => ColumnType::TimestampNtz { scale },
+            "TIMESTAMP_LTZ" => ColumnType::TimestampLtz { scale },
+            "TIMESTAMP_TZ" => ColumnType::TimestampTz { scale },
+            "VARIANT" => ColumnType::Variant,
+            "OBJECT" => ColumnType::Object,
+            "ARRAY" => ColumnType::Array,
+            other => ColumnType::Other(other.to_owned()),
+        }
+    }
+}
+
+impl From<&RowType> for ColumnType {
+    fn from(row_type: &RowType) -> Self {
+        let scale = row_type.scale.unwrap_or_default();
+        match row_type.data_type.to_ascii_lowercase().as_str() {
+            "fixed" => ColumnType::Number {
+                precision: row_type.precision.unwrap_or(38),
+                scale,
+            },
+            "real" => ColumnType::Real,
+            "text" => ColumnType::Text { length: row_type.byte_length },
+            "binary" => ColumnType::Binary,
+            "boolean" => ColumnType::Boolean,
+            "date" => ColumnType::Date,
+            "time" => ColumnType::Time { scale },
+            "timestamp_ntz" => ColumnType::TimestampNtz { scale },
+            "timestamp_ltz" => ColumnType::TimestampLtz { scale },
+            "timestamp_tz" => ColumnType::TimestampTz { scale },
+            "variant" => ColumnType::Variant,
+            "object" => ColumnType::Object,
+            "array" => ColumnType::Array,
+            _ => ColumnType::Other(row_type.data_type.clone()),
+        }
+    }
+}
+
+impl Display for ColumnType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColumnType::Number { precision, scale } => write!(f, "NUMBER({precision},{scale})"),
+            ColumnType::Real => f.write_str("FLOAT"),
+            ColumnType::Text { length: Some(length) } => write!(f, "VARCHAR({length})"),
+            ColumnType::Text { length: None } => f.write_str("VARCHAR"),
+            ColumnType::Binary => f.write_str("BINARY"),
+            ColumnType::Boolean => f.write_str("BOOLEAN"),
+            ColumnType::Date => f.write_str("DATE"),
+            ColumnType::Time { scale } => write!(f, "TIME({scale})"),
+            ColumnType::TimestampNtz { scale } => write!(f, "TIMESTAMP_NTZ({scale})"),
+            ColumnType::TimestampLtz { scale } => write!(f, "TIMESTAMP_LTZ({scale})"),
+            ColumnType::TimestampTz { scale } => write!(f, "TIMESTAMP_TZ({scale})"),
+            ColumnType::Variant => f.write_str("VARIANT"),
+            ColumnType::Object => f.write_str("OBJECT"),
+            ColumnType::Array => f.write_str("ARRAY"),
+            ColumnType::Other(name) => f.write_str(&name.to_ascii_uppercase()),
+        }
+    }
+}
+
+/// Cell could not be converted into the Rust type of the field it maps to.
+#[derive(Debug)]
+pub struct ColumnMismatch {
+    pub column: Box<str>,
+    pub column_type: ColumnType,
+    pub rust_type: &'static str,
+    pub source: anyhow::Error,
+}
+
+impl ColumnMismatch {
+    pub fn new<T, E: Into<anyhow::Error>>(row_type: &RowType, error: E) -> Self {
+        ColumnMismatch {
+            column: row_type.name.as_str().into(),
+            column_type: row_type.into(),
+            rust_type: std::any::type_name::<T>(),
+            source: error.into(),
+        }
+    }
+}
+
+impl Display for ColumnMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "column {} is {} but struct field is {} (expected {})",
+            self.column, self.column_type, self.rust_type, self.column_type.rust_type(),
+        )
+    }
+}
+
+impl std::error::Error for ColumnMismatch {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+/// Check that the response has at least as many columns as the type consumes.
+pub fn expect_columns<T>(row_types: &[RowType], count: usize) -> Result<(), anyhow::Error> {
+    if row_types.len() < count {
+        anyhow::bail!(
+            "query returned {} column(s) but {} expects {count}",
+            row_types.len(), std::any::type_name::<T>(),
+        );
+    }
+    Ok(())
+}
+
+/// Position of the column named `name`, ignoring case.
+pub fn column_index(row_types: &[RowType], name: &str) -> Option<usize> {
+    row_types.iter().position(|row_type| row_type.name.eq_ignore_ascii_case(name))
+}
+
+/// Position of the column named `name`, ignoring case, an error naming `T` if the query did not return it.
+pub fn expect_column<T>(row_types: &[RowType], name: &str) -> Result<usize, anyhow::Error> {
+    column_index(row_types, name).ok_or_else(|| anyhow::anyhow!(
+        "query did not return column {name} {} expects, returned {}",
+        std::any::type_name::<T>(),
+        row_types.iter().map(|row_type| row_type.name.as_str()).collect::<Vec<_>>().join(", "),
+    ))
+}
+
+/// Check that a column has the expected name, ignoring case.
+pub fn expect_column_name(row_type: &RowType, name: &str) -> Result<(), anyhow::Error> {
+    if !row_type.name.eq_ignore_ascii_case(name) {
+        anyhow::bail!("expected column {name} but query returned {}", row_type.name);
+    }
+    Ok(())
+}
+
+impl DeserializeFromStr for Decimal {
+    type Err = anyhow::Error;
+    fn deserialize_from_str(s: &str) -> Result<Self, Self::Err> {
+        Decimal::from_str_exact(s)
+            .or_else(|_| Decimal::from_scientific(s))
+            .map_err(Into::into)
+    }
+}
+
+/// `DATE` cells are sent as the number of days since the epoch.
+impl DeserializeFromStr for NaiveDate {
+    type Err = anyhow::Error;
+    fn deserialize_from_str(s: &str) -> Result<Self, Self::Err> {
+        let days: i64 = s.parse()?;
+        NaiveDate::default()
+            .checked_add_signed(chrono::Duration::days(days))
+            .ok_or_else(|| anyhow::anyhow!("date out of range: {s}"))
+    }
+}
+
+/// `TIME` cells are sent as seconds since midnight, ex. `3723.000000000`.
+impl DeserializeFromStr for NaiveTime {
+    type Err = anyhow::Error;
+    fn deserialize_from_str(s: &str) -> Result<Self, Self::Err> {
+        let nanos = parse_epoch_nanos(s)?;
+        let (seconds, nanos) = (nanos.div_euclid(1_000_000_000), nanos.rem_euclid(1_000_000_000));
+        u32::try_from(seconds).ok()
+            .and_then(|seconds| NaiveTime::from_num_seconds_from_midnight_opt(seconds, nanos as u32))
+            .ok_or_else(|| anyhow::anyhow!("time out of range: {s}"))
+    }
+}
+
+/// `TIMESTAMP_NTZ` cells are sent as seconds since the epoch, ex. `1616173619.000000000`.
+impl DeserializeFromStr for NaiveDateTime {
+    type Err = anyhow::Error;
+    fn deserialize_from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(epoch_nanos_to_date_time(s, parse_epoch_nanos(s)?)?.naive_utc())
+    }
+}
+
+/// `TIMESTAMP_TZ` cells are sent as seconds since the epoch followed by the
+/// offset in minutes plus 1440, ex. `1616173619.000000000 1500`.
+/// `TIMESTAMP_LTZ` cells omit the offset and are read as UTC.
+impl DeserializeFromStr for DateTime<FixedOffset> {
+    type Err = anyhow::Error;
+    fn deserialize_from_str(s: &str) -> Result<Self, Self::Err> {
+        let (timestamp, offset) = match s.split_once(' ') {
+            Some((timestamp, offset)) => (timestamp, offset.parse::<i32>()? - 1440),
+            None => (s, 0),
+        };
+        let offset = FixedOffset::east_opt(offset * 60)
+            .ok_or_else(|| anyhow::anyhow!("offset out of range: {s}"))?;
+        Ok(epoch_nanos_to_date_time(s, parse_epoch_nanos(timestamp)?)?.with_timezone(&offset))
+    }
+}
+
+fn parse_epoch_nanos(s: &str) -> Result<i128, anyhow::Error> {
+    let (seconds, fraction) = s.split_once('.').unwrap_or((s, ""));
+    if fraction.len() > 9 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+        anyhow::bail!("invalid fractional seconds: {s}");
+    }
+    let negative = seconds.starts_with('-');
+    let seconds: i128 = seconds.trim_start_matches('-').parse()?;
+    let fraction: i128 = if fraction.is_empty() {
+        0
+    } else {
+        format!("{fraction:0<9}").parse()?
+    };
+    let nanos = seconds * 1_000_000_000 + fraction;
+    Ok(if negative { -nanos } else { nanos })
+}
+
+fn epoch_nanos_to_date_time(s: &str, nanos: i128) -> Result<DateTime<chrono::Utc>, anyhow::Error> {
+    i64::try_from(nanos.div_euclid(1_000_000_000)).ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, nanos.rem_euclid(1_000_000_000) as u32))
+        .ok_or_else(|| anyhow::anyhow!("timestamp out of range: {s}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row_type(data_type: &str, precision: Option<u32>, scale: Option<i32>) -> RowType {
+        RowType {
+            name: "AMOUNT".into(),
+            database: "DB".into(),
+            schema: "PUBLIC".into(),
+            table: "T".into(),
+            precision,
+            byte_length: None,
+            data_type: data_type.into(),
+            scale,
+            nullable: false,
+        }
+    }
+
+    #[test]
+    fn coercion_matrix() {
+        let number = ColumnType::from(&row_type("fixed", Some(38), Some(2)));
+        assert_eq!(number.to_string(), "NUMBER(38,2)");
+        assert_eq!(number.rust_type(), "rust_decimal::Decimal");
+        assert_eq!(ColumnType::from(&row_type("fixed", Some(10), Some(0))).rust_type(), "i64");
+        assert_eq!(ColumnType::from(&row_type("fixed", Some(38), Some(0))).rust_type(), "i128");
+        assert_eq!(ColumnType::from(&row_type("TIMESTAMP_NTZ", None, Some(9))).to_string(), "TIMESTAMP_NTZ(9)");
+        assert_eq!(ColumnType::from_declared("NUMBER(38,2)"), number);
+        assert_eq!(ColumnType::from_declared("INTEGER").rust_type(), "i128");
+        assert_eq!(ColumnType::from_declared("VARCHAR(16)"), ColumnType::Text { length: Some(16) });
+        assert_eq!(ColumnType::from_declared("TIMESTAMP"), ColumnType::TimestampNtz { scale: 9 });
+
+        let mismatch = ColumnMismatch::new::<i64, _>(
+            &row_type("fixed", Some(38), Some(2)),
+            anyhow::anyhow!("invalid digit found in string"),
+        );
+        assert!(mismatch.to_string().starts_with("column AMOUNT is NUMBER(38,2) but struct field is i64"));
+        assert_eq!(&*mismatch.column, "AMOUNT");
+
+        assert!(expect_column_name(&row_type("fixed", Some(38), Some(2)), "amount").is_ok());
+        assert!(expect_column_name(&row_type("fixed", Some(38), Some(2)), "PRICE").is_err());
+    }
+
+    #[test]
+    fn wire_formats() -> Result<(), anyhow::Error> {
+        assert_eq!(NaiveDate::deserialize_from_str("19000")?, NaiveDate::from_ymd_opt(2022, 1, 8).unwrap());
+        assert_eq!(NaiveTime::deserialize_from_str("3723.500000000")?, NaiveTime::from_hms_milli_opt(1, 2, 3, 500).unwrap());
+        assert_eq!(
+            NaiveDateTime::deserialize_from_str("-1.5")?,
+            NaiveDate::from_ymd_opt(1969, 12, 31).unwrap().and_hms_milli_opt(23, 59, 58, 500).unwrap(),
+        );
+        let tz = DateTime::<FixedOffset>::deserialize_from_str("1616173619.000000000 1500")?;
+        assert_eq!(tz.offset().local_minus_utc(), 60 * 60);
+        assert_eq!(tz.timestamp(), 1616173619);
+        assert_eq!(Decimal::deserialize_from_str("12.50")?.to_string(), "12.50");
+        Ok(())
+    }
+}
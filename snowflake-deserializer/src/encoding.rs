@@ -0,0 +1,189 @@
+//! How date and time bindings are encoded, the SQL API expects each as an integer string:
+//!
+//! | Snowflake type  | Encoding                                                     |
+//! |-----------------|--------------------------------------------------------------|
+//! | `DATE`          | milliseconds since the Unix epoch                            |
+//! | `TIME`          | fractions of a second since midnight, at a scale             |
+//! | `TIMESTAMP_NTZ` | fractions of a second since the Unix epoch, at a scale       |
+//!
+//! The scale is the number of fractional digits, as in `TIMESTAMP_NTZ(9)`, bindings are sent at [`TIMESTAMP_SCALE`].
+//! Timestamps are encoded as `i128`, so every date chrono can represent fits at any scale.
+//! Every encoding has a matching decoding, so values can be checked to survive the roundtrip.
+
+use std::fmt::Display;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Timelike};
+
+/// Scale `TIME` and `TIMESTAMP_NTZ` bindings are sent at, nanoseconds.
+pub const TIMESTAMP_SCALE: u32 = 9;
+
+const MAX_SCALE: u32 = 9;
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
+const SECONDS_PER_DAY: u32 = 86_400;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EncodingError {
+    /// Scale above 9, Snowflake supports at most nanoseconds.
+    InvalidScale(u32),
+    /// Value has digits the scale can not hold, ex. nanoseconds at scale 3.
+    PrecisionLoss { scale: u32 },
+    /// Encoded value does not map to a value of the type.
+    OutOfRange { value: i128, data_type: &'static str },
+}
+
+impl Display for EncodingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EncodingError::InvalidScale(scale) => write!(f, "scale {scale} is above the maximum of {MAX_SCALE}"),
+            EncodingError::PrecisionLoss { scale } => write!(f, "value has more fractional digits than scale {scale}"),
+            EncodingError::OutOfRange { value, data_type } => write!(f, "{value} is out of range for {data_type}"),
+        }
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+/// Milliseconds since the Unix epoch at midnight of `date`.
+pub fn encode_date(date: NaiveDate) -> i64 {
+    date.and_time(NaiveTime::MIN).and_utc().timestamp_millis()
+}
+
+/// Inverse of [`encode_date`], errors if out of range or not at midnight.
+pub fn decode_date(millis: i64) -> Result<NaiveDate, EncodingError> {
+    let out_of_range = EncodingError::OutOfRange { value: millis.into(), data_type: "DATE" };
+    let date_time = DateTime::from_timestamp_millis(millis).ok_or(out_of_range.clone())?.naive_utc();
+    if date_time.time() != NaiveTime::MIN {
+        return Err(out_of_range);
+    }
+    Ok(date_time.date())
+}
+
+/// Fractions of a second since midnight at `scale`,
+/// a leap second counts as the last nanoseconds of the second before.
+pub fn encode_time(time: NaiveTime, scale: u32) -> Result<i64, EncodingError> {
+    let fraction = fraction(time.nanosecond(), scale)?;
+    Ok(time.num_seconds_from_midnight() as i64 * 10i64.pow(scale) + fraction as i64)
+}
+
+/// [`encode_time`] at [`TIMESTAMP_SCALE`], which holds every time.
+pub fn encode_time_nanos(time: NaiveTime) -> i64 {
+    time.num_seconds_from_midnight() as i64 * NANOS_PER_SECOND as i64 + nanos(time.nanosecond()) as i64
+}
+
+/// Inverse of [`encode_time`], errors if not within a day.
+pub fn decode_time(value: i64, scale: u32) -> Result<NaiveTime, EncodingError> {
+    let (seconds, nanos) = split(value.into(), scale)?;
+    u32::try_from(seconds).ok()
+        .filter(|seconds| *seconds < SECONDS_PER_DAY)
+        .and_then(|seconds| NaiveTime::from_num_seconds_from_midnight_opt(seconds, nanos))
+        .ok_or(EncodingError::OutOfRange { value: value.into(), data_type: "TIME" })
+}
+
+/// Fractions of a second since the Unix epoch at `scale`,
+/// a leap second counts as the last nanoseconds of the second before.
+pub fn encode_timestamp_ntz(date_time: NaiveDateTime, scale: u32) -> Result<i128, EncodingError> {
+    let fraction = fraction(date_time.nanosecond(), scale)?;
+    Ok(date_time.and_utc().timestamp() as i128 * 10i128.pow(scale) + fraction as i128)
+}
+
+/// [`encode_timestamp_ntz`] at [`TIMESTAMP_SCALE`], which holds every date and time.
+pub fn encode_timestamp_ntz_nanos(date_time: NaiveDateTime) -> i128 {
+    date_time.and_utc().timestamp() as i128 * NANOS_PER_SECOND as i128 + nanos(date_time.nanosecond()) as i128
+}
+
+/// Inverse of [`encode_timestamp_ntz`].
+pub fn decode_timestamp_ntz(value: i128, scale: u32) -> Result<NaiveDateTime, EncodingError> {
+    let (seconds, nanos) = split(value, scale)?;
+    i64::try_from(seconds).ok()
+        .and_then(|seconds| DateTime::from_timestamp(seconds, nanos))
+        .map(|date_time| date_time.naive_utc())
+        .ok_or(EncodingError::OutOfRange { value, data_type: "TIMESTAMP_NTZ" })
+}
+
+fn check_scale(scale: u32) -> Result<(), EncodingError> {
+    if scale > MAX_SCALE {
+        return Err(EncodingError::InvalidScale(scale));
+    }
+    Ok(())
+}
+
+/// Fractional part of a second at `scale`.
+fn fraction(nanos: u32, scale: u32) -> Result<u32, EncodingError> {
+    check_scale(scale)?;
+    let nanos = self::nanos(nanos);
+    let divisor = 10u32.pow(MAX_SCALE - scale);
+    if !nanos.is_multiple_of(divisor) {
+        return Err(EncodingError::PrecisionLoss { scale });
+    }
+    Ok(nanos / divisor)
+}
+
+/// Nanoseconds of a second, those of a leap second are the last of the second before.
+fn nanos(nanos: u32) -> u32 {
+    nanos.min(NANOS_PER_SECOND - 1)
+}
+
+/// Whole seconds and nanoseconds of a value at `scale`.
+fn split(value: i128, scale: u32) -> Result<(i128, u32), EncodingError> {
+    check_scale(scale)?;
+    let per_second = 10i128.pow(scale);
+    let nanos = value.rem_euclid(per_second) as u32 * 10u32.pow(MAX_SCALE - scale);
+    Ok((value.div_euclid(per_second), nanos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn known_values() {
+        let date = NaiveDate::from_ymd_opt(2023, 1, 2).unwrap();
+        assert_eq!(encode_date(NaiveDate::from_ymd_opt(1970, 1, 1).unwrap()), 0);
+        assert_eq!(encode_date(date), 1_672_617_600_000);
+        assert_eq!(encode_time(NaiveTime::from_hms_nano_opt(0, 0, 1, 5).unwrap(), 9), Ok(1_000_000_005));
+        assert_eq!(encode_time(NaiveTime::from_hms_milli_opt(0, 0, 1, 5).unwrap(), 3), Ok(1_005));
+        assert_eq!(encode_time(NaiveTime::from_hms_opt(23, 59, 59).unwrap(), 0), Ok(86_399));
+        assert_eq!(encode_timestamp_ntz(date.and_hms_nano_opt(0, 0, 0, 1).unwrap(), 9), Ok(1_672_617_600_000_000_001));
+        let far = NaiveDate::from_ymd_opt(3000, 1, 1).unwrap().and_time(NaiveTime::MIN);
+        assert_eq!(encode_timestamp_ntz(far, 9), Ok(32_503_680_000_000_000_000));
+        assert_eq!(decode_timestamp_ntz(32_503_680_000_000_000_000, 9), Ok(far));
+        let leap = NaiveTime::from_hms_nano_opt(23, 59, 59, 1_500_000_000).unwrap();
+        assert_eq!(Ok(encode_time_nanos(leap)), encode_time(leap, TIMESTAMP_SCALE));
+        assert_eq!(Ok(encode_timestamp_ntz_nanos(far)), encode_timestamp_ntz(far, TIMESTAMP_SCALE));
+    }
+
+    #[test]
+    fn errors() {
+        let time = NaiveTime::from_hms_nano_opt(0, 0, 1, 5).unwrap();
+        assert_eq!(encode_time(time, 10), Err(EncodingError::InvalidScale(10)));
+        assert_eq!(encode_time(time, 6), Err(EncodingError::PrecisionLoss { scale: 6 }));
+        assert!(decode_date(1).is_err());
+        assert!(decode_time(86_400, 0).is_err());
+        assert!(decode_time(-1, 9).is_err());
+        assert!(decode_timestamp_ntz(i128::MAX, 0).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn date_roundtrip(days in -100_000i64..100_000) {
+            let date = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap() + chrono::Duration::days(days);
+            prop_assert_eq!(decode_date(encode_date(date)), Ok(date));
+        }
+
+        #[test]
+        fn time_roundtrip(seconds in 0..SECONDS_PER_DAY, nanos in 0..NANOS_PER_SECOND, scale in 0..=MAX_SCALE) {
+            let nanos = nanos - nanos % 10u32.pow(MAX_SCALE - scale);
+            let time = NaiveTime::from_num_seconds_from_midnight_opt(seconds, nanos).unwrap();
+            prop_assert_eq!(decode_time(encode_time(time, scale)?, scale), Ok(time));
+        }
+
+        #[test]
+        fn timestamp_ntz_roundtrip(seconds in -8_000_000_000_000i64..8_000_000_000_000, nanos in 0..NANOS_PER_SECOND, scale in 0..=MAX_SCALE) {
+            let nanos = nanos - nanos % 10u32.pow(MAX_SCALE - scale);
+            let date_time = DateTime::from_timestamp(seconds, nanos).unwrap().naive_utc();
+            prop_assert_eq!(decode_timestamp_ntz(encode_timestamp_ntz(date_time, scale)?, scale), Ok(date_time));
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Share the storage of repeated text values, ex. low-cardinality columns of large extracts.
+//!
+//! Columns are interned by deserializing them into [`Interned`], values that are equal
+//! while [`interning`] runs point to the same allocation.
+
+use std::{cell::RefCell, collections::HashSet, fmt::Display, ops::Deref, sync::Arc};
+
+use crate::DeserializeFromStr;
+
+/// Text shared with equal values deserialized in the same [`interning`] scope.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interned(Arc<str>);
+
+impl Interned {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+    pub fn into_arc(self) -> Arc<str> {
+        self.0
+    }
+}
+
+impl Deref for Interned {
+    type Target = str;
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for Interned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Allocated on its own outside of [`interning`].
+impl DeserializeFromStr for Interned {
+    type Err = std::convert::Infallible;
+    fn deserialize_from_str(s: &str) -> Result<Self, Self::Err> {
+        let value = INTERNER.with(|interner| match &mut *interner.borrow_mut() {
+            Some(interner) => interner.intern(s),
+            None => Arc::from(s),
+        });
+        Ok(Interned(value))
+    }
+}
+
+/// What interning saved.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InternStats {
+    /// Values deserialized into [`Interned`].
+    pub values: usize,
+    /// Distinct values, each allocated once.
+    pub unique: usize,
+    /// Bytes of text not allocated because an equal value was reused.
+    pub bytes_saved: usize,
+}
+
+#[derive(Default)]
+struct Interner {
+    values: HashSet<Arc<str>>,
+    stats: InternStats,
+}
+
+impl Interner {
+    fn intern(&mut self, s: &str) -> Arc<str> {
+        self.stats.values += 1;
+        if let Some(value) = self.values.get(s) {
+            self.stats.bytes_saved += s.len();
+            return value.clone();
+        }
+        let value: Arc<str> = Arc::from(s);
+        self.values.insert(value.clone());
+        self.stats.unique += 1;
+        value
+    }
+}
+
+thread_local! {
+    static INTERNER: RefCell<Option<Interner>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, [`Interned`] values it deserializes on this thread share equal values.
+pub fn interning<R>(f: impl FnOnce() -> R) -> (R, InternStats) {
+    /// Restores the interner of an enclosing scope, also if `f` panics.
+    struct Scope(Option<Option<Interner>>);
+    impl Scope {
+        fn end(&mut self) -> Option<Interner> {
+            let previous = self.0.take()?;
+            INTERNER.with(|interner| std::mem::replace(&mut *interner.borrow_mut(), previous))
+        }
+    }
+    impl Drop for Scope {
+        fn drop(&mut self) {
+            self.end();
+        }
+    }
+    let mut scope = Scope(Some(INTERNER.with(|interner| interner.borrow_mut().replace(Interner::default()))));
+    let result = f();
+    let stats = scope.end().map(|interner| interner.stats).unwrap_or_default();
+    (result, stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_values() -> Result<(), anyhow::Error> {
+        let (values, stats) = interning(|| {
+            ["red", "blue", "red", "red"].map(|s| Interned::deserialize_from_str(s).unwrap())
+        });
+        assert!(Arc::ptr_eq(&values[0].0, &values[2].0));
+        assert!(!Arc::ptr_eq(&values[0].0, &values[1].0));
+        assert_eq!(stats, InternStats { values: 4, unique: 2, bytes_saved: 6 });
+
+        let outside = [Interned::deserialize_from_str("red")?, Interned::deserialize_from_str("red")?];
+        assert!(!Arc::ptr_eq(&outside[0].0, &outside[1].0));
+        Ok(())
+    }
+}
@@ -1,19 +1,54 @@
-use std::str::FromStr;
-use serde::Deserialize;
+use std::{collections::{BTreeMap, HashMap}, fmt::Display, str::FromStr, sync::Arc};
+use serde::{Deserialize, Serialize};
 
 pub mod bindings;
+pub mod codegen;
+pub mod coercion;
+pub mod encoding;
+pub mod intern;
+pub mod null;
+pub mod query;
+pub mod redact;
+pub mod schema;
+pub mod table;
+pub mod transform;
+pub mod variant;
 
 pub trait SnowflakeDeserialize {
     fn snowflake_deserialize(response: SnowflakeSQLResponse) -> Result<SnowflakeSQLResult<Self>, anyhow::Error>
         where Self: Sized;
 }
 
-#[derive(Deserialize, Debug)]
+/// Deserialize from consecutive columns of a row, implemented by the derive
+/// so structs can be nested with `#[snowflake(flatten)]`.
+pub trait SnowflakeDeserializeRow: Sized {
+    /// Number of columns consumed.
+    const WIDTH: usize;
+    /// Where the columns of `Self` are, ex. positions of columns looked up by name.
+    type Columns;
+    /// Check and resolve the columns once per response, `row_types` starts at the first column of `Self`.
+    fn check_row_types(row_types: &[RowType]) -> Result<Self::Columns, anyhow::Error>;
+    /// `row_types` and `row` start at the first column of `Self`, `columns` is what [`check_row_types`](Self::check_row_types) resolved.
+    fn deserialize_row(row_types: &[RowType], columns: &Self::Columns, row: &[String]) -> Result<Self, anyhow::Error>;
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct SnowflakeSQLResponse {
+    /// Empty for statements that return no result set.
+    #[serde(default)]
     pub result_set_meta_data: MetaData,
+    /// `NULL` cells hold [`null_sentinel`](Self::null_sentinel).
+    #[serde(default, deserialize_with = "null::deserialize_rows")]
     pub data: Vec<Vec<String>>,
+    /// Sentinel of `NULL` cells the response was parsed with, see [`null`].
+    #[serde(skip_deserializing, default = "null::current")]
+    pub null_sentinel: Arc<str>,
     pub code: String,
+    pub statement_handle: String,
+    /// Handles of each child statement of a multi-statement request.
+    #[serde(default)]
+    pub statement_handles: Vec<String>,
     pub statement_status_url: String,
     pub request_id: String,
     pub sql_state: String,
@@ -22,20 +57,74 @@ pub struct SnowflakeSQLResponse {
 }
 
 impl SnowflakeSQLResponse {
+    /// Deserialize the rows, the result carries the request id and statement handle of the response.
+    ///
+    /// A response without columns has no rows for any `T`.
     pub fn deserialize<T: SnowflakeDeserialize>(self) -> Result<SnowflakeSQLResult<T>, anyhow::Error> {
-        T::snowflake_deserialize(self)
+        let request_id = self.request_id.clone();
+        let statement_handle = self.statement_handle.clone();
+        let mut result = match self.is_empty() {
+            true => SnowflakeSQLResult::new(Vec::new()),
+            false => null::with_sentinel(&self.null_sentinel.clone(), || T::snowflake_deserialize(self))?,
+        };
+        result.request_id = request_id;
+        result.statement_handle = statement_handle;
+        Ok(result)
+    }
+    /// Like [`deserialize`](Self::deserialize), [`intern::Interned`] fields share equal values.
+    pub fn deserialize_interned<T: SnowflakeDeserialize>(self) -> Result<(SnowflakeSQLResult<T>, intern::InternStats), anyhow::Error> {
+        let (result, stats) = intern::interning(|| self.deserialize::<T>());
+        Ok((result?, stats))
+    }
+    /// Deserialize every row that can be instead of failing on the first bad row,
+    /// rejected rows keep their raw cells so they can be quarantined.
+    /// Errors only if the columns do not match `T`.
+    pub fn deserialize_partial<T: SnowflakeDeserializeRow>(self) -> Result<PartialSQLResult<T>, anyhow::Error> {
+        let request_id = self.request_id.clone();
+        let statement_handle = self.statement_handle.clone();
+        if self.is_empty() {
+            return Ok(PartialSQLResult { data: Vec::new(), rejected: Vec::new(), request_id, statement_handle });
+        }
+        let row_types = &self.result_set_meta_data.row_type;
+        let columns = T::check_row_types(row_types)?;
+        let mut data = Vec::with_capacity(self.data.len());
+        let mut rejected = Vec::new();
+        null::with_sentinel(&self.null_sentinel, || {
+            for (index, row) in self.data.into_iter().enumerate() {
+                match T::deserialize_row(row_types, &columns, &row) {
+                    Ok(value) => data.push(value),
+                    Err(source) => rejected.push(RowError { index, raw: row, source }),
+                }
+            }
+        });
+        Ok(PartialSQLResult { data, rejected, request_id, statement_handle })
+    }
+    /// Whether the response has neither columns nor rows.
+    pub fn is_empty(&self) -> bool {
+        self.result_set_meta_data.row_type.is_empty() && self.data.is_empty()
     }
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct MetaData {
     pub num_rows: usize,
     pub format: String,
     pub row_type: Vec<RowType>,
+    /// Large results are split into partitions, only the first is part of the response.
+    #[serde(default)]
+    pub partition_info: Vec<PartitionInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionInfo {
+    pub row_count: usize,
+    pub uncompressed_size: usize,
+    pub compressed_size: Option<usize>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct RowType {
     pub name: String,
@@ -52,9 +141,253 @@ pub struct RowType {
     //pub length: ???,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SnowflakeSQLResult<T> {
     pub data: Vec<T>,
+    /// Id the request was sent with, empty unless deserialized with [`SnowflakeSQLResponse::deserialize`].
+    pub request_id: String,
+    /// Handle of the statement, empty unless deserialized with [`SnowflakeSQLResponse::deserialize`].
+    pub statement_handle: String,
+}
+
+impl<T> SnowflakeSQLResult<T> {
+    pub fn new(data: Vec<T>) -> Self {
+        SnowflakeSQLResult {
+            data,
+            request_id: String::new(),
+            statement_handle: String::new(),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+impl<T> From<Vec<T>> for SnowflakeSQLResult<T> {
+    fn from(data: Vec<T>) -> Self {
+        SnowflakeSQLResult::new(data)
+    }
+}
+
+impl<T> FromIterator<T> for SnowflakeSQLResult<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        SnowflakeSQLResult::new(iter.into_iter().collect())
+    }
+}
+
+impl<T> std::ops::Deref for SnowflakeSQLResult<T> {
+    type Target = [T];
+    fn deref(&self) -> &[T] {
+        &self.data
+    }
+}
+
+impl<T> std::ops::DerefMut for SnowflakeSQLResult<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.data
+    }
+}
+
+impl<T> IntoIterator for SnowflakeSQLResult<T> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SnowflakeSQLResult<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut SnowflakeSQLResult<T> {
+    type Item = &'a mut T;
+    type IntoIter = std::slice::IterMut<'a, T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.data.iter_mut()
+    }
+}
+
+/// Serialized as the sequence of rows, the request id and statement handle are left out.
+impl<T: Serialize> Serialize for SnowflakeSQLResult<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.data.serialize(serializer)
+    }
+}
+
+/// Result of [`SnowflakeSQLResponse::deserialize_partial`].
+#[derive(Debug)]
+pub struct PartialSQLResult<T> {
+    pub data: Vec<T>,
+    pub rejected: Vec<RowError>,
+    pub request_id: String,
+    pub statement_handle: String,
+}
+
+/// Row that could not be deserialized, with its position in the result and its raw cells.
+#[derive(Debug)]
+pub struct RowError {
+    pub index: usize,
+    pub raw: Vec<String>,
+    pub source: anyhow::Error,
+}
+
+impl Display for RowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "row {} could not be deserialized", self.index)
+    }
+}
+
+impl std::error::Error for RowError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(self.source.as_ref())
+    }
+}
+
+macro_rules! impl_snowflake_deserialize_map {
+    ($ty: ty) => {
+        /// Rows keyed by column name, for queries without a dedicated struct.
+        impl SnowflakeDeserialize for $ty {
+            fn snowflake_deserialize(response: SnowflakeSQLResponse) -> Result<SnowflakeSQLResult<Self>, anyhow::Error> {
+                let row_types = response.result_set_meta_data.row_type;
+                let data = response.data
+                    .into_iter()
+                    .map(|row| row_types.iter()
+                        .map(|row_type| row_type.name.clone())
+                        .zip(row)
+                        .collect())
+                    .collect();
+                Ok(SnowflakeSQLResult::new(data))
+            }
+        }
+    };
+}
+
+impl_snowflake_deserialize_map!(HashMap<String, String>);
+impl_snowflake_deserialize_map!(BTreeMap<String, String>);
+impl_snowflake_deserialize_map!(Vec<(String, String)>);
+
+/// Column name and value pairs in the order of the result, ex. for reports that must keep the column order.
+///
+/// Integers that fit in 64 bits, floats and booleans are JSON numbers and booleans, semi-structured
+/// columns are their JSON, `NULL` is `null` and everything else, including decimals to stay exact, is a string.
+impl SnowflakeDeserialize for Vec<(String, serde_json::Value)> {
+    fn snowflake_deserialize(response: SnowflakeSQLResponse) -> Result<SnowflakeSQLResult<Self>, anyhow::Error> {
+        let row_types = &response.result_set_meta_data.row_type;
+        let mut data = Vec::with_capacity(response.data.len());
+        for row in &response.data {
+            data.push(row_types.iter()
+                .zip(row)
+                .map(|(row_type, cell)| Ok((row_type.name.clone(), json_value(row_type, cell)?)))
+                .collect::<Result<_, anyhow::Error>>()?);
+        }
+        Ok(SnowflakeSQLResult::new(data))
+    }
+}
+
+fn json_value(row_type: &RowType, cell: &str) -> Result<serde_json::Value, anyhow::Error> {
+    use coercion::{ColumnMismatch, ColumnType};
+    use serde_json::Value;
+    if row_type.nullable && null::is_null(cell) {
+        return Ok(Value::Null);
+    }
+    let mismatch = |e: anyhow::Error| ColumnMismatch::new::<Value, _>(row_type, e);
+    Ok(match ColumnType::from(row_type) {
+        ColumnType::Number { scale: 0, .. } => match (cell.parse::<i64>(), cell.parse::<u64>()) {
+            (Ok(value), _) => value.into(),
+            (_, Ok(value)) => value.into(),
+            _ => Value::String(cell.to_owned()),
+        },
+        ColumnType::Real => f64::deserialize_from_str(cell).map_err(|e| mismatch(e.into()))?.into(),
+        ColumnType::Boolean => bool::deserialize_from_str(cell).map_err(mismatch)?.into(),
+        ColumnType::Variant | ColumnType::Object | ColumnType::Array
+            => serde_json::from_str(cell).map_err(|e| mismatch(e.into()))?,
+        _ => Value::String(cell.to_owned()),
+    })
+}
+
+macro_rules! impl_snowflake_deserialize_tuple {
+    ($($ty: ident $index: tt),+) => {
+        /// Columns in order, for ad hoc queries without a dedicated struct.
+        impl<$($ty),+> SnowflakeDeserialize for ($($ty,)+)
+            where $($ty: DeserializeFromStr, <$ty as DeserializeFromStr>::Err: Into<anyhow::Error>),+
+        {
+            fn snowflake_deserialize(response: SnowflakeSQLResponse) -> Result<SnowflakeSQLResult<Self>, anyhow::Error> {
+                let row_types = &response.result_set_meta_data.row_type;
+                coercion::expect_columns::<Self>(row_types, [$($index),+].len())?;
+                let mut data = Vec::with_capacity(response.data.len());
+                for row in &response.data {
+                    data.push(($(
+                        $ty::deserialize_from_str(&row[$index])
+                            .map_err(|e| coercion::ColumnMismatch::new::<$ty, _>(&row_types[$index], e))?,
+                    )+));
+                }
+                Ok(SnowflakeSQLResult::new(data))
+            }
+        }
+    };
+}
+
+impl_snowflake_deserialize_tuple!(A 0);
+impl_snowflake_deserialize_tuple!(A 0, B 1);
+impl_snowflake_deserialize_tuple!(A 0, B 1, C 2);
+impl_snowflake_deserialize_tuple!(A 0, B 1, C 2, D 3);
+impl_snowflake_deserialize_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_snowflake_deserialize_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_snowflake_deserialize_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_snowflake_deserialize_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_snowflake_deserialize_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_snowflake_deserialize_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_snowflake_deserialize_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_snowflake_deserialize_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+
+macro_rules! impl_snowflake_deserialize_scalar {
+    ($($ty: ty),+ $(,)?) => {
+        $(
+            /// Single column result, ex. `SELECT COUNT(*)`.
+            impl SnowflakeDeserialize for $ty {
+                fn snowflake_deserialize(response: SnowflakeSQLResponse) -> Result<SnowflakeSQLResult<Self>, anyhow::Error> {
+                    deserialize_scalar(response)
+                }
+            }
+        )+
+    };
+}
+
+impl_snowflake_deserialize_scalar!(
+    bool,
+    usize, isize, u8, i8, u16, i16, u32, i32, u64, i64, u128, i128,
+    f32, f64,
+    String,
+    rust_decimal::Decimal,
+    chrono::NaiveDate, chrono::NaiveTime, chrono::NaiveDateTime,
+    chrono::DateTime<chrono::FixedOffset>,
+    variant::Variant,
+);
+
+fn deserialize_scalar<T>(response: SnowflakeSQLResponse) -> Result<SnowflakeSQLResult<T>, anyhow::Error>
+    where T: DeserializeFromStr, T::Err: Into<anyhow::Error>
+{
+    let row_types = &response.result_set_meta_data.row_type;
+    if row_types.len() != 1 {
+        anyhow::bail!(
+            "query returned {} columns but {} expects exactly 1",
+            row_types.len(), std::any::type_name::<T>(),
+        );
+    }
+    let mut data = Vec::with_capacity(response.data.len());
+    for row in &response.data {
+        data.push(T::deserialize_from_str(&row[0])
+            .map_err(|e| coercion::ColumnMismatch::new::<T, _>(&row_types[0], e))?);
+    }
+    Ok(SnowflakeSQLResult::new(data))
 }
 
 /// For custom data parsing,
@@ -77,6 +410,17 @@ impl DeserializeFromStr for bool {
     }
 }
 
+/// `None` for `NULL` cells, see [`null`].
+impl<T: DeserializeFromStr> DeserializeFromStr for Option<T> {
+    type Err = T::Err;
+    fn deserialize_from_str(s: &str) -> Result<Self, Self::Err> {
+        if null::is_null(s) {
+            return Ok(None);
+        }
+        T::deserialize_from_str(s).map(Some)
+    }
+}
+
 macro_rules! impl_deserialize_from_str {
     ($ty: ty) => {
         impl DeserializeFromStr for $ty {
@@ -89,6 +433,7 @@ macro_rules! impl_deserialize_from_str {
 }
 
 impl_deserialize_from_str!(usize);
+impl_deserialize_from_str!(i8);
 impl_deserialize_from_str!(isize);
 impl_deserialize_from_str!(u8);
 impl_deserialize_from_str!(u16);
@@ -102,3 +447,196 @@ impl_deserialize_from_str!(i128);
 impl_deserialize_from_str!(f32);
 impl_deserialize_from_str!(f64);
 impl_deserialize_from_str!(String);
+
+macro_rules! impl_deserialize_from_str_shared {
+    ($($ty: ty),+ $(,)?) => {
+        $(
+            /// Text stored in one allocation, ex. to share repeated values after deserializing.
+            impl DeserializeFromStr for $ty {
+                type Err = std::convert::Infallible;
+                fn deserialize_from_str(s: &str) -> Result<Self, Self::Err> {
+                    Ok(<$ty>::from(s))
+                }
+            }
+        )+
+    };
+}
+
+impl_deserialize_from_str_shared!(Box<str>, std::rc::Rc<str>, std::sync::Arc<str>);
+
+impl DeserializeFromStr for std::borrow::Cow<'static, str> {
+    type Err = std::convert::Infallible;
+    fn deserialize_from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(std::borrow::Cow::Owned(s.to_owned()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    pub(crate) fn response(columns: &[&str], data: &[&[&str]]) -> SnowflakeSQLResponse {
+        let row_type: Vec<_> = columns.iter()
+            .map(|name| serde_json::json!({
+                "name": name,
+                "database": "DB",
+                "schema": "PUBLIC",
+                "table": "TEST_TABLE",
+                "type": "text",
+                "nullable": false,
+            }))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "resultSetMetaData": {
+                "numRows": data.len(),
+                "format": "jsonv2",
+                "rowType": row_type,
+            },
+            "data": data,
+            "code": "090001",
+            "statementHandle": "handle",
+            "statementStatusUrl": "/api/v2/statements/handle?requestId=id",
+            "requestId": "id",
+            "sqlState": "00000",
+            "message": "Statement executed successfully.",
+        })).unwrap()
+    }
+
+    #[test]
+    fn deserialize_map() -> Result<(), anyhow::Error> {
+        let result = response(&["ID", "NAME"], &[&["1", "JoMama"], &["2", "JoPapa"]])
+            .deserialize::<BTreeMap<String, String>>()?;
+        assert_eq!(result.data.len(), 2);
+        assert_eq!(result.data[1]["ID"], "2");
+        assert_eq!(result.data[1]["NAME"], "JoPapa");
+        Ok(())
+    }
+
+    #[test]
+    fn without_columns() -> Result<(), anyhow::Error> {
+        assert!(response(&[], &[]).deserialize::<(u32, String)>()?.is_empty());
+        assert!(response(&["status"], &[]).deserialize::<(u32, String)>().is_err());
+
+        let response: SnowflakeSQLResponse = serde_json::from_value(serde_json::json!({
+            "code": "090001",
+            "statementHandle": "handle",
+            "statementStatusUrl": "/api/v2/statements/handle?requestId=id",
+            "requestId": "id",
+            "sqlState": "00000",
+            "message": "Statement executed successfully.",
+        }))?;
+        assert!(response.deserialize::<BTreeMap<String, String>>()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn ordered_pairs() -> Result<(), anyhow::Error> {
+        let response = response(&["ZETA", "ALPHA"], &[&["z", "a"]]);
+        let pairs = response.clone().deserialize::<Vec<(String, String)>>()?;
+        assert_eq!(pairs.data[0], [("ZETA".into(), "z".into()), ("ALPHA".into(), "a".into())]);
+
+        let mut response = response;
+        let row_types = &mut response.result_set_meta_data.row_type;
+        row_types[0].data_type = "fixed".into();
+        row_types[0].scale = Some(0);
+        row_types[1].data_type = "variant".into();
+        row_types[1].nullable = true;
+        response.data = vec![vec!["-7".into(), r#"{"b": [1]}"#.into()], vec!["8".into(), null::sentinel()]];
+        let values = response.deserialize::<Vec<(String, serde_json::Value)>>()?;
+        assert_eq!(values.data[0], [("ZETA".into(), serde_json::json!(-7)), ("ALPHA".into(), serde_json::json!({"b": [1]}))]);
+        assert_eq!(values.data[1][1].1, serde_json::Value::Null);
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_tuple_and_scalar() -> Result<(), anyhow::Error> {
+        let result = response(&["ID", "NAME"], &[&["1", "JoMama"]])
+            .deserialize::<(u32, String)>()?;
+        assert_eq!(result.data, vec![(1, "JoMama".to_string())]);
+        let result = response(&["COUNT(*)"], &[&["69"]]).deserialize::<i64>()?;
+        assert_eq!(result.data, vec![69]);
+        assert!(response(&["ID", "NAME"], &[&["1", "JoMama"]]).deserialize::<i64>().is_err());
+        assert!(response(&["ID"], &[&["1"]]).deserialize::<(u32, String)>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn null_sentinel() -> Result<(), anyhow::Error> {
+        let data = serde_json::json!([["1", null], ["null", "x"]]);
+        let rows = null::deserialize_rows(&data)?;
+        assert_eq!(rows, vec![vec!["1", "null"], vec!["null", "x"]]);
+        assert_eq!(Option::<u32>::deserialize_from_str("null")?, None);
+        assert_eq!(Option::<u32>::deserialize_from_str("1")?, Some(1));
+
+        let mut response = response(&["A", "B"], &[]);
+        response.result_set_meta_data.row_type[1].nullable = true;
+        let body = serde_json::json!({ "data": data, "code": "090001", "statementHandle": "handle",
+            "statementStatusUrl": "", "requestId": "", "sqlState": "", "message": "" });
+        let parsed: SnowflakeSQLResponse = null::with_sentinel(&"\u{1}NULL".into(), || serde_json::from_value(body))?;
+        assert_eq!(&*parsed.null_sentinel, "\u{1}NULL");
+        response.data = parsed.data;
+        response.null_sentinel = parsed.null_sentinel;
+        let rows = response.deserialize::<(Option<String>, Option<String>)>()?;
+        assert_eq!(rows.data, [(Some("1".into()), None), (Some("null".into()), Some("x".into()))]);
+        assert!(null::is_null("null"), "sentinel is only in effect within its scope");
+        Ok(())
+    }
+
+    #[test]
+    fn typed_cells() -> Result<(), anyhow::Error> {
+        let data = serde_json::json!([["1", 2, -3.5, true, {"a": 1}, [1, 2]]]);
+        let rows = null::deserialize_rows(&data)?;
+        assert_eq!(rows, vec![vec!["1", "2", "-3.5", "true", r#"{"a":1}"#, "[1,2]"]]);
+        let mut response = response(&["A", "B", "C", "D"], &[]);
+        response.data = vec![rows[0][..4].to_vec()];
+        let result = response.deserialize::<(i64, i64, f64, bool)>()?;
+        assert_eq!(result.data, vec![(1, 2, -3.5, true)]);
+        Ok(())
+    }
+
+    #[test]
+    fn result_traits() -> Result<(), anyhow::Error> {
+        let mut result: SnowflakeSQLResult<u32> = (1..=3).collect();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result.first(), Some(&1));
+        for value in &mut result {
+            *value *= 2;
+        }
+        assert_eq!((&result).into_iter().sum::<u32>(), 12);
+        assert_eq!(serde_json::to_string(&result)?, "[2,4,6]");
+        assert_eq!(result.into_iter().collect::<Vec<_>>(), vec![2, 4, 6]);
+        assert!(SnowflakeSQLResult::<u32>::from(Vec::new()).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn shared_strings() -> Result<(), anyhow::Error> {
+        let result = response(&["A", "B", "C", "D"], &[&["a", "b", "c", "d"]])
+            .deserialize::<(Box<str>, std::rc::Rc<str>, std::sync::Arc<str>, std::borrow::Cow<'static, str>)>()?;
+        let (a, b, c, d) = &result.data[0];
+        assert_eq!((&**a, &**b, &**c, &**d), ("a", "b", "c", "d"));
+        assert_eq!(Option::<std::sync::Arc<str>>::deserialize_from_str("null")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn metadata_round_trip() -> Result<(), anyhow::Error> {
+        let metadata = response(&["A"], &[&["a"]]).result_set_meta_data;
+        let json = serde_json::to_value(&metadata)?;
+        assert_eq!(json["rowType"][0]["type"], "text");
+        assert_eq!(json["numRows"], 1);
+        let parsed: MetaData = serde_json::from_value(json.clone())?;
+        assert_eq!(serde_json::to_value(&parsed)?, json);
+        Ok(())
+    }
+
+    #[test]
+    fn comparable() -> Result<(), anyhow::Error> {
+        let response = response(&["A"], &[&["a"]]);
+        assert_eq!(response.clone(), response);
+        let result = response.deserialize::<String>()?;
+        assert_eq!(result.clone(), result);
+        assert_ne!(result, SnowflakeSQLResult::new(vec!["a".to_owned()]));
+        Ok(())
+    }
+}
@@ -0,0 +1,65 @@
+//! How `NULL` cells are represented.
+//!
+//! Cells are strings, a `NULL` cell is replaced by the sentinel, `null` unless another is given with [`with_sentinel`].
+//! `Option<T>` deserializes the sentinel as `None`, so a text column holding the literal
+//! string `null` is read as `None` too unless the sentinel is a value the data never holds.
+//!
+//! Responses keep the sentinel they were parsed with in [`SnowflakeSQLResponse::null_sentinel`](crate::SnowflakeSQLResponse::null_sentinel)
+//! and deserialize their rows with it.
+
+use std::{cell::RefCell, sync::Arc};
+
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+
+pub const DEFAULT_SENTINEL: &str = "null";
+
+thread_local! {
+    static SENTINEL: RefCell<Option<Arc<str>>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, rows it parses on this thread replace `NULL` cells by `sentinel` and cells equal to it are `NULL`.
+pub fn with_sentinel<R>(sentinel: &Arc<str>, f: impl FnOnce() -> R) -> R {
+    /// Restores the sentinel of an enclosing scope, also if `f` panics.
+    struct Scope(Option<Arc<str>>);
+    impl Drop for Scope {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            SENTINEL.with(|sentinel| *sentinel.borrow_mut() = previous);
+        }
+    }
+    let _scope = Scope(SENTINEL.with(|current| current.borrow_mut().replace(sentinel.clone())));
+    f()
+}
+
+/// Sentinel in effect on this thread, [`DEFAULT_SENTINEL`] outside of [`with_sentinel`].
+pub fn current() -> Arc<str> {
+    SENTINEL.with(|sentinel| sentinel.borrow().clone())
+        .unwrap_or_else(|| DEFAULT_SENTINEL.into())
+}
+
+pub fn sentinel() -> String {
+    current().to_string()
+}
+
+pub fn is_null(s: &str) -> bool {
+    SENTINEL.with(|sentinel| sentinel.borrow().as_deref().unwrap_or(DEFAULT_SENTINEL) == s)
+}
+
+/// Rows of a response, `NULL` cells become the sentinel in effect.
+///
+/// Cells are strings, but numbers, booleans, arrays and objects are accepted too and kept as their JSON text,
+/// so typed values are parsed the same as quoted ones by the column types of the result.
+pub fn deserialize_rows<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Vec<String>>, D::Error> {
+    let rows = Vec::<Vec<Value>>::deserialize(deserializer)?;
+    let sentinel = sentinel();
+    Ok(rows.into_iter()
+        .map(|row| row.into_iter()
+            .map(|cell| match cell {
+                Value::Null => sentinel.clone(),
+                Value::String(s) => s,
+                cell => cell.to_string(),
+            })
+            .collect())
+        .collect())
+}
@@ -0,0 +1,60 @@
+//! Statements checked at compile time, created with `snowflake_query!` and `snowflake_repository!`.
+
+use std::{borrow::Cow, marker::PhantomData};
+
+use crate::bindings::BindingValue;
+
+/// Statement along with its bindings, rows deserialize into `T`.
+#[derive(Debug)]
+pub struct Query<T> {
+    statement: Cow<'static, str>,
+    bindings: Vec<BindingValue>,
+    row: PhantomData<fn() -> T>,
+}
+
+impl<T> Query<T> {
+    pub fn new<S: Into<Cow<'static, str>>>(statement: S, bindings: Vec<BindingValue>) -> Self {
+        Query {
+            statement: statement.into(),
+            bindings,
+            row: PhantomData,
+        }
+    }
+    pub fn statement(&self) -> &str {
+        &self.statement
+    }
+    pub fn bindings(&self) -> &[BindingValue] {
+        &self.bindings
+    }
+    pub fn into_bindings(self) -> Vec<BindingValue> {
+        self.bindings
+    }
+    pub fn into_parts(self) -> (Cow<'static, str>, Vec<BindingValue>) {
+        (self.statement, self.bindings)
+    }
+}
+
+/// Statement that inserts, updates or deletes rows, along with its bindings.
+#[derive(Debug)]
+pub struct Manipulation {
+    statement: Cow<'static, str>,
+    bindings: Vec<BindingValue>,
+}
+
+impl Manipulation {
+    pub fn new<S: Into<Cow<'static, str>>>(statement: S, bindings: Vec<BindingValue>) -> Self {
+        Manipulation {
+            statement: statement.into(),
+            bindings,
+        }
+    }
+    pub fn statement(&self) -> &str {
+        &self.statement
+    }
+    pub fn bindings(&self) -> &[BindingValue] {
+        &self.bindings
+    }
+    pub fn into_parts(self) -> (Cow<'static, str>, Vec<BindingValue>) {
+        (self.statement, self.bindings)
+    }
+}
@@ -0,0 +1,106 @@
+//! Mask sensitive columns while deserializing, ex. PII code must not see unless it was explicitly unlocked.
+//!
+//! Fields marked `#[snowflake(redact)]` are replaced according to the [`RedactionPolicy`] set with
+//! [`with_policy`], outside of it they are replaced with [`PLACEHOLDER`]. `NULL` cells stay `NULL`.
+//! Replacements are text, so redacted fields should be `String` or `Option<String>`.
+
+use std::{borrow::Cow, cell::RefCell, fmt, sync::Arc};
+
+use crate::{null, RowType};
+
+/// Text redacted values are replaced with by default.
+pub const PLACEHOLDER: &str = "***";
+
+/// Replacement of a value given its column, see [`RedactionPolicy::Custom`].
+pub type Redactor = dyn Fn(&RowType, &str) -> String + Send + Sync;
+
+/// What redacted fields are deserialized from.
+#[derive(Clone)]
+pub enum RedactionPolicy {
+    /// Replace values with the text, [`PLACEHOLDER`] by default.
+    Placeholder(Cow<'static, str>),
+    /// Replace values with what the function returns for the column and value, ex. a keyed hash.
+    Custom(Arc<Redactor>),
+    /// Keep values as is.
+    Unlocked,
+}
+
+impl RedactionPolicy {
+    pub fn custom<F: Fn(&RowType, &str) -> String + Send + Sync + 'static>(redact: F) -> Self {
+        RedactionPolicy::Custom(Arc::new(redact))
+    }
+}
+
+impl Default for RedactionPolicy {
+    fn default() -> Self {
+        RedactionPolicy::Placeholder(Cow::Borrowed(PLACEHOLDER))
+    }
+}
+
+impl fmt::Debug for RedactionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedactionPolicy::Placeholder(placeholder) => f.debug_tuple("Placeholder").field(placeholder).finish(),
+            RedactionPolicy::Custom(_) => f.write_str("Custom"),
+            RedactionPolicy::Unlocked => f.write_str("Unlocked"),
+        }
+    }
+}
+
+thread_local! {
+    static POLICY: RefCell<Option<RedactionPolicy>> = const { RefCell::new(None) };
+}
+
+/// Run `f`, redacted fields it deserializes on this thread follow `policy`.
+pub fn with_policy<R>(policy: &RedactionPolicy, f: impl FnOnce() -> R) -> R {
+    /// Restores the policy of an enclosing scope, also if `f` panics.
+    struct Scope(Option<RedactionPolicy>);
+    impl Drop for Scope {
+        fn drop(&mut self) {
+            let previous = self.0.take();
+            POLICY.with(|policy| *policy.borrow_mut() = previous);
+        }
+    }
+    let _scope = Scope(POLICY.with(|current| current.borrow_mut().replace(policy.clone())));
+    f()
+}
+
+/// Cell of a `#[snowflake(redact)]` field after applying the policy in effect.
+pub fn apply<'c>(column: &RowType, cell: &'c str) -> Cow<'c, str> {
+    if null::is_null(cell) {
+        return Cow::Borrowed(cell);
+    }
+    POLICY.with(|policy| match &*policy.borrow() {
+        None => Cow::Borrowed(PLACEHOLDER),
+        Some(RedactionPolicy::Placeholder(placeholder)) => Cow::Owned(placeholder.to_string()),
+        Some(RedactionPolicy::Custom(redact)) => Cow::Owned(redact(column, cell)),
+        Some(RedactionPolicy::Unlocked) => Cow::Borrowed(cell),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn policies() {
+        let column: RowType = serde_json::from_value(serde_json::json!({
+            "name": "EMAIL",
+            "database": "DB",
+            "schema": "PUBLIC",
+            "table": "USERS",
+            "type": "text",
+            "nullable": true,
+        })).unwrap();
+        assert_eq!(apply(&column, "jo@example.com"), PLACEHOLDER);
+        assert_eq!(apply(&column, &null::sentinel()), null::sentinel());
+        with_policy(&RedactionPolicy::Unlocked, || {
+            assert_eq!(apply(&column, "jo@example.com"), "jo@example.com");
+            let custom = RedactionPolicy::custom(|column, value| format!("{}:{}", column.name, value.len()));
+            assert_eq!(with_policy(&custom, || apply(&column, "jo@example.com")), "EMAIL:14");
+            assert_eq!(apply(&column, "jo@example.com"), "jo@example.com");
+        });
+        let placeholder = RedactionPolicy::Placeholder("[redacted]".into());
+        assert_eq!(with_policy(&placeholder, || apply(&column, "jo@example.com")), "[redacted]");
+    }
+}
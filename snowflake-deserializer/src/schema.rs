@@ -0,0 +1,122 @@
+//! Schema of tables saved to a file, read at compile time by `snowflake_query!` to check statements.
+//!
+//! Generate it with `SnowflakeExecutor::fetch_schema` and commit it next to `Cargo.toml`
+//! as [`Schema::FILE_NAME`], regenerate it whenever the tables change.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Schema {
+    pub tables: Vec<TableSchema>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TableSchema {
+    /// Fully qualified name, ex. `DB.SCHEMA.TABLE`.
+    pub name: String,
+    /// Columns in order.
+    pub columns: Vec<ColumnSchema>,
+    /// Comment of the table or view.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ColumnSchema {
+    pub name: String,
+    /// Snowflake data type, ex. `NUMBER(38,0)` or `TEXT`.
+    pub data_type: String,
+    pub nullable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl Schema {
+    pub const FILE_NAME: &'static str = "snowflake_schema.json";
+    /// Environment variable with the path of the schema file, relative to `Cargo.toml`.
+    pub const PATH_VARIABLE: &'static str = "SNOWFLAKE_SCHEMA";
+
+    /// Path in [`PATH_VARIABLE`](Self::PATH_VARIABLE), or [`FILE_NAME`](Self::FILE_NAME) next to `Cargo.toml`
+    /// of the crate being built.
+    pub fn path() -> PathBuf {
+        let manifest_dir = PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+        match std::env::var(Self::PATH_VARIABLE) {
+            Ok(path) => manifest_dir.join(path),
+            Err(_) => manifest_dir.join(Self::FILE_NAME),
+        }
+    }
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, anyhow::Error> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("could not read schema {}, generate it with `fetch_schema`", path.display()))?;
+        Self::from_json(&json)
+            .with_context(|| format!("could not parse schema {}", path.display()))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, anyhow::Error> {
+        Ok(serde_json::from_str(json)?)
+    }
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("schema is always valid json")
+    }
+    /// Table named `name`, unquoted parts of the name match ignoring case.
+    pub fn table(&self, name: &str) -> Option<&TableSchema> {
+        let name = normalize_name(name);
+        self.tables.iter().find(|table| normalize_name(&table.name) == name)
+    }
+}
+
+impl TableSchema {
+    /// Column named `name`, an unquoted name matches ignoring case.
+    pub fn column(&self, name: &str) -> Option<&ColumnSchema> {
+        let name = normalize_identifier(name);
+        self.columns.iter().find(|column| column.name == name)
+    }
+}
+
+/// Unquoted identifiers are stored uppercase, quoted identifiers as is.
+pub fn normalize_identifier(identifier: &str) -> String {
+    let identifier = identifier.trim();
+    match identifier.strip_prefix('"').and_then(|identifier| identifier.strip_suffix('"')) {
+        Some(quoted) => quoted.replace("\"\"", "\""),
+        None => identifier.to_ascii_uppercase(),
+    }
+}
+
+fn normalize_name(name: &str) -> Vec<String> {
+    name.split('.').map(normalize_identifier).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup() -> Result<(), anyhow::Error> {
+        let schema = Schema::from_json(r#"{ "tables": [{
+            "name": "DB.PUBLIC.USERS",
+            "columns": [{ "name": "ID", "data_type": "NUMBER(38,0)", "nullable": false }]
+        }] }"#)?;
+        let table = schema.table("db.public.users").unwrap();
+        assert!(table.column("id").is_some());
+        assert!(table.column("\"id\"").is_none());
+        assert!(schema.table("DB.OTHER.USERS").is_none());
+        assert_eq!(Schema::from_json(&schema.to_json())?, schema);
+        Ok(())
+    }
+
+    #[test]
+    fn read() -> Result<(), anyhow::Error> {
+        let path = std::env::temp_dir().join(format!("snowflake-schema-{}.json", std::process::id()));
+        let error = Schema::read(&path).unwrap_err();
+        assert!(format!("{error:#}").contains("generate it with `fetch_schema`"));
+        let schema = Schema { tables: Vec::new() };
+        std::fs::write(&path, schema.to_json())?;
+        assert_eq!(Schema::read(&path)?, schema);
+        std::fs::remove_file(path)?;
+        Ok(())
+    }
+}
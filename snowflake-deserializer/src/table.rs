@@ -0,0 +1,38 @@
+/// Static description of a Snowflake table a type maps to,
+/// used to introspect tables at runtime, ex. to detect schema drift.
+pub trait SnowflakeTable {
+    /// Fully qualified name, ex. `DB.SCHEMA.TABLE`.
+    const TABLE: &'static str;
+    /// Columns in order.
+    const COLUMNS: &'static [ColumnMeta];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColumnMeta {
+    pub name: &'static str,
+    /// Snowflake data type, ex. `NUMBER(38,0)` or `VARCHAR`.
+    pub data_type: &'static str,
+    pub nullable: bool,
+}
+
+/// Base name of a data type as reported by `INFORMATION_SCHEMA.COLUMNS.DATA_TYPE`,
+/// ex. `VARCHAR(16)` and `STRING` both become `TEXT`.
+pub fn normalize_data_type(data_type: &str) -> String {
+    let base = data_type
+        .split('(')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_uppercase();
+    match base.as_str() {
+        "VARCHAR" | "STRING" | "CHAR" | "CHARACTER" | "NCHAR" | "NVARCHAR" | "NVARCHAR2" | "CHAR VARYING" | "NCHAR VARYING"
+            => "TEXT".into(),
+        "INT" | "INTEGER" | "BIGINT" | "SMALLINT" | "TINYINT" | "BYTEINT" | "DECIMAL" | "DEC" | "NUMERIC"
+            => "NUMBER".into(),
+        "DOUBLE" | "DOUBLE PRECISION" | "REAL" | "FLOAT4" | "FLOAT8"
+            => "FLOAT".into(),
+        "DATETIME" | "TIMESTAMP" => "TIMESTAMP_NTZ".into(),
+        "VARBINARY" => "BINARY".into(),
+        _ => base,
+    }
+}
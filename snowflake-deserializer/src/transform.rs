@@ -0,0 +1,136 @@
+//! Rewrite raw cells before they are deserialized, ex. to trim padded text or map legacy sentinels like `N/A` to `NULL`.
+//!
+//! A [`RowFilter`] runs the transforms given for every column, then those given for the cell's column, in order.
+//! Columns are matched by the name Snowflake reports, unquoted identifiers are upper case.
+//! `NULL` cells are left as is.
+//!
+//! ```
+//! # use snowflake_deserializer::transform::{CellTransform, RowFilter};
+//! let filter = RowFilter::new()
+//!     .with(CellTransform::Trim)
+//!     .with_column("SCORE", CellTransform::null_if(["N/A", "-"]))
+//!     .with_column("NAME", CellTransform::map(|cell| Some(cell.replace('\u{a0}', " "))));
+//! ```
+
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use crate::SnowflakeSQLResponse;
+
+/// New content of a cell given its text, `None` for `NULL`, see [`CellTransform::Map`].
+pub type CellMap = dyn Fn(&str) -> Option<String> + Send + Sync;
+
+/// Change to the text of a cell.
+#[derive(Clone)]
+pub enum CellTransform {
+    /// Remove leading and trailing whitespace.
+    Trim,
+    /// `NULL` if the cell is one of the values, ex. `N/A`.
+    NullIf(Vec<String>),
+    /// Whatever the function returns, ex. to normalize the encoding of text.
+    Map(Arc<CellMap>),
+}
+
+impl CellTransform {
+    pub fn null_if<V: Into<String>>(values: impl IntoIterator<Item = V>) -> Self {
+        CellTransform::NullIf(values.into_iter().map(Into::into).collect())
+    }
+    pub fn map<F: Fn(&str) -> Option<String> + Send + Sync + 'static>(map: F) -> Self {
+        CellTransform::Map(Arc::new(map))
+    }
+    /// `cell` after the transform, `None` for `NULL`.
+    fn apply(&self, cell: String) -> Option<String> {
+        match self {
+            CellTransform::Trim => match cell.trim() {
+                trimmed if trimmed.len() == cell.len() => Some(cell),
+                trimmed => Some(trimmed.to_owned()),
+            },
+            CellTransform::NullIf(values) => (!values.contains(&cell)).then_some(cell),
+            CellTransform::Map(map) => map(&cell),
+        }
+    }
+}
+
+impl fmt::Debug for CellTransform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CellTransform::Trim => f.write_str("Trim"),
+            CellTransform::NullIf(values) => f.debug_tuple("NullIf").field(values).finish(),
+            CellTransform::Map(_) => f.write_str("Map"),
+        }
+    }
+}
+
+/// Transforms of raw cells, for every column or by column name.
+#[derive(Clone, Debug, Default)]
+pub struct RowFilter {
+    every_column: Vec<CellTransform>,
+    columns: HashMap<String, Vec<CellTransform>>,
+}
+
+impl RowFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Apply `transform` to every column.
+    pub fn with(mut self, transform: CellTransform) -> Self {
+        self.every_column.push(transform);
+        self
+    }
+    /// Apply `transform` to the column named `column`.
+    pub fn with_column(mut self, column: impl Into<String>, transform: CellTransform) -> Self {
+        self.columns.entry(column.into()).or_default().push(transform);
+        self
+    }
+    pub fn is_empty(&self) -> bool {
+        self.every_column.is_empty() && self.columns.is_empty()
+    }
+    /// Transform the rows of `response` in place.
+    pub fn apply(&self, response: &mut SnowflakeSQLResponse) {
+        if self.is_empty() {
+            return;
+        }
+        let transforms: Vec<Vec<&CellTransform>> = response.result_set_meta_data.row_type.iter()
+            .map(|column| self.every_column.iter()
+                .chain(self.columns.get(&column.name).into_iter().flatten())
+                .collect())
+            .collect();
+        let sentinel = response.null_sentinel.to_string();
+        for row in &mut response.data {
+            for (cell, transforms) in row.iter_mut().zip(&transforms) {
+                if transforms.is_empty() || *cell == sentinel {
+                    continue;
+                }
+                let mut value = Some(std::mem::take(cell));
+                for transform in transforms {
+                    value = value.and_then(|value| transform.apply(value));
+                }
+                *cell = value.unwrap_or_else(|| sentinel.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::response;
+
+    use super::*;
+
+    #[test]
+    fn transformed() {
+        let filter = RowFilter::new()
+            .with(CellTransform::Trim)
+            .with_column("SCORE", CellTransform::null_if(["N/A"]))
+            .with_column("NAME", CellTransform::map(|cell| Some(cell.to_uppercase())));
+        let mut response = response(&["NAME", "SCORE"], &[&[" ada ", " N/A"], &["bob", "7 "]]);
+        response.null_sentinel = "\u{1}NULL".into();
+        response.data.push(vec![response.null_sentinel.to_string(); 2]);
+        filter.apply(&mut response);
+        let sentinel = response.null_sentinel.to_string();
+        assert_eq!(response.data, [
+            vec!["ADA".to_owned(), sentinel.clone()],
+            vec!["BOB".to_owned(), "7".to_owned()],
+            vec![sentinel.clone(), sentinel],
+        ]);
+    }
+}
@@ -0,0 +1,165 @@
+use std::sync::OnceLock;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+
+use crate::DeserializeFromStr;
+
+/// Semi-structured value from a `VARIANT`, `OBJECT` or `ARRAY` column.
+///
+/// The cell is kept as the raw string Snowflake sent and is only parsed
+/// the first time it is navigated, so unused variant columns cost nothing.
+#[derive(Clone, Debug, Default)]
+pub struct Variant {
+    raw: String,
+    value: OnceLock<Value>,
+}
+
+impl Variant {
+    pub fn new<S: Into<String>>(raw: S) -> Self {
+        Variant {
+            raw: raw.into(),
+            value: OnceLock::new(),
+        }
+    }
+    /// The cell exactly as Snowflake returned it.
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+    /// Parsed JSON value, parsed on first access.
+    pub fn value(&self) -> Result<&Value, anyhow::Error> {
+        if let Some(value) = self.value.get() {
+            return Ok(value);
+        }
+        let value = serde_json::from_str(&self.raw)?;
+        Ok(self.value.get_or_init(|| value))
+    }
+    /// Navigate to a nested value, ex. `a.b[0].c`.
+    ///
+    /// Returns `None` if any segment of the path does not exist.
+    pub fn get_path(&self, path: &str) -> Result<Option<Variant>, anyhow::Error> {
+        let mut value = self.value()?;
+        for segment in parse_path(path)? {
+            let next = match segment {
+                PathSegment::Key(key) => value.get(key),
+                PathSegment::Index(index) => value.get(index),
+            };
+            match next {
+                Some(next) => value = next,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(value.clone().into()))
+    }
+    /// Deserialize the value into any type implementing `serde::Deserialize`.
+    pub fn as_<T: DeserializeOwned>(&self) -> Result<T, anyhow::Error> {
+        Ok(T::deserialize(self.value()?)?)
+    }
+    pub fn into_value(self) -> Result<Value, anyhow::Error> {
+        match self.value.into_inner() {
+            Some(value) => Ok(value),
+            None => Ok(serde_json::from_str(&self.raw)?),
+        }
+    }
+}
+
+impl From<Value> for Variant {
+    fn from(value: Value) -> Self {
+        Variant {
+            raw: value.to_string(),
+            value: OnceLock::from(value),
+        }
+    }
+}
+
+impl PartialEq for Variant {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.value(), other.value()) {
+            (Ok(value), Ok(other)) => value == other,
+            _ => self.raw == other.raw,
+        }
+    }
+}
+
+/// Serialized as the JSON value of the cell.
+impl Serialize for Variant {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value()
+            .map_err(serde::ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Variant {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Value::deserialize(deserializer).map(Variant::from)
+    }
+}
+
+impl DeserializeFromStr for Variant {
+    type Err = anyhow::Error;
+    fn deserialize_from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Variant::new(s))
+    }
+}
+
+enum PathSegment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+fn parse_path(path: &str) -> Result<Vec<PathSegment<'_>>, anyhow::Error> {
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        let (key, mut indices) = match part.find('[') {
+            Some(start) => part.split_at(start),
+            None => (part, ""),
+        };
+        if !key.is_empty() {
+            segments.push(PathSegment::Key(key));
+        } else if indices.is_empty() {
+            anyhow::bail!("empty segment in variant path `{path}`");
+        }
+        while !indices.is_empty() {
+            let end = indices.find(']')
+                .ok_or_else(|| anyhow::anyhow!("unclosed `[` in variant path `{path}`"))?;
+            if !indices.starts_with('[') {
+                anyhow::bail!("unexpected `{indices}` in variant path `{path}`");
+            }
+            let index = indices[1..end].parse()
+                .map_err(|_| anyhow::anyhow!("invalid index `{}` in variant path `{path}`", &indices[1..end]))?;
+            segments.push(PathSegment::Index(index));
+            indices = &indices[end + 1..];
+        }
+    }
+    Ok(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_path() -> Result<(), anyhow::Error> {
+        let variant = Variant::new(r#"{"a": {"b": [{"c": 69}, {"c": 420}]}, "d": [[1, 2]]}"#);
+        assert_eq!(variant.get_path("a.b[1].c")?.unwrap().as_::<u32>()?, 420);
+        assert_eq!(variant.get_path("d[0][1]")?.unwrap().as_::<u8>()?, 2);
+        assert!(variant.get_path("a.x")?.is_none());
+        assert!(variant.get_path("a.b[5]")?.is_none());
+        assert!(variant.get_path("a..b").is_err());
+        assert!(variant.get_path("a.b[0").is_err());
+        fn assert_sync<T: Sync>(_: &T) {}
+        assert_sync(&variant);
+        Ok(())
+    }
+
+    #[test]
+    fn serde() -> Result<(), anyhow::Error> {
+        let variant = Variant::new(r#"{"a": [1, 2]}"#);
+        let json = serde_json::to_string(&variant)?;
+        assert_eq!(json, r#"{"a":[1,2]}"#);
+        assert_eq!(serde_json::from_str::<Variant>(&json)?, variant);
+        assert!(serde_json::to_string(&Variant::new("{")).is_err());
+        Ok(())
+    }
+}
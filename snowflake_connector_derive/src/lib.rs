@@ -1,58 +1,488 @@
 extern crate proc_macro;
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{self, parse_macro_input, DeriveInput, Data, Fields};
+use syn::{self, parse_macro_input, DeriveInput, Data, Fields, Attribute, Lit, LitStr, Meta, NestedMeta, Path, Type, PathArguments, GenericArgument};
 
-#[proc_macro_derive(SnowflakeDeserialize)]
+mod query;
+mod repository;
+
+/// `#[snowflake(table = "DB.SCHEMA.TABLE")]` on the struct also implements `SnowflakeTable`,
+/// column types are inferred from field types unless given with `#[snowflake(data_type = "NUMBER(38,2)")]`.
+///
+/// Columns are mapped to fields in order and named after the uppercase field name,
+/// `#[snowflake(rename = "COL")]` names the column explicitly and checks the query returned it at that position.
+/// `#[snowflake(flatten)]` maps the next columns into a nested struct that also derives `SnowflakeDeserialize`.
+/// `#[snowflake(keep_raw)]` on a `Vec<String>` field keeps the raw cells of the row, it consumes no column.
+/// `#[snowflake(redact)]` replaces the cell according to the `redact::RedactionPolicy` in effect before deserializing it.
+///
+/// `#[snowflake(by_name)]` on the struct maps columns to fields by name instead of position,
+/// columns without a field are ignored and `Option` fields whose column is missing are `None`,
+/// so adding or dropping a nullable column does not break deserialization.
+///
+/// `#[snowflake(post_deserialize = "path::to::fn")]` on the struct calls `fn(&mut Self)` on every row
+/// right after it was deserialized, ex. to trim or normalize values without a second pass over the result.
+#[proc_macro_derive(SnowflakeDeserialize, attributes(snowflake))]
 pub fn snowflake_deserialize_derive(input: TokenStream) -> TokenStream {
     let ast: DeriveInput = parse_macro_input!(input);
     impl_snowflake_deserialize(&ast)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Expands to the code `#[derive(SnowflakeDeserialize)]` generates for the given struct,
+/// pretty printed as a `&'static str`, for golden tests of generated code.
+#[proc_macro]
+pub fn expand_for_test(input: TokenStream) -> TokenStream {
+    let ast: DeriveInput = parse_macro_input!(input);
+    let expanded = match impl_snowflake_deserialize(&ast) {
+        Ok(expanded) => expanded,
+        Err(e) => return e.into_compile_error().into(),
+    };
+    let expanded = match syn::parse2::<syn::File>(expanded.clone()) {
+        Ok(file) => prettyplease::unparse(&file),
+        Err(_) => expanded.to_string(),
+    };
+    quote!(#expanded).into()
+}
+
+/// `snowflake_query!("SELECT ID, NAME FROM DB.PUBLIC.USERS WHERE ID = ?", id)` checks the statement against
+/// the schema file saved with `fetch_schema` at compile time and evaluates to a `query::Query`
+/// whose rows are a struct with a field per selected column, run it with `SnowflakeExecutor::query`.
+///
+/// The schema file is `snowflake_schema.json` next to `Cargo.toml`, or the path in `SNOWFLAKE_SCHEMA`.
+/// Only `SELECT` of plain column names or `*` from a single table can be checked,
+/// every `?` placeholder needs a binding.
+#[proc_macro]
+pub fn snowflake_query(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as query::QueryInput);
+    query::impl_snowflake_query(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// `snowflake_repository!(User, "DB.PUBLIC.USERS", primary_key = ["ID"])` generates, from the schema file
+/// `snowflake_query!` uses, the row struct `User` with a field per column, a `UserFilter` with an optional
+/// field per column and a `UserRepository` whose `find_by_pk`, `insert`, `upsert`, `update`, `delete` and `list`
+/// return parameterized statements, run them with `SnowflakeExecutor::query` and `SnowflakeExecutor::manipulate`.
+///
+/// `upsert` merges rows on the primary key, `update` is only generated when the table has columns outside of it.
+#[proc_macro]
+pub fn snowflake_repository(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as repository::RepositoryInput);
+    repository::impl_snowflake_repository(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
 
-fn impl_snowflake_deserialize(ast: &DeriveInput) -> TokenStream {
+fn impl_snowflake_deserialize(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = &ast.ident;
     let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let attributes = ContainerAttributes::parse(&ast.attrs)?;
 
-    let (t_name, t_index, t_ty) = match &ast.data {
+    let fields = match &ast.data {
         Data::Struct(data) => {
             match &data.fields {
-                Fields::Named(data) => {
-                    let count = data.named.len();
-                    let mut t_name = Vec::with_capacity(count);
-                    let mut t_index = Vec::with_capacity(count);
-                    let mut t_ty = Vec::with_capacity(count);
-                    for (i, field) in data.named.iter().enumerate() {
-                        let name = field.ident.as_ref().unwrap();
-                        let ty = &field.ty;
-                        t_name.push(name);
-                        t_index.push(i);
-                        t_ty.push(ty);
-                    }
-                    (t_name, t_index, t_ty)
+                Fields::Named(fields) => fields,
+                Fields::Unnamed(fields) => return Err(syn::Error::new_spanned(
+                    fields,
+                    "SnowflakeDeserialize requires named fields, columns are mapped to fields in order",
+                )),
+                Fields::Unit => return Err(syn::Error::new_spanned(
+                    name,
+                    "SnowflakeDeserialize can not be derived for a unit struct, it has no fields to map columns to",
+                )),
+            }
+        },
+        Data::Enum(data) => return Err(syn::Error::new_spanned(
+            data.enum_token,
+            "SnowflakeDeserialize can only be derived for a struct, implement DeserializeFromStr for enums instead",
+        )),
+        Data::Union(data) => return Err(syn::Error::new_spanned(
+            data.union_token,
+            "SnowflakeDeserialize can only be derived for a struct, not a union",
+        )),
+    };
+    let count = fields.named.len();
+    let mut t_attributes = Vec::with_capacity(count);
+    for field in &fields.named {
+        t_attributes.push(FieldAttributes::parse(&field.attrs)?);
+    }
+    if attributes.by_name.is_some() {
+        if let Some(flatten) = t_attributes.iter().find_map(|attributes| attributes.flatten.as_ref()) {
+            return Err(syn::Error::new_spanned(
+                flatten,
+                "`flatten` can not be combined with `by_name`, nested columns are mapped by position",
+            ));
+        }
+    }
+    if attributes.table.is_some() {
+        if let Some(flatten) = t_attributes.iter().find_map(|attributes| attributes.flatten.as_ref()) {
+            return Err(syn::Error::new_spanned(
+                flatten,
+                "`flatten` can not be combined with `table`, the nested columns are unknown to SnowflakeTable",
+            ));
+        }
+    }
+
+    // Offset of each field is the number of plain columns before it plus the width of flattened fields before it.
+    let mut columns = 0usize;
+    let mut flattened = Vec::new();
+    let mut t_check = Vec::new();
+    let mut t_field = Vec::with_capacity(count);
+    // Resolved by `check_row_types` once per response, the positions of columns looked up by name
+    // and the columns of flattened fields.
+    let mut t_column_ty = Vec::new();
+    let mut t_column = Vec::new();
+    let by_name = attributes.by_name.is_some();
+    for (field, attributes) in fields.named.iter().zip(&t_attributes) {
+        let name = field.ident.as_ref().expect("named fields have an identifier");
+        let ty = &field.ty;
+        let offset = quote! { #columns #(+ <#flattened as SnowflakeDeserializeRow>::WIDTH)* };
+        let resolved = syn::Index::from(t_column.len());
+        let cell = |index: proc_macro2::TokenStream| match attributes.redact {
+            Some(_) => quote! { &redact::apply(&row_types[#index], &row[#index]) },
+            None => quote! { &row[#index] },
+        };
+        if attributes.keep_raw.is_some() {
+            t_field.push(quote! {
+                #name: row.to_vec()
+            });
+        } else if attributes.flatten.is_some() {
+            t_column_ty.push(quote! { <#ty as SnowflakeDeserializeRow>::Columns });
+            t_column.push(quote! {
+                <#ty as SnowflakeDeserializeRow>::check_row_types(&row_types[#offset..])?
+            });
+            t_field.push(quote! {
+                #name: <#ty as SnowflakeDeserializeRow>::deserialize_row(&row_types[#offset..], &columns.#resolved, &row[#offset..])?
+            });
+            flattened.push(ty);
+        } else if by_name {
+            let column = column_name(field, attributes);
+            let cell = cell(quote! { index });
+            match option_inner(ty) {
+                Some(_) => {
+                    t_column_ty.push(quote! { Option<usize> });
+                    t_column.push(quote! { coercion::column_index(row_types, #column) });
+                    t_field.push(quote! {
+                        #name: match columns.#resolved {
+                            Some(index) => <#ty>::deserialize_from_str(#cell)
+                                .map_err(|e| coercion::ColumnMismatch::new::<#ty, _>(&row_types[index], e))?,
+                            None => None,
+                        }
+                    });
+                },
+                None => {
+                    t_column_ty.push(quote! { usize });
+                    t_column.push(quote! { coercion::expect_column::<Self>(row_types, #column)? });
+                    t_field.push(quote! {
+                        #name: {
+                            let index = columns.#resolved;
+                            <#ty>::deserialize_from_str(#cell)
+                                .map_err(|e| coercion::ColumnMismatch::new::<#ty, _>(&row_types[index], e))?
+                        }
+                    });
                 },
-                _ => panic!("Named fields only!"),
             }
+            columns += 1;
+        } else {
+            if let Some(rename) = &attributes.rename {
+                t_check.push(quote! {
+                    coercion::expect_column_name(&row_types[#offset], #rename)?;
+                });
+            }
+            let cell = cell(offset.clone());
+            t_field.push(quote! {
+                #name: <#ty>::deserialize_from_str(#cell)
+                    .map_err(|e| coercion::ColumnMismatch::new::<#ty, _>(&row_types[#offset], e))?
+            });
+            columns += 1;
+        }
+    }
+    // Columns are looked up by name in `by_name` mode, the response may have more or fewer of them.
+    let expect_columns = match by_name {
+        true => quote! {},
+        false => quote! { coercion::expect_columns::<Self>(row_types, Self::WIDTH)?; },
+    };
+    let width = quote! { #columns #(+ <#flattened as SnowflakeDeserializeRow>::WIDTH)* };
+    let resolved = match t_column.is_empty() {
+        true => quote! { _columns },
+        false => quote! { columns },
+    };
+    let table = match &attributes.table {
+        Some(table) => snowflake_table(ast, table, &fields.named, &t_attributes),
+        None => quote! {},
+    };
+    let deserialize_row = match &attributes.post_deserialize {
+        Some(post_deserialize) => quote! {
+            let mut value = Self {
+                #(#t_field),*
+            };
+            #post_deserialize(&mut value);
+            Ok(value)
+        },
+        None => quote! {
+            Ok(Self {
+                #(#t_field),*
+            })
         },
-        Data::Enum(_) => panic!("This macro can only be derived in a struct, not enum."),
-        Data::Union(_) => panic!("This macro can only be derived in a struct, not union."),
     };
     let gen = quote! {
+        impl #impl_generics SnowflakeDeserializeRow for #name #ty_generics #where_clause {
+            const WIDTH: usize = #width;
+            type Columns = (#(#t_column_ty,)*);
+            fn check_row_types(row_types: &[RowType]) -> Result<Self::Columns, anyhow::Error> {
+                #expect_columns
+                #(#t_check)*
+                Ok((#(#t_column,)*))
+            }
+            fn deserialize_row(row_types: &[RowType], #resolved: &Self::Columns, row: &[String]) -> Result<Self, anyhow::Error> {
+                #deserialize_row
+            }
+        }
         impl #impl_generics SnowflakeDeserialize for #name #ty_generics #where_clause {
             fn snowflake_deserialize(
                 response: SnowflakeSQLResponse,
             ) -> Result<SnowflakeSQLResult<Self>, anyhow::Error> {
-                let count = response.result_set_meta_data.num_rows;
-                let mut results = Vec::with_capacity(count);
-                for data in response.data {
-                    results.push(#name #ty_generics {
-                        #(#t_name: <#t_ty>::deserialize_from_str(&data[#t_index])?),*
-                    });
+                let row_types = &response.result_set_meta_data.row_type;
+                let columns = <Self as SnowflakeDeserializeRow>::check_row_types(row_types)?;
+                let mut results = Vec::with_capacity(response.data.len());
+                for (index, row) in response.data.into_iter().enumerate() {
+                    let result = <Self as SnowflakeDeserializeRow>::deserialize_row(row_types, &columns, &row)
+                        .map_err(|source| RowError { index, raw: row, source })?;
+                    results.push(result);
+                }
+                Ok(SnowflakeSQLResult::new(results))
+            }
+        }
+        #table
+    };
+    Ok(gen)
+}
+
+fn snowflake_table(
+    ast: &DeriveInput,
+    table: &LitStr,
+    fields: &syn::punctuated::Punctuated<syn::Field, syn::token::Comma>,
+    attributes: &[FieldAttributes],
+) -> proc_macro2::TokenStream {
+    let name = &ast.ident;
+    let (impl_generics, ty_generics, where_clause) = ast.generics.split_for_impl();
+    let columns = fields.iter()
+        .zip(attributes)
+        .filter(|(_, attributes)| attributes.keep_raw.is_none())
+        .map(|(field, attributes)| {
+            let column = column_name(field, attributes);
+            let (nullable, ty) = match option_inner(&field.ty) {
+                Some(ty) => (true, ty),
+                None => (false, &field.ty),
+            };
+            let data_type = match &attributes.data_type {
+                Some(data_type) => data_type.value(),
+                None => infer_data_type(ty).into(),
+            };
+            quote! {
+                table::ColumnMeta {
+                    name: #column,
+                    data_type: #data_type,
+                    nullable: #nullable,
+                }
+            }
+        });
+    quote! {
+        impl #impl_generics table::SnowflakeTable for #name #ty_generics #where_clause {
+            const TABLE: &'static str = #table;
+            const COLUMNS: &'static [table::ColumnMeta] = &[#(#columns),*];
+        }
+    }
+}
+
+/// `#[snowflake(...)]` on the struct.
+#[derive(Default)]
+struct ContainerAttributes {
+    table: Option<LitStr>,
+    by_name: Option<Path>,
+    post_deserialize: Option<syn::ExprPath>,
+}
+
+impl ContainerAttributes {
+    const KEYS: &'static [&'static str] = &["table", "by_name", "post_deserialize"];
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut attributes = ContainerAttributes::default();
+        parse_snowflake_attributes(attrs, |path, value| {
+            if path.is_ident("table") {
+                let table = string_value(path, value)?;
+                if table.value().trim().is_empty() {
+                    return Err(syn::Error::new_spanned(table, "table name can not be empty"));
                 }
-                Ok(SnowflakeSQLResult {
-                    data: results,
-                })
+                set_once(&mut attributes.table, path, table)
+            } else if path.is_ident("by_name") {
+                flag_value(path, value)?;
+                set_once(&mut attributes.by_name, path, path.clone())
+            } else if path.is_ident("post_deserialize") {
+                let function = string_value(path, value)?.parse::<syn::ExprPath>()?;
+                set_once(&mut attributes.post_deserialize, path, function)
+            } else {
+                Err(unknown_attribute(path, Self::KEYS))
+            }
+        })?;
+        Ok(attributes)
+    }
+}
+
+/// `#[snowflake(...)]` on a field.
+#[derive(Default)]
+struct FieldAttributes {
+    rename: Option<LitStr>,
+    data_type: Option<LitStr>,
+    flatten: Option<Path>,
+    keep_raw: Option<Path>,
+    redact: Option<Path>,
+}
+
+impl FieldAttributes {
+    const KEYS: &'static [&'static str] = &["rename", "data_type", "flatten", "keep_raw", "redact"];
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut attributes = FieldAttributes::default();
+        parse_snowflake_attributes(attrs, |path, value| {
+            if path.is_ident("rename") {
+                let rename = string_value(path, value)?;
+                if rename.value().is_empty() {
+                    return Err(syn::Error::new_spanned(rename, "column name can not be empty"));
+                }
+                set_once(&mut attributes.rename, path, rename)
+            } else if path.is_ident("data_type") {
+                set_once(&mut attributes.data_type, path, string_value(path, value)?)
+            } else if path.is_ident("flatten") {
+                flag_value(path, value)?;
+                set_once(&mut attributes.flatten, path, path.clone())
+            } else if path.is_ident("keep_raw") {
+                flag_value(path, value)?;
+                set_once(&mut attributes.keep_raw, path, path.clone())
+            } else if path.is_ident("redact") {
+                flag_value(path, value)?;
+                set_once(&mut attributes.redact, path, path.clone())
+            } else {
+                Err(unknown_attribute(path, Self::KEYS))
+            }
+        })?;
+        if let Some(keep_raw) = &attributes.keep_raw {
+            if attributes.rename.is_some() || attributes.data_type.is_some() || attributes.flatten.is_some() || attributes.redact.is_some() {
+                return Err(syn::Error::new_spanned(keep_raw, "`keep_raw` can not be combined with other attributes"));
+            }
+        }
+        if let Some(flatten) = &attributes.flatten {
+            if attributes.rename.is_some() || attributes.data_type.is_some() || attributes.redact.is_some() {
+                return Err(syn::Error::new_spanned(
+                    flatten,
+                    "`flatten` can not be combined with `rename`, `data_type` or `redact`, annotate the nested struct's fields instead",
+                ));
+            }
+        }
+        Ok(attributes)
+    }
+}
+
+/// Calls `f` with every `key` or `key = value` inside `#[snowflake(...)]` attributes.
+fn parse_snowflake_attributes<F>(attrs: &[Attribute], mut f: F) -> syn::Result<()>
+    where F: FnMut(&Path, Option<&Lit>) -> syn::Result<()>
+{
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("snowflake")) {
+        let list = match attr.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(syn::Error::new_spanned(meta, "expected #[snowflake(...)]")),
+        };
+        for nested in &list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) => f(path, None)?,
+                NestedMeta::Meta(Meta::NameValue(value)) => f(&value.path, Some(&value.lit))?,
+                nested => return Err(syn::Error::new_spanned(nested, "expected `key` or `key = \"value\"`")),
             }
         }
+    }
+    Ok(())
+}
+
+fn string_value(path: &Path, value: Option<&Lit>) -> syn::Result<LitStr> {
+    let key = path_name(path);
+    match value {
+        Some(Lit::Str(lit)) => Ok(lit.clone()),
+        Some(lit) => Err(syn::Error::new_spanned(lit, format!("expected a string, ex. `{key} = \"...\"`"))),
+        None => Err(syn::Error::new_spanned(path, format!("expected a value, ex. `{key} = \"...\"`"))),
+    }
+}
+
+fn flag_value(path: &Path, value: Option<&Lit>) -> syn::Result<()> {
+    match value {
+        Some(lit) => Err(syn::Error::new_spanned(lit, format!("`{}` does not take a value", path_name(path)))),
+        None => Ok(()),
+    }
+}
+
+fn set_once<T>(slot: &mut Option<T>, path: &Path, value: T) -> syn::Result<()> {
+    if slot.is_some() {
+        return Err(syn::Error::new_spanned(path, format!("duplicate attribute `{}`", path_name(path))));
+    }
+    *slot = Some(value);
+    Ok(())
+}
+
+fn unknown_attribute(path: &Path, keys: &[&str]) -> syn::Error {
+    let expected = keys.iter()
+        .map(|key| format!("`{key}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    syn::Error::new_spanned(path, format!("unknown attribute `{}`, expected one of {expected}", path_name(path)))
+}
+
+fn path_name(path: &Path) -> String {
+    quote!(#path).to_string().replace(' ', "")
+}
+
+/// Name of the column a field maps to, its `rename` or else the uppercase field name.
+fn column_name(field: &syn::Field, attributes: &FieldAttributes) -> String {
+    match &attributes.rename {
+        Some(rename) => rename.value(),
+        None => field.ident.as_ref().expect("named fields have an identifier").to_string().to_ascii_uppercase(),
+    }
+}
+
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None,
+    };
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) => match arguments.args.first()? {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn infer_data_type(ty: &Type) -> &'static str {
+    let ident = match ty {
+        Type::Path(path) => match path.path.segments.last() {
+            Some(segment) => segment.ident.to_string(),
+            None => return "VARCHAR",
+        },
+        _ => return "VARCHAR",
     };
-    gen.into()
+    match ident.as_str() {
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" |
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize"
+            => "NUMBER(38,0)",
+        "Decimal" => "NUMBER",
+        "f32" | "f64" => "FLOAT",
+        "bool" => "BOOLEAN",
+        "NaiveDate" => "DATE",
+        "NaiveTime" => "TIME",
+        "NaiveDateTime" => "TIMESTAMP_NTZ",
+        "DateTime" => "TIMESTAMP_TZ",
+        "Variant" => "VARIANT",
+        _ => "VARCHAR",
+    }
 }
@@ -0,0 +1,214 @@
+//! `snowflake_query!`, statements checked against the schema file at compile time.
+
+use std::path::PathBuf;
+
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use snowflake_deserializer::{coercion::ColumnType, schema::{ColumnSchema, Schema, TableSchema}};
+use syn::{parse::{Parse, ParseStream}, punctuated::Punctuated, DeriveInput, Expr, Ident, LitStr, Token, Type};
+
+pub(crate) struct QueryInput {
+    statement: LitStr,
+    bindings: Punctuated<Expr, Token![,]>,
+}
+
+impl Parse for QueryInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let statement = input.parse()?;
+        let bindings = if input.is_empty() {
+            Punctuated::new()
+        } else {
+            input.parse::<Token![,]>()?;
+            Punctuated::parse_terminated(input)?
+        };
+        Ok(QueryInput { statement, bindings })
+    }
+}
+
+pub(crate) fn impl_snowflake_query(input: &QueryInput) -> syn::Result<TokenStream> {
+    let statement = &input.statement;
+    let error = |message: String| syn::Error::new_spanned(statement, message);
+    let (path, schema) = load_schema().map_err(error)?;
+    let select = Select::parse(&statement.value()).map_err(error)?;
+    let table = schema.table(&select.table).ok_or_else(|| error(format!(
+        "table {} is not in {}, regenerate it with `fetch_schema`",
+        select.table, path.display(),
+    )))?;
+    if select.placeholders != input.bindings.len() {
+        return Err(error(format!(
+            "statement has {} placeholder(s) but {} binding(s) were given",
+            select.placeholders, input.bindings.len(),
+        )));
+    }
+
+    let columns = select.columns(table).map_err(error)?;
+    let mut fields = Vec::with_capacity(columns.len());
+    let mut attributes = Vec::with_capacity(columns.len());
+    let mut names: Vec<String> = Vec::with_capacity(columns.len());
+    for column in columns {
+        let field = field_name(&column.name);
+        if names.contains(&field.to_string()) {
+            return Err(error(format!("column {} is selected more than once", column.name)));
+        }
+        names.push(field.to_string());
+        let ty: Type = syn::parse_str(ColumnType::from_declared(&column.data_type).rust_type())?;
+        let ty = if column.nullable { quote!(Option<#ty>) } else { quote!(#ty) };
+        let name = &column.name;
+        fields.push(quote! { pub #field: #ty });
+        attributes.push(quote! { #[snowflake(rename = #name)] });
+    }
+    let row = quote! {
+        #[derive(Clone, Debug)]
+        pub struct SnowflakeQueryRow {
+            #(#fields),*
+        }
+    };
+    // Only the input of the derive carries `#[snowflake(...)]`, the struct itself is emitted without it.
+    let derive_input: DeriveInput = syn::parse2(quote! {
+        pub struct SnowflakeQueryRow {
+            #(#attributes #fields),*
+        }
+    })?;
+    let row_impl = crate::impl_snowflake_deserialize(&derive_input)?;
+    let path = path.to_string_lossy().into_owned();
+    let bindings = input.bindings.iter();
+    Ok(quote! {
+        {
+            // Recompile when the schema changes.
+            const _: &[u8] = include_bytes!(#path);
+            #row
+            #row_impl
+            query::Query::<SnowflakeQueryRow>::new(#statement, vec![#(Into::into(#bindings)),*])
+        }
+    })
+}
+
+/// Schema file, `SNOWFLAKE_SCHEMA` or the schema file next to `Cargo.toml`.
+pub(crate) fn load_schema() -> Result<(PathBuf, Schema), String> {
+    let path = Schema::path();
+    let json = std::fs::read_to_string(&path)
+        .map_err(|e| format!("could not read schema {}—{e}, generate it with `fetch_schema`", path.display()))?;
+    let schema = Schema::from_json(&json)
+        .map_err(|e| format!("could not parse schema {}—{e}", path.display()))?;
+    Ok((path, schema))
+}
+
+/// The parts of `SELECT columns FROM table ...` that are checked.
+#[derive(Debug, PartialEq)]
+struct Select {
+    /// Column names, `None` for `*`.
+    columns: Option<Vec<String>>,
+    table: String,
+    placeholders: usize,
+}
+
+impl Select {
+    fn parse(statement: &str) -> Result<Self, String> {
+        let statement = statement.trim().trim_end_matches(';');
+        let upper = statement.to_ascii_uppercase();
+        if !upper.starts_with("SELECT") || !upper["SELECT".len()..].starts_with(char::is_whitespace) {
+            return Err("only `SELECT columns FROM table` statements can be checked".into());
+        }
+        let from = upper.match_indices("FROM")
+            .map(|(index, _)| index)
+            .find(|index| {
+                upper[..*index].ends_with(char::is_whitespace)
+                    && upper[index + 4..].starts_with(char::is_whitespace)
+            })
+            .ok_or("statement has no FROM clause")?;
+        let list = statement["SELECT".len()..from].trim();
+        let columns = if list == "*" {
+            None
+        } else {
+            let columns = list.split(',')
+                .map(|column| {
+                    let column = column.trim();
+                    let name = column.rsplit('.').next().unwrap_or_default();
+                    if is_identifier(name) {
+                        Ok(name.to_owned())
+                    } else {
+                        Err(format!("only column names can be checked, found `{column}`"))
+                    }
+                })
+                .collect::<Result<_, String>>()?;
+            Some(columns)
+        };
+        let table = statement[from + 4..].split_whitespace().next().unwrap_or_default();
+        if !table.split('.').all(is_identifier) {
+            return Err(format!("only a table name can be checked after FROM, found `{table}`"));
+        }
+        Ok(Select {
+            columns,
+            table: table.to_owned(),
+            placeholders: count_placeholders(statement),
+        })
+    }
+    fn columns<'a>(&self, table: &'a TableSchema) -> Result<Vec<&'a ColumnSchema>, String> {
+        match &self.columns {
+            None => Ok(table.columns.iter().collect()),
+            Some(columns) => columns.iter()
+                .map(|name| table.column(name).ok_or_else(|| format!(
+                    "column {name} does not exist in {}, expected one of {}",
+                    table.name,
+                    table.columns.iter().map(|column| column.name.as_str()).collect::<Vec<_>>().join(", "),
+                )))
+                .collect(),
+        }
+    }
+}
+
+fn is_identifier(identifier: &str) -> bool {
+    if identifier.len() > 2 && identifier.starts_with('"') && identifier.ends_with('"') {
+        return true;
+    }
+    let mut chars = identifier.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// `?` outside of string literals.
+fn count_placeholders(statement: &str) -> usize {
+    let mut quoted = false;
+    let mut count = 0;
+    for c in statement.chars() {
+        match c {
+            '\'' => quoted = !quoted,
+            '?' if !quoted => count += 1,
+            _ => {},
+        }
+    }
+    count
+}
+
+/// Lowercase column name, characters not allowed in an identifier become `_`.
+pub(crate) fn field_name(column: &str) -> Ident {
+    let mut name: String = column.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        name.insert(0, '_');
+    }
+    syn::parse_str::<Ident>(&name).unwrap_or_else(|_| format_ident!("{}_", name, span = Span::call_site()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_select() {
+        let select = Select::parse("SELECT id, T.name FROM DB.PUBLIC.USERS WHERE id = ? AND name <> '?';").unwrap();
+        assert_eq!(select, Select {
+            columns: Some(vec!["id".into(), "name".into()]),
+            table: "DB.PUBLIC.USERS".into(),
+            placeholders: 1,
+        });
+        assert_eq!(Select::parse("select * from USERS").unwrap().columns, None);
+        assert!(Select::parse("SELECT COUNT(*) FROM USERS").is_err());
+        assert!(Select::parse("SELECT ID FROM (SELECT 1)").is_err());
+        assert!(Select::parse("DELETE FROM USERS").is_err());
+        assert_eq!(field_name("FULL NAME"), "full_name");
+        assert_eq!(field_name("TYPE"), "type_");
+        assert_eq!(field_name("1ST"), "_1st");
+    }
+}
@@ -0,0 +1,278 @@
+//! `snowflake_repository!`, typed CRUD statements for a table of the schema file.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use snowflake_deserializer::coercion::ColumnType;
+use syn::{parse::{Parse, ParseStream}, punctuated::Punctuated, DeriveInput, Ident, LitStr, Token, Type};
+
+use crate::query::{field_name, load_schema};
+
+/// Types the generated statements can not bind yet.
+const UNBINDABLE: [&str; 2] = ["chrono::DateTime<chrono::FixedOffset>", "variant::Variant"];
+
+pub(crate) struct RepositoryInput {
+    row: Ident,
+    table: LitStr,
+    primary_key: Punctuated<LitStr, Token![,]>,
+}
+
+impl Parse for RepositoryInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let row = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let table = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let key: Ident = input.parse()?;
+        if key != "primary_key" {
+            return Err(syn::Error::new_spanned(key, "expected `primary_key = [\"COLUMN\", ...]`"));
+        }
+        input.parse::<Token![=]>()?;
+        let content;
+        syn::bracketed!(content in input);
+        let primary_key = Punctuated::parse_terminated(&content)?;
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+        Ok(RepositoryInput { row, table, primary_key })
+    }
+}
+
+struct Column {
+    field: Ident,
+    /// Quoted column name, as written in statements.
+    sql: String,
+    ty: Type,
+    nullable: bool,
+    primary_key: bool,
+}
+
+pub(crate) fn impl_snowflake_repository(input: &RepositoryInput) -> syn::Result<TokenStream> {
+    let table_literal = &input.table;
+    let error = |message: String| syn::Error::new_spanned(table_literal, message);
+    let (path, schema) = load_schema().map_err(error)?;
+    let table = schema.table(&table_literal.value()).ok_or_else(|| error(format!(
+        "table {} is not in {}, regenerate it with `fetch_schema`",
+        table_literal.value(), path.display(),
+    )))?;
+    if input.primary_key.is_empty() {
+        return Err(error("primary key needs at least one column".into()));
+    }
+    for key in &input.primary_key {
+        let column = table.column(&key.value()).ok_or_else(|| syn::Error::new_spanned(key, format!(
+            "primary key column {} does not exist in {}",
+            key.value(), table.name,
+        )))?;
+        if column.nullable {
+            return Err(syn::Error::new_spanned(key, format!("primary key column {} is nullable", column.name)));
+        }
+    }
+
+    let mut columns = Vec::with_capacity(table.columns.len());
+    for column in &table.columns {
+        let rust_type = ColumnType::from_declared(&column.data_type).rust_type();
+        if UNBINDABLE.contains(&rust_type) {
+            return Err(error(format!(
+                "column {} of type {} can not be bound, use `snowflake_query!` for this table",
+                column.name, column.data_type,
+            )));
+        }
+        columns.push(Column {
+            field: field_name(&column.name),
+            sql: format!("\"{}\"", column.name.replace('"', "\"\"")),
+            ty: syn::parse_str(rust_type)?,
+            nullable: column.nullable,
+            primary_key: input.primary_key.iter().any(|key| column.name.eq_ignore_ascii_case(&key.value())),
+        });
+    }
+
+    let row = &input.row;
+    let repository = format_ident!("{}Repository", row);
+    let filter = format_ident!("{}Filter", row);
+    let table_name = &table.name;
+    let select = format!(
+        "SELECT {} FROM {table_name}",
+        columns.iter().map(|column| column.sql.as_str()).collect::<Vec<_>>().join(", "),
+    );
+    let keys: Vec<&Column> = columns.iter().filter(|column| column.primary_key).collect();
+    let key_condition = keys.iter()
+        .map(|column| format!("{} = ?", column.sql))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+
+    let row_fields: Vec<TokenStream> = columns.iter()
+        .map(|Column { field, ty, nullable, .. }| match nullable {
+            true => quote! { pub #field: Option<#ty> },
+            false => quote! { pub #field: #ty },
+        })
+        .collect();
+    let renames = table.columns.iter().map(|column| {
+        let name = &column.name;
+        quote! { #[snowflake(rename = #name)] }
+    });
+    // Only the input of the derive carries `#[snowflake(...)]`, the struct itself is emitted without it.
+    let derive_input: DeriveInput = syn::parse2(quote! {
+        pub struct #row {
+            #(#renames #row_fields),*
+        }
+    })?;
+    let row_impl = crate::impl_snowflake_deserialize(&derive_input)?;
+
+    let key_fields: Vec<&Ident> = keys.iter().map(|column| &column.field).collect();
+    let key_types: Vec<&Type> = keys.iter().map(|column| &column.ty).collect();
+    let find_by_pk = format!("{select} WHERE {key_condition}");
+    let delete = format!("DELETE FROM {table_name} WHERE {key_condition}");
+
+    let insert_values = columns.iter().map(|Column { field, nullable, .. }| match nullable {
+        true => quote! {
+            match &row.#field {
+                Some(value) => {
+                    values.push("?");
+                    arguments.push(Into::into(Clone::clone(value)));
+                },
+                None => values.push("NULL"),
+            }
+        },
+        false => quote! {
+            values.push("?");
+            arguments.push(Into::into(Clone::clone(&row.#field)));
+        },
+    });
+    let column_list = columns.iter().map(|column| column.sql.as_str()).collect::<Vec<_>>().join(", ");
+    let insert = format!("INSERT INTO {table_name} ({column_list}) VALUES ");
+    let updated: Vec<&Column> = columns.iter().filter(|column| !column.primary_key).collect();
+    let on = keys.iter()
+        .map(|column| format!("T.{0} = S.{0}", column.sql))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let matched = match updated.is_empty() {
+        true => String::new(),
+        false => format!(
+            " WHEN MATCHED THEN UPDATE SET {}",
+            updated.iter().map(|column| format!("T.{0} = S.{0}", column.sql)).collect::<Vec<_>>().join(", "),
+        ),
+    };
+    let sources = columns.iter().map(|column| format!("S.{}", column.sql)).collect::<Vec<_>>().join(", ");
+    let merge_prefix = format!("MERGE INTO {table_name} AS T USING (SELECT * FROM (VALUES ");
+    let merge_suffix = format!(
+        ") AS V ({column_list})) AS S ON {on}{matched} WHEN NOT MATCHED THEN INSERT ({column_list}) VALUES ({sources})",
+    );
+
+    let update = if updated.is_empty() {
+        quote! {}
+    } else {
+        let assignments = updated.iter().map(|Column { field, sql, nullable, .. }| {
+            let bound = format!("{sql} = ?");
+            match nullable {
+                true => {
+                    let null = format!("{sql} = NULL");
+                    quote! {
+                        match &row.#field {
+                            Some(value) => {
+                                assignments.push(#bound);
+                                arguments.push(Into::into(Clone::clone(value)));
+                            },
+                            None => assignments.push(#null),
+                        }
+                    }
+                },
+                false => quote! {
+                    assignments.push(#bound);
+                    arguments.push(Into::into(Clone::clone(&row.#field)));
+                },
+            }
+        });
+        let update = format!("UPDATE {table_name} SET ");
+        let key_condition = format!(" WHERE {key_condition}");
+        quote! {
+            /// Set every column of the row with the same primary key.
+            pub fn update(row: &#row) -> query::Manipulation {
+                let mut assignments: Vec<&'static str> = Vec::new();
+                let mut arguments: Vec<bindings::BindingValue> = Vec::new();
+                #(#assignments)*
+                #(arguments.push(Into::into(Clone::clone(&row.#key_fields)));)*
+                query::Manipulation::new(format!("{}{}{}", #update, assignments.join(", "), #key_condition), arguments)
+            }
+        }
+    };
+
+    let filter_fields = columns.iter().map(|Column { field, ty, .. }| quote! { pub #field: Option<#ty> });
+    let filter_conditions = columns.iter().map(|Column { field, sql, .. }| {
+        let condition = format!("{sql} = ?");
+        quote! {
+            if let Some(value) = &filter.#field {
+                conditions.push(#condition);
+                arguments.push(Into::into(Clone::clone(value)));
+            }
+        }
+    });
+
+    let path = path.to_string_lossy().into_owned();
+    let repository_doc = format!("Statements for {table_name}, run them with `SnowflakeExecutor::query` and `SnowflakeExecutor::manipulate`.");
+    let filter_doc = format!("Columns of {table_name} to match in [`{repository}::list`], `None` matches any value.");
+    Ok(quote! {
+        // Recompile when the schema changes.
+        const _: &[u8] = include_bytes!(#path);
+
+        #[derive(Clone, Debug)]
+        pub struct #row {
+            #(#row_fields),*
+        }
+        #row_impl
+
+        #[doc = #filter_doc]
+        #[derive(Clone, Debug, Default)]
+        pub struct #filter {
+            #(#filter_fields),*
+        }
+
+        #[doc = #repository_doc]
+        #[derive(Clone, Copy, Debug)]
+        pub struct #repository;
+
+        impl #repository {
+            pub const TABLE: &'static str = #table_name;
+
+            pub fn find_by_pk(#(#key_fields: #key_types),*) -> query::Query<#row> {
+                query::Query::new(#find_by_pk, vec![#(Into::into(#key_fields)),*])
+            }
+            pub fn insert(row: &#row) -> query::Manipulation {
+                let mut arguments: Vec<bindings::BindingValue> = Vec::new();
+                let values = Self::values(row, &mut arguments);
+                query::Manipulation::new(format!("{}{}", #insert, values), arguments)
+            }
+            /// Insert `rows`, or update the rows with the same primary key, in a single `MERGE`,
+            /// `None` when `rows` is empty.
+            pub fn upsert(rows: &[#row]) -> Option<query::Manipulation> {
+                if rows.is_empty() {
+                    return None;
+                }
+                let mut arguments: Vec<bindings::BindingValue> = Vec::new();
+                let values: Vec<String> = rows.iter()
+                    .map(|row| Self::values(row, &mut arguments))
+                    .collect();
+                Some(query::Manipulation::new(format!("{}{}{}", #merge_prefix, values.join(", "), #merge_suffix), arguments))
+            }
+            #update
+            pub fn delete(#(#key_fields: #key_types),*) -> query::Manipulation {
+                query::Manipulation::new(#delete, vec![#(Into::into(#key_fields)),*])
+            }
+            /// Rows matching every column set in `filter`.
+            pub fn list(filter: &#filter) -> query::Query<#row> {
+                let mut conditions: Vec<&'static str> = Vec::new();
+                let mut arguments: Vec<bindings::BindingValue> = Vec::new();
+                #(#filter_conditions)*
+                if conditions.is_empty() {
+                    query::Query::new(#select, arguments)
+                } else {
+                    query::Query::new(format!("{} WHERE {}", #select, conditions.join(" AND ")), arguments)
+                }
+            }
+            /// `(?, NULL, ...)` with a placeholder per bound column, `NULL` for empty nullable columns.
+            fn values(row: &#row, arguments: &mut Vec<bindings::BindingValue>) -> String {
+                let mut values: Vec<&'static str> = Vec::new();
+                #(#insert_values)*
+                format!("({})", values.join(", "))
+            }
+        }
+    })
+}
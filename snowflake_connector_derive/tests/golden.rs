@@ -0,0 +1,72 @@
+use snowflake_connector_derive::expand_for_test;
+
+/// Compare against `tests/golden/<name>.rs`, run with `UPDATE_GOLDEN=1` to accept changes.
+fn assert_golden(name: &str, expanded: &str) {
+    let path = format!("{}/tests/golden/{name}.rs", env!("CARGO_MANIFEST_DIR"));
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        std::fs::write(&path, expanded).unwrap();
+        return;
+    }
+    let golden = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("failed to read {path}, run with UPDATE_GOLDEN=1 to create it—{e}"));
+    assert_eq!(golden, expanded, "expansion differs from {path}, run with UPDATE_GOLDEN=1 to accept");
+}
+
+#[test]
+fn basic() {
+    assert_golden("basic", expand_for_test! {
+        struct Test {
+            id: u32,
+            name: String,
+        }
+    });
+}
+
+#[test]
+fn table() {
+    assert_golden("table", expand_for_test! {
+        #[snowflake(table = "DB.PUBLIC.TEST_TABLE")]
+        struct Test {
+            id: u32,
+            #[snowflake(rename = "FULL NAME")]
+            name: Option<String>,
+            #[snowflake(data_type = "NUMBER(38,2)")]
+            amount: f64,
+        }
+    });
+}
+
+#[test]
+fn redact() {
+    assert_golden("redact", expand_for_test! {
+        struct User {
+            id: u32,
+            #[snowflake(redact)]
+            email: Option<String>,
+        }
+    });
+}
+
+#[test]
+fn flatten() {
+    assert_golden("flatten", expand_for_test! {
+        struct Customer {
+            id: u32,
+            #[snowflake(flatten)]
+            address: Address,
+            active: bool,
+        }
+    });
+}
+
+#[test]
+fn by_name() {
+    assert_golden("by_name", expand_for_test! {
+        #[snowflake(by_name)]
+        struct Test {
+            id: u32,
+            #[snowflake(rename = "FULL NAME")]
+            name: Option<String>,
+        }
+    });
+}
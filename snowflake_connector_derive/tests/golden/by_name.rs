@@ -0,0 +1,59 @@
+impl SnowflakeDeserializeRow for Test {
+    const WIDTH: usize = 2usize;
+    type Columns = (usize, Option<usize>);
+    fn check_row_types(row_types: &[RowType]) -> Result<Self::Columns, anyhow::Error> {
+        Ok((
+            coercion::expect_column::<Self>(row_types, "ID")?,
+            coercion::column_index(row_types, "FULL NAME"),
+        ))
+    }
+    fn deserialize_row(
+        row_types: &[RowType],
+        columns: &Self::Columns,
+        row: &[String],
+    ) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            id: {
+                let index = columns.0;
+                <u32>::deserialize_from_str(&row[index])
+                    .map_err(|e| coercion::ColumnMismatch::new::<
+                        u32,
+                        _,
+                    >(&row_types[index], e))?
+            },
+            name: match columns.1 {
+                Some(index) => {
+                    <Option<String>>::deserialize_from_str(&row[index])
+                        .map_err(|e| coercion::ColumnMismatch::new::<
+                            Option<String>,
+                            _,
+                        >(&row_types[index], e))?
+                }
+                None => None,
+            },
+        })
+    }
+}
+impl SnowflakeDeserialize for Test {
+    fn snowflake_deserialize(
+        response: SnowflakeSQLResponse,
+    ) -> Result<SnowflakeSQLResult<Self>, anyhow::Error> {
+        let row_types = &response.result_set_meta_data.row_type;
+        let columns = <Self as SnowflakeDeserializeRow>::check_row_types(row_types)?;
+        let mut results = Vec::with_capacity(response.data.len());
+        for (index, row) in response.data.into_iter().enumerate() {
+            let result = <Self as SnowflakeDeserializeRow>::deserialize_row(
+                    row_types,
+                    &columns,
+                    &row,
+                )
+                .map_err(|source| RowError {
+                    index,
+                    raw: row,
+                    source,
+                })?;
+            results.push(result);
+        }
+        Ok(SnowflakeSQLResult::new(results))
+    }
+}
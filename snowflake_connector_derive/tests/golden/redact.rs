@@ -0,0 +1,51 @@
+impl SnowflakeDeserializeRow for User {
+    const WIDTH: usize = 2usize;
+    type Columns = ();
+    fn check_row_types(row_types: &[RowType]) -> Result<Self::Columns, anyhow::Error> {
+        coercion::expect_columns::<Self>(row_types, Self::WIDTH)?;
+        Ok(())
+    }
+    fn deserialize_row(
+        row_types: &[RowType],
+        _columns: &Self::Columns,
+        row: &[String],
+    ) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            id: <u32>::deserialize_from_str(&row[0usize])
+                .map_err(|e| coercion::ColumnMismatch::new::<
+                    u32,
+                    _,
+                >(&row_types[0usize], e))?,
+            email: <Option<
+                String,
+            >>::deserialize_from_str(&redact::apply(&row_types[1usize], &row[1usize]))
+                .map_err(|e| coercion::ColumnMismatch::new::<
+                    Option<String>,
+                    _,
+                >(&row_types[1usize], e))?,
+        })
+    }
+}
+impl SnowflakeDeserialize for User {
+    fn snowflake_deserialize(
+        response: SnowflakeSQLResponse,
+    ) -> Result<SnowflakeSQLResult<Self>, anyhow::Error> {
+        let row_types = &response.result_set_meta_data.row_type;
+        let columns = <Self as SnowflakeDeserializeRow>::check_row_types(row_types)?;
+        let mut results = Vec::with_capacity(response.data.len());
+        for (index, row) in response.data.into_iter().enumerate() {
+            let result = <Self as SnowflakeDeserializeRow>::deserialize_row(
+                    row_types,
+                    &columns,
+                    &row,
+                )
+                .map_err(|source| RowError {
+                    index,
+                    raw: row,
+                    source,
+                })?;
+            results.push(result);
+        }
+        Ok(SnowflakeSQLResult::new(results))
+    }
+}
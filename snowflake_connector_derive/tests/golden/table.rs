@@ -0,0 +1,75 @@
+impl SnowflakeDeserializeRow for Test {
+    const WIDTH: usize = 3usize;
+    type Columns = ();
+    fn check_row_types(row_types: &[RowType]) -> Result<Self::Columns, anyhow::Error> {
+        coercion::expect_columns::<Self>(row_types, Self::WIDTH)?;
+        coercion::expect_column_name(&row_types[1usize], "FULL NAME")?;
+        Ok(())
+    }
+    fn deserialize_row(
+        row_types: &[RowType],
+        _columns: &Self::Columns,
+        row: &[String],
+    ) -> Result<Self, anyhow::Error> {
+        Ok(Self {
+            id: <u32>::deserialize_from_str(&row[0usize])
+                .map_err(|e| coercion::ColumnMismatch::new::<
+                    u32,
+                    _,
+                >(&row_types[0usize], e))?,
+            name: <Option<String>>::deserialize_from_str(&row[1usize])
+                .map_err(|e| coercion::ColumnMismatch::new::<
+                    Option<String>,
+                    _,
+                >(&row_types[1usize], e))?,
+            amount: <f64>::deserialize_from_str(&row[2usize])
+                .map_err(|e| coercion::ColumnMismatch::new::<
+                    f64,
+                    _,
+                >(&row_types[2usize], e))?,
+        })
+    }
+}
+impl SnowflakeDeserialize for Test {
+    fn snowflake_deserialize(
+        response: SnowflakeSQLResponse,
+    ) -> Result<SnowflakeSQLResult<Self>, anyhow::Error> {
+        let row_types = &response.result_set_meta_data.row_type;
+        let columns = <Self as SnowflakeDeserializeRow>::check_row_types(row_types)?;
+        let mut results = Vec::with_capacity(response.data.len());
+        for (index, row) in response.data.into_iter().enumerate() {
+            let result = <Self as SnowflakeDeserializeRow>::deserialize_row(
+                    row_types,
+                    &columns,
+                    &row,
+                )
+                .map_err(|source| RowError {
+                    index,
+                    raw: row,
+                    source,
+                })?;
+            results.push(result);
+        }
+        Ok(SnowflakeSQLResult::new(results))
+    }
+}
+impl table::SnowflakeTable for Test {
+    const TABLE: &'static str = "DB.PUBLIC.TEST_TABLE";
+    const COLUMNS: &'static [table::ColumnMeta] = &[
+        table::ColumnMeta {
+            name: "ID",
+            data_type: "NUMBER(38,0)",
+            nullable: false,
+        },
+        table::ColumnMeta {
+            name: "FULL NAME",
+            data_type: "VARCHAR",
+            nullable: true,
+        },
+        table::ColumnMeta {
+            name: "AMOUNT",
+            data_type: "NUMBER(38,2)",
+            nullable: false,
+        },
+    ];
+}
@@ -0,0 +1,8 @@
+#[test]
+fn ui() {
+    // Schema `snowflake_query!` checks statements against.
+    std::env::set_var("SNOWFLAKE_SCHEMA", concat!(env!("CARGO_MANIFEST_DIR"), "/tests/ui/snowflake_schema.json"));
+    let t = trybuild::TestCases::new();
+    t.pass("tests/ui/pass/*.rs");
+    t.compile_fail("tests/ui/fail/*.rs");
+}
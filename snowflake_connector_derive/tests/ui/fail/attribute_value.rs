@@ -0,0 +1,9 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+
+#[derive(SnowflakeDeserialize)]
+#[snowflake(table = 1)]
+pub struct Test {
+    pub id: u32,
+}
+
+fn main() {}
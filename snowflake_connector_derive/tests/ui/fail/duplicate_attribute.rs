@@ -0,0 +1,9 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+
+#[derive(SnowflakeDeserialize)]
+#[snowflake(table = "DB.PUBLIC.A", table = "DB.PUBLIC.B")]
+pub struct Test {
+    pub id: u32,
+}
+
+fn main() {}
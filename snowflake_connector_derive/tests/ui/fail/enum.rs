@@ -0,0 +1,9 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+
+#[derive(SnowflakeDeserialize)]
+pub enum Test {
+    A,
+    B,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+
+#[derive(SnowflakeDeserialize)]
+pub struct Address {
+    pub city: String,
+}
+
+#[derive(SnowflakeDeserialize)]
+#[snowflake(table = "DB.PUBLIC.CUSTOMER")]
+pub struct Customer {
+    pub id: u32,
+    #[snowflake(flatten)]
+    pub address: Address,
+}
+
+fn main() {}
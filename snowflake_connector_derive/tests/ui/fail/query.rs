@@ -0,0 +1,7 @@
+use snowflake_connector_derive::snowflake_query;
+
+fn main() {
+    let _ = snowflake_query!("SELECT ID, EMAIL FROM DB.PUBLIC.USERS");
+    let _ = snowflake_query!("SELECT ID FROM DB.PUBLIC.ORDERS");
+    let _ = snowflake_query!("SELECT ID FROM DB.PUBLIC.USERS WHERE ID = ?");
+}
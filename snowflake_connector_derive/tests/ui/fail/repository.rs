@@ -0,0 +1,7 @@
+use snowflake_connector_derive::snowflake_repository;
+
+snowflake_repository!(User, "DB.PUBLIC.USERS", primary_key = ["USER_ID"]);
+
+snowflake_repository!(Named, "DB.PUBLIC.USERS", primary_key = ["NAME"]);
+
+fn main() {}
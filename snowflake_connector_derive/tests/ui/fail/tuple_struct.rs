@@ -0,0 +1,6 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+
+#[derive(SnowflakeDeserialize)]
+pub struct Test(u32, String);
+
+fn main() {}
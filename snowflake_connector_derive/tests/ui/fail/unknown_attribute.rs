@@ -0,0 +1,9 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+
+#[derive(SnowflakeDeserialize)]
+pub struct Test {
+    #[snowflake(datatype = "NUMBER(38,0)")]
+    pub id: u32,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+use snowflake_deserializer::*;
+
+#[derive(SnowflakeDeserialize)]
+pub struct Test {
+    pub id: u32,
+    pub name: String,
+    pub value: variant::Variant,
+}
+
+fn main() {}
@@ -0,0 +1,47 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+use snowflake_deserializer::*;
+
+#[derive(SnowflakeDeserialize, Debug)]
+#[snowflake(by_name)]
+pub struct Test {
+    pub id: u32,
+    #[snowflake(rename = "FULL_NAME")]
+    pub name: String,
+    pub email: Option<String>,
+}
+
+#[derive(SnowflakeDeserialize, Debug)]
+#[snowflake(by_name)]
+pub struct Required {
+    pub id: u32,
+    pub email: String,
+}
+
+fn main() {
+    let row_type = |name: &str, data_type: &str| serde_json::json!({
+        "name": name, "database": "DB", "schema": "PUBLIC", "table": "TEST",
+        "precision": null, "byteLength": null, "type": data_type, "scale": null, "nullable": false,
+    });
+    let response: SnowflakeSQLResponse = serde_json::from_value(serde_json::json!({
+        "resultSetMetaData": {
+            "numRows": 1,
+            "format": "jsonv2",
+            "rowType": [row_type("FULL_NAME", "text"), row_type("ADDED", "text"), row_type("ID", "fixed")],
+        },
+        "data": [["a", "x", "1"]],
+        "code": "090001",
+        "statementHandle": "",
+        "statementStatusUrl": "",
+        "requestId": "",
+        "sqlState": "00000",
+        "message": "",
+    })).unwrap();
+
+    let result = response.clone().deserialize::<Test>().unwrap();
+    assert_eq!(result.data[0].id, 1);
+    assert_eq!(result.data[0].name, "a");
+    assert_eq!(result.data[0].email, None);
+
+    let error = response.deserialize::<Required>().unwrap_err();
+    assert!(error.to_string().contains("EMAIL"));
+}
@@ -0,0 +1,48 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+use snowflake_deserializer::*;
+
+#[derive(SnowflakeDeserialize)]
+pub struct Address {
+    pub street: String,
+    #[snowflake(rename = "CITY")]
+    pub city: String,
+}
+
+#[derive(SnowflakeDeserialize)]
+pub struct Customer {
+    pub id: u32,
+    #[snowflake(flatten)]
+    pub address: Address,
+    pub active: bool,
+}
+
+fn main() {
+    let row_type = |name: &str, data_type: &str| serde_json::json!({
+        "name": name, "database": "DB", "schema": "PUBLIC", "table": "CUSTOMER",
+        "precision": null, "byteLength": null, "type": data_type, "scale": null, "nullable": false,
+    });
+    let response: SnowflakeSQLResponse = serde_json::from_value(serde_json::json!({
+        "resultSetMetaData": {
+            "numRows": 1,
+            "format": "jsonv2",
+            "rowType": [
+                row_type("ID", "fixed"),
+                row_type("STREET", "text"),
+                row_type("CITY", "text"),
+                row_type("ACTIVE", "boolean"),
+            ],
+        },
+        "data": [["1", "Main St", "Springfield", "true"]],
+        "code": "090001",
+        "statementHandle": "",
+        "statementStatusUrl": "",
+        "requestId": "",
+        "sqlState": "00000",
+        "message": "",
+    })).unwrap();
+    assert_eq!(<Customer as SnowflakeDeserializeRow>::WIDTH, 4);
+    let customer = &response.deserialize::<Customer>().unwrap().data[0];
+    assert_eq!(customer.id, 1);
+    assert_eq!(customer.address.city, "Springfield");
+    assert!(customer.active);
+}
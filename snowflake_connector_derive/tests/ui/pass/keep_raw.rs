@@ -0,0 +1,42 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+use snowflake_deserializer::*;
+
+#[derive(SnowflakeDeserialize, Debug)]
+pub struct Test {
+    pub id: u32,
+    pub name: String,
+    #[snowflake(keep_raw)]
+    pub raw: Vec<String>,
+}
+
+fn main() {
+    let row_type = |name: &str, data_type: &str| serde_json::json!({
+        "name": name, "database": "DB", "schema": "PUBLIC", "table": "TEST",
+        "precision": null, "byteLength": null, "type": data_type, "scale": null, "nullable": false,
+    });
+    let response: SnowflakeSQLResponse = serde_json::from_value(serde_json::json!({
+        "resultSetMetaData": {
+            "numRows": 2,
+            "format": "jsonv2",
+            "rowType": [row_type("ID", "fixed"), row_type("NAME", "text")],
+        },
+        "data": [["1", "a"], ["x", "b"]],
+        "code": "090001",
+        "statementHandle": "",
+        "statementStatusUrl": "",
+        "requestId": "",
+        "sqlState": "00000",
+        "message": "",
+    })).unwrap();
+
+    let error = response.clone().deserialize::<Test>().unwrap_err();
+    let error = error.downcast_ref::<RowError>().unwrap();
+    assert_eq!(error.index, 1);
+    assert_eq!(error.raw, ["x", "b"]);
+
+    let result = response.deserialize_partial::<Test>().unwrap();
+    assert_eq!(result.data.len(), 1);
+    assert_eq!(result.data[0].raw, ["1", "a"]);
+    assert_eq!(result.rejected.len(), 1);
+    assert_eq!(result.rejected[0].index, 1);
+}
@@ -0,0 +1,42 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+use snowflake_deserializer::*;
+
+#[derive(SnowflakeDeserialize, Debug)]
+#[snowflake(post_deserialize = "Test::normalize")]
+pub struct Test {
+    pub id: u32,
+    pub email: String,
+    pub grams: f64,
+}
+
+impl Test {
+    fn normalize(&mut self) {
+        self.email = self.email.trim().to_lowercase();
+        self.grams *= 1000.0;
+    }
+}
+
+fn main() {
+    let row_type = |name: &str, data_type: &str| serde_json::json!({
+        "name": name, "database": "DB", "schema": "PUBLIC", "table": "TEST",
+        "precision": null, "byteLength": null, "type": data_type, "scale": null, "nullable": false,
+    });
+    let response: SnowflakeSQLResponse = serde_json::from_value(serde_json::json!({
+        "resultSetMetaData": {
+            "numRows": 1,
+            "format": "jsonv2",
+            "rowType": [row_type("ID", "fixed"), row_type("EMAIL", "text"), row_type("GRAMS", "real")],
+        },
+        "data": [["1", " Jo@Example.com ", "1.5"]],
+        "code": "090001",
+        "statementHandle": "",
+        "statementStatusUrl": "",
+        "requestId": "",
+        "sqlState": "00000",
+        "message": "",
+    })).unwrap();
+
+    let result = response.deserialize::<Test>().unwrap();
+    assert_eq!(result.data[0].email, "jo@example.com");
+    assert_eq!(result.data[0].grams, 1500.0);
+}
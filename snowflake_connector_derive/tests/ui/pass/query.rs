@@ -0,0 +1,22 @@
+use snowflake_connector_derive::snowflake_query;
+use snowflake_deserializer::*;
+
+fn rows<T>(_: &query::Query<T>) -> Vec<T> {
+    Vec::new()
+}
+
+fn main() {
+    let id = 1;
+    let query = snowflake_query!("SELECT id, name FROM DB.PUBLIC.USERS WHERE id = ?", id);
+    assert_eq!(query.statement(), "SELECT id, name FROM DB.PUBLIC.USERS WHERE id = ?");
+    assert_eq!(query.bindings().len(), 1);
+    for row in rows(&query) {
+        let _: i64 = row.id;
+        let _: Option<String> = row.name;
+    }
+
+    let all = snowflake_query!("SELECT * FROM db.public.users");
+    for row in rows(&all) {
+        let _: f64 = row.balance;
+    }
+}
@@ -0,0 +1,35 @@
+use snowflake_connector_derive::snowflake_repository;
+use snowflake_deserializer::*;
+
+snowflake_repository!(User, "DB.PUBLIC.USERS", primary_key = ["ID"]);
+
+fn main() {
+    let find = UserRepository::find_by_pk(1);
+    assert_eq!(find.statement(), r#"SELECT "ID", "NAME", "BALANCE" FROM DB.PUBLIC.USERS WHERE "ID" = ?"#);
+    assert_eq!(find.bindings().len(), 1);
+
+    let user = User { id: 1, name: None, balance: 2.5 };
+    let insert = UserRepository::insert(&user);
+    assert_eq!(insert.statement(), r#"INSERT INTO DB.PUBLIC.USERS ("ID", "NAME", "BALANCE") VALUES (?, NULL, ?)"#);
+    assert_eq!(insert.bindings().len(), 2);
+    let update = UserRepository::update(&User { name: Some("a".into()), ..user.clone() });
+    assert_eq!(update.statement(), r#"UPDATE DB.PUBLIC.USERS SET "NAME" = ?, "BALANCE" = ? WHERE "ID" = ?"#);
+    assert_eq!(update.bindings().len(), 3);
+    let upsert = UserRepository::upsert(&[user.clone(), User { id: 2, name: Some("b".into()), balance: 0.0 }]).unwrap();
+    assert_eq!(
+        upsert.statement(),
+        concat!(
+            r#"MERGE INTO DB.PUBLIC.USERS AS T USING (SELECT * FROM (VALUES (?, NULL, ?), (?, ?, ?)) AS V ("ID", "NAME", "BALANCE")) AS S "#,
+            r#"ON T."ID" = S."ID" WHEN MATCHED THEN UPDATE SET T."NAME" = S."NAME", T."BALANCE" = S."BALANCE" "#,
+            r#"WHEN NOT MATCHED THEN INSERT ("ID", "NAME", "BALANCE") VALUES (S."ID", S."NAME", S."BALANCE")"#,
+        ),
+    );
+    assert_eq!(upsert.bindings().len(), 5);
+    assert!(UserRepository::upsert(&[]).is_none());
+    assert_eq!(UserRepository::delete(1).statement(), r#"DELETE FROM DB.PUBLIC.USERS WHERE "ID" = ?"#);
+
+    assert_eq!(UserRepository::list(&UserFilter::default()).statement(), r#"SELECT "ID", "NAME", "BALANCE" FROM DB.PUBLIC.USERS"#);
+    let list = UserRepository::list(&UserFilter { name: Some("a".into()), balance: Some(1.0), ..Default::default() });
+    assert_eq!(list.statement(), r#"SELECT "ID", "NAME", "BALANCE" FROM DB.PUBLIC.USERS WHERE "NAME" = ? AND "BALANCE" = ?"#);
+    assert_eq!(list.bindings().len(), 2);
+}
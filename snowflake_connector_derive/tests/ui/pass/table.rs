@@ -0,0 +1,17 @@
+use snowflake_connector_derive::SnowflakeDeserialize;
+use snowflake_deserializer::{*, table::SnowflakeTable};
+
+#[derive(SnowflakeDeserialize)]
+#[snowflake(table = "DB.PUBLIC.TEST_TABLE")]
+pub struct Test {
+    #[snowflake(rename = "ORDER")]
+    pub order: u32,
+    #[snowflake(data_type = "NUMBER(38,2)")]
+    pub amount: f64,
+}
+
+fn main() {
+    assert_eq!(Test::TABLE, "DB.PUBLIC.TEST_TABLE");
+    assert_eq!(Test::COLUMNS[0].name, "ORDER");
+    assert_eq!(Test::COLUMNS[1].data_type, "NUMBER(38,2)");
+}